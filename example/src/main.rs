@@ -18,7 +18,7 @@ use std::{env, io, fs::File, io::Read, io::Write};
      This is generally recommended as it provides better control and clarity.
  */
 use json_rust::{json_object::{ValueType, Key, JsonKeyPtr, JsonObject}, json::parser};
-use json_rust::helper::{traverse, TraverseError};
+use json_rust::helper::{traverse, PrintVisitor};
 
 fn main() -> Result<(), io::Error> {
 
@@ -31,20 +31,29 @@ fn main() -> Result<(), io::Error> {
 
     //traverse(&json_object);
 
-    match traverse(&json_object) {
-        Ok(()) => {
+    match json_object {
+        Ok(Some(jobj)) => {
 
-            println!("Traversal completed successfully");
-        },
-        Err(TraverseError::IoError(io_err_msg)) => {
+            match traverse(&jobj, &mut PrintVisitor) {
+                Ok(()) => {
 
-            eprintln!("IO error during traversal: {}", io_err_msg);
-            // Handle IO error specific logic
+                    println!("Traversal completed successfully");
+                },
+                Err(e) => {
+
+                    eprintln!("Error during traversal: {:?}", e);
+                }
+            }
         },
-        Err(TraverseError::NoJsonObject) => {
+        Ok(None) => {
             
             eprintln!("No JSON object found to traverse");
             // Handle the case where there's no JSON object
+        },
+        Err(e) => {
+
+            eprintln!("IO error during parsing: {}", e);
+            // Handle IO error specific logic
         }
     }
            
@@ -0,0 +1,570 @@
+/*
+    json-rust-derive/src/lib.rs
+    This file is part of the JSON-rust crate.
+    Written by, Q@khaa.pk
+ */
+
+/*
+    Derive macros of the JSON-rust crate, use them through json_rust::from_json and json_rust::to_json.
+    A struct with named fields is an object, a tuple struct an array, a newtype whatever its field is
+    and a unit struct null. Enums are tagged as described in src/to_json.rs of the JSON-rust crate.
+    Attributes go inside #[json(...)], on fields (rename, default, skip, flatten), on variants (rename)
+    and on enums (tag, content, untagged).
+ */
+
+use proc_macro::TokenStream;
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{parse_macro_input, parse_quote, Attribute, Data, DataEnum, DeriveInput, Error, ExprPath, Field, Fields, LitStr, Variant};
+
+/// Implements `json_rust::from_json::FromJson`.
+#[proc_macro_derive(FromJson, attributes(json))]
+pub fn derive_from_json(input: TokenStream) -> TokenStream {
+
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match from_json(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Implements `json_rust::to_json::ToJson`.
+#[proc_macro_derive(ToJson, attributes(json))]
+pub fn derive_to_json(input: TokenStream) -> TokenStream {
+
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match to_json(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+// What #[json(...)] says about a field
+#[derive(Default)]
+struct FieldAttributes {
+    rename: Option<String>,
+    default: Option<Option<ExprPath>>, // Some(None) for a bare `default`, Some(Some(f)) for `default = "f"`
+    skip: bool,
+    flatten: bool,
+}
+
+impl FieldAttributes {
+
+    fn parse(field: &Field) -> Result<Self, Error> {
+
+        let mut ret = FieldAttributes::default();
+
+        for attr in json_attributes(&field.attrs) {
+
+            attr.parse_nested_meta(|meta| {
+
+                if meta.path.is_ident("rename") {
+
+                    let name: LitStr = meta.value()?.parse()?;
+                    ret.rename = Some(name.value());
+                } else if meta.path.is_ident("default") {
+
+                    if meta.input.peek(syn::Token![=]) {
+
+                        let function: LitStr = meta.value()?.parse()?;
+                        ret.default = Some(Some(function.parse()?));
+                    } else {
+
+                        ret.default = Some(None);
+                    }
+                } else if meta.path.is_ident("skip") {
+
+                    ret.skip = true;
+                } else if meta.path.is_ident("flatten") {
+
+                    ret.flatten = true;
+                } else {
+
+                    return Err(meta.error("expected rename, default, skip or flatten"));
+                }
+
+                Ok(())
+            })?;
+        }
+
+        if ret.flatten && (ret.rename.is_some() || ret.default.is_some()) {
+
+            return Err(Error::new_spanned(field, "a flattened field has no member of its own to rename or default"));
+        }
+
+        Ok(ret)
+    }
+}
+
+// How the variants of an enum are told apart
+enum Tagging {
+    External,                 // {"Variant": content}
+    Internal(String),         // {"tag": "Variant", ...fields}
+    Adjacent(String, String), // {"tag": "Variant", "content": content}
+    Untagged,                 // content
+}
+
+impl Tagging {
+
+    fn parse(input: &DeriveInput) -> Result<Self, Error> {
+
+        let mut tag: Option<String> = None;
+        let mut content: Option<String> = None;
+        let mut untagged = false;
+
+        for attr in json_attributes(&input.attrs) {
+
+            attr.parse_nested_meta(|meta| {
+
+                if meta.path.is_ident("tag") {
+
+                    let name: LitStr = meta.value()?.parse()?;
+                    tag = Some(name.value());
+                } else if meta.path.is_ident("content") {
+
+                    let name: LitStr = meta.value()?.parse()?;
+                    content = Some(name.value());
+                } else if meta.path.is_ident("untagged") {
+
+                    untagged = true;
+                } else {
+
+                    return Err(meta.error("expected tag, content or untagged"));
+                }
+
+                Ok(())
+            })?;
+        }
+
+        match (tag, content, untagged) {
+
+            (None, None, false) => Ok(Tagging::External),
+            (Some(tag), None, false) => Ok(Tagging::Internal(tag)),
+            (Some(tag), Some(content), false) => Ok(Tagging::Adjacent(tag, content)),
+            (None, None, true) => Ok(Tagging::Untagged),
+            (None, Some(_), false) => Err(Error::new_spanned(&input.ident, "content needs a tag")),
+            _ => Err(Error::new_spanned(&input.ident, "an untagged enum has no tag or content")),
+        }
+    }
+}
+
+// The tag of a variant, its name unless #[json(rename = "...")] says otherwise
+fn variant_name(variant: &Variant) -> Result<String, Error> {
+
+    let mut ret = variant.ident.unraw().to_string();
+
+    for attr in json_attributes(&variant.attrs) {
+
+        attr.parse_nested_meta(|meta| {
+
+            if meta.path.is_ident("rename") {
+
+                let name: LitStr = meta.value()?.parse()?;
+                ret = name.value();
+
+                Ok(())
+            } else {
+
+                Err(meta.error("expected rename"))
+            }
+        })?;
+    }
+
+    Ok(ret)
+}
+
+fn json_attributes(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+
+    attrs.iter().filter(|attr| attr.path().is_ident("json"))
+}
+
+fn from_json(mut input: DeriveInput) -> Result<TokenStream2, Error> {
+
+    for param in input.generics.type_params_mut() {
+
+        param.bounds.push(parse_quote!(::json_rust::from_json::FromJson));
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let (body, missing) = match &input.data {
+
+        Data::Struct(data) => {
+
+            // A newtype is read as its field would be, a missing member included
+            let missing = match &data.fields {
+
+                Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => Some(quote! {
+                    fn from_missing(path: &::json_rust::pointer::Path) -> ::core::result::Result<Self, ::json_rust::from_json::FromJsonError> {
+                        ::json_rust::from_json::FromJson::from_missing(path).map(#name)
+                    }
+                }),
+                _ => None,
+            };
+
+            (fields_from_json(&data.fields, quote! { #name })?, missing)
+        },
+        Data::Enum(data) => (enum_from_json(&input, data)?, None),
+        Data::Union(_) => return Err(Error::new_spanned(&input.ident, "unions are not supported")),
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::json_rust::from_json::FromJson for #name #ty_generics #where_clause {
+            fn from_json(value: ::json_rust::from_json::Value<'_>, path: &::json_rust::pointer::Path) -> ::core::result::Result<Self, ::json_rust::from_json::FromJsonError> {
+                #body
+            }
+
+            #missing
+        }
+    })
+}
+
+// An expression reading `value` at `path` into `constructor`, a struct or a variant with the given fields
+fn fields_from_json(fields: &Fields, constructor: TokenStream2) -> Result<TokenStream2, Error> {
+
+    Ok(match fields {
+
+        Fields::Named(named) => {
+
+            let mut initializers = Vec::new();
+
+            for field in &named.named {
+
+                let attributes = FieldAttributes::parse(field)?;
+                let ident = field.ident.as_ref().unwrap();
+                let member = attributes.rename.clone().unwrap_or_else(|| ident.unraw().to_string());
+
+                let value = if attributes.skip {
+
+                    quote! { ::core::default::Default::default() }
+                } else if attributes.flatten {
+
+                    quote! { ::json_rust::from_json::FromJson::from_json(value, path)? }
+                } else {
+
+                    match &attributes.default {
+
+                        None => quote! { ::json_rust::from_json::member(value, #member, path)? },
+                        Some(None) => quote! { ::json_rust::from_json::member_or(value, #member, path, ::core::default::Default::default)? },
+                        Some(Some(function)) => quote! { ::json_rust::from_json::member_or(value, #member, path, #function)? },
+                    }
+                };
+
+                initializers.push(quote! { #ident: #value });
+            }
+
+            quote! {{
+                ::json_rust::from_json::expect_type(value, ::json_rust::json_object::ValueType::ObjectType, "an object", path)?;
+                ::core::result::Result::Ok(#constructor { #(#initializers),* })
+            }}
+        },
+        Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+
+            quote! {
+                ::json_rust::from_json::FromJson::from_json(value, path).map(#constructor)
+            }
+        },
+        Fields::Unnamed(unnamed) => {
+
+            let n = unnamed.unnamed.len();
+            let elements = (0..n).map(|i| quote! {
+                ::json_rust::from_json::FromJson::from_json(::json_rust::from_json::Value::Key(elements[#i]), &path.with_index(#i))?
+            });
+
+            quote! {{
+                ::json_rust::from_json::expect_type(value, ::json_rust::json_object::ValueType::ArrayType, "an array", path)?;
+
+                let elements: ::std::vec::Vec<_> = value.iter().collect();
+                if elements.len() != #n {
+                    return ::core::result::Result::Err(::json_rust::from_json::FromJsonError::new(path, ::json_rust::from_json::FromJsonErrorKind::Length(#n, elements.len())));
+                }
+
+                ::core::result::Result::Ok(#constructor(#(#elements),*))
+            }}
+        },
+        Fields::Unit => {
+
+            quote! {{
+                ::json_rust::from_json::expect_type(value, ::json_rust::json_object::ValueType::NullType, "null", path)?;
+                ::core::result::Result::Ok(#constructor)
+            }}
+        },
+    })
+}
+
+fn enum_from_json(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2, Error> {
+
+    let name = &input.ident;
+    let tagging = Tagging::parse(input)?;
+
+    let mut arms = Vec::new();
+
+    for variant in &data.variants {
+
+        let tag = variant_name(variant)?;
+        let ident = &variant.ident;
+        let body = fields_from_json(&variant.fields, quote! { #name::#ident })?;
+
+        arms.push(match &tagging {
+
+            // A unit variant is written as a string, it is also read from {"Variant": null}
+            Tagging::External => match &variant.fields {
+
+                Fields::Unit => quote! {
+                    #tag => match content {
+                        ::core::option::Option::Some(_) => {
+                            let path = &content_path;
+                            #body
+                        },
+                        ::core::option::Option::None => ::core::result::Result::Ok(#name::#ident),
+                    }
+                },
+                _ => quote! {
+                    #tag => match content {
+                        ::core::option::Option::Some(_) => {
+                            let path = &content_path;
+                            #body
+                        },
+                        ::core::option::Option::None => ::core::result::Result::Err(::json_rust::from_json::FromJsonError::new(path, ::json_rust::from_json::FromJsonErrorKind::Type("an object with a single member"))),
+                    }
+                },
+            },
+            Tagging::Internal(_) => match &variant.fields {
+
+                Fields::Named(_) => quote! { #tag => #body },
+                Fields::Unit => quote! { #tag => ::core::result::Result::Ok(#name::#ident) },
+                Fields::Unnamed(_) => return Err(Error::new_spanned(variant, "an internally tagged enum can only have unit and struct variants")),
+            },
+            Tagging::Adjacent(_, _) => match &variant.fields {
+
+                Fields::Unit => quote! { #tag => ::core::result::Result::Ok(#name::#ident) },
+                _ => quote! {
+                    #tag => {
+                        let path = &content_path;
+                        let value = match content {
+                            ::core::option::Option::Some(key) => ::json_rust::from_json::Value::Key(key),
+                            ::core::option::Option::None => return ::core::result::Result::Err(::json_rust::from_json::FromJsonError::new(path, ::json_rust::from_json::FromJsonErrorKind::Missing)),
+                        };
+                        #body
+                    }
+                },
+            },
+            Tagging::Untagged => quote! {
+                let attempt = (|| -> ::core::result::Result<Self, ::json_rust::from_json::FromJsonError> { #body })();
+                if attempt.is_ok() {
+                    return attempt;
+                }
+            },
+        });
+    }
+
+    let unknown = quote! {
+        _ => ::core::result::Result::Err(::json_rust::from_json::FromJsonError::new(path, ::json_rust::from_json::FromJsonErrorKind::UnknownVariant(tag)))
+    };
+
+    Ok(match &tagging {
+
+        Tagging::External => quote! {
+            let (tag, content) = match value.value_type() {
+                ::json_rust::json_object::ValueType::StringType => (<::std::string::String as ::json_rust::from_json::FromJson>::from_json(value, path)?, ::core::option::Option::None),
+                ::json_rust::json_object::ValueType::ObjectType if value.iter().count() == 1 => {
+                    let member = value.iter().next().unwrap();
+                    (::json_rust::from_json::member_name(member, path)?, ::core::option::Option::Some(member))
+                },
+                _ => return ::core::result::Result::Err(::json_rust::from_json::FromJsonError::new(path, ::json_rust::from_json::FromJsonErrorKind::Type("a string or an object with a single member"))),
+            };
+
+            let content_path = path.with_key(&tag);
+            let value = match content {
+                ::core::option::Option::Some(member) => ::json_rust::from_json::Value::Key(member),
+                ::core::option::Option::None => value,
+            };
+
+            match tag.as_str() {
+                #(#arms,)*
+                #unknown
+            }
+        },
+        Tagging::Internal(tag_member) => quote! {
+            ::json_rust::from_json::expect_type(value, ::json_rust::json_object::ValueType::ObjectType, "an object", path)?;
+
+            let tag: ::std::string::String = ::json_rust::from_json::member(value, #tag_member, path)?;
+
+            match tag.as_str() {
+                #(#arms,)*
+                #unknown
+            }
+        },
+        Tagging::Adjacent(tag_member, content_member) => quote! {
+            ::json_rust::from_json::expect_type(value, ::json_rust::json_object::ValueType::ObjectType, "an object", path)?;
+
+            let tag: ::std::string::String = ::json_rust::from_json::member(value, #tag_member, path)?;
+            #[allow(unused_variables)]
+            let content = value.get(#content_member);
+            #[allow(unused_variables)]
+            let content_path = path.with_key(#content_member);
+
+            match tag.as_str() {
+                #(#arms,)*
+                #unknown
+            }
+        },
+        Tagging::Untagged => {
+
+            let expected = format!("a value of {}", name);
+
+            quote! {
+                #(#arms)*
+
+                ::core::result::Result::Err(::json_rust::from_json::FromJsonError::new(path, ::json_rust::from_json::FromJsonErrorKind::Type(#expected)))
+            }
+        },
+    })
+}
+
+fn to_json(mut input: DeriveInput) -> Result<TokenStream2, Error> {
+
+    for param in input.generics.type_params_mut() {
+
+        param.bounds.push(parse_quote!(::json_rust::to_json::ToJson));
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let body = match &input.data {
+
+        Data::Struct(data) => {
+
+            let (pattern, content) = fields_to_json(&data.fields, quote! { #name })?;
+
+            quote! {
+                let #pattern = self;
+                #content
+            }
+        },
+        Data::Enum(data) => enum_to_json(&input, data)?,
+        Data::Union(_) => return Err(Error::new_spanned(&input.ident, "unions are not supported")),
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::json_rust::to_json::ToJson for #name #ty_generics #where_clause {
+            fn to_json(&self) -> ::json_rust::json_object::Key {
+                #body
+            }
+        }
+    })
+}
+
+// A pattern binding the fields of `constructor` by reference, and an expression building the Key they are written as
+fn fields_to_json(fields: &Fields, constructor: TokenStream2) -> Result<(TokenStream2, TokenStream2), Error> {
+
+    let binding = |i: usize| Ident::new(&format!("__field{}", i), Span::call_site());
+
+    Ok(match fields {
+
+        Fields::Named(named) => {
+
+            let mut bindings = Vec::new();
+            let mut members = Vec::new();
+
+            for (i, field) in named.named.iter().enumerate() {
+
+                let attributes = FieldAttributes::parse(field)?;
+
+                if attributes.skip {
+
+                    continue;
+                }
+
+                let ident = field.ident.as_ref().unwrap();
+                let member = attributes.rename.clone().unwrap_or_else(|| ident.unraw().to_string());
+                let var = binding(i);
+
+                bindings.push(quote! { #ident: #var });
+                members.push(if attributes.flatten {
+
+                    quote! { members.extend(::json_rust::to_json::flattened(#var)); }
+                } else {
+
+                    quote! { members.push(::json_rust::to_json::member(#member, #var)); }
+                });
+            }
+
+            (quote! { #constructor { #(#bindings,)* .. } }, quote! {{
+                let mut members: ::std::vec::Vec<::json_rust::json_object::Key> = ::std::vec::Vec::new();
+                #(#members)*
+                ::json_rust::to_json::object(members)
+            }})
+        },
+        Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+
+            let var = binding(0);
+
+            (quote! { #constructor(#var) }, quote! { ::json_rust::to_json::ToJson::to_json(#var) })
+        },
+        Fields::Unnamed(unnamed) => {
+
+            let vars: Vec<Ident> = (0..unnamed.unnamed.len()).map(binding).collect();
+
+            (quote! { #constructor(#(#vars),*) }, quote! {
+                ::json_rust::to_json::array(::std::vec![#(::json_rust::to_json::ToJson::to_json(#vars)),*])
+            })
+        },
+        Fields::Unit => (quote! { #constructor }, quote! { ::json_rust::to_json::ToJson::to_json(&()) }),
+    })
+}
+
+fn enum_to_json(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2, Error> {
+
+    let name = &input.ident;
+    let tagging = Tagging::parse(input)?;
+
+    let mut arms = Vec::new();
+
+    for variant in &data.variants {
+
+        let tag = variant_name(variant)?;
+        let ident = &variant.ident;
+        let (pattern, content) = fields_to_json(&variant.fields, quote! { #name::#ident })?;
+        let unit = matches!(variant.fields, Fields::Unit);
+
+        let value = match &tagging {
+
+            Tagging::External if unit => quote! { ::json_rust::to_json::ToJson::to_json(#tag) },
+            Tagging::External => quote! {
+                ::json_rust::to_json::object(::std::vec![::json_rust::to_json::named(#tag, #content)])
+            },
+            Tagging::Internal(tag_member) => match &variant.fields {
+
+                Fields::Named(_) => quote! { ::json_rust::to_json::tagged(#tag_member, #tag, #content) },
+                Fields::Unit => quote! {
+                    ::json_rust::to_json::object(::std::vec![::json_rust::to_json::member(#tag_member, #tag)])
+                },
+                Fields::Unnamed(_) => return Err(Error::new_spanned(variant, "an internally tagged enum can only have unit and struct variants")),
+            },
+            Tagging::Adjacent(tag_member, _) if unit => quote! {
+                ::json_rust::to_json::object(::std::vec![::json_rust::to_json::member(#tag_member, #tag)])
+            },
+            Tagging::Adjacent(tag_member, content_member) => quote! {
+                ::json_rust::to_json::object(::std::vec![
+                    ::json_rust::to_json::member(#tag_member, #tag),
+                    ::json_rust::to_json::named(#content_member, #content),
+                ])
+            },
+            Tagging::Untagged => content,
+        };
+
+        arms.push(quote! { #pattern => #value });
+    }
+
+    Ok(quote! {
+        match self {
+            #(#arms,)*
+        }
+    })
+}
@@ -0,0 +1,103 @@
+/*
+    json-rust-derive/tests/from_json.rs
+    This file is part of the JSON-rust crate.
+    Written by, Q@khaa.pk
+ */
+
+/*
+    What #[derive(FromJson)] expands to, driven through json_rust the way a user of the crate would.
+ */
+
+use json_rust::from_json::{from_json_object, FromJson, FromJsonErrorKind};
+use json_rust::json::{parse_str, ParserOptions};
+use json_rust::json_object::JsonObject;
+
+fn parse(text: &str) -> JsonObject {
+    *parse_str(text, &ParserOptions::default()).unwrap().unwrap()
+}
+
+fn seven() -> u32 {
+    7
+}
+
+#[derive(Debug, PartialEq, FromJson)]
+struct Image {
+    name: String,
+    #[json(rename = "w")]
+    width: u32,
+    #[json(default)]
+    tags: Vec<String>,
+    #[json(default = "seven")]
+    depth: u32,
+    #[json(skip)]
+    cached: Option<String>,
+    #[json(flatten)]
+    extra: Extra,
+    comment: Option<String>,
+    r#type: String,
+}
+
+#[derive(Debug, PartialEq, FromJson)]
+struct Extra {
+    origin: (i32, i32),
+}
+
+#[derive(Debug, PartialEq, FromJson)]
+struct Meters(f64);
+
+#[derive(Debug, PartialEq, FromJson)]
+struct Pair(u8, bool);
+
+#[derive(Debug, PartialEq, FromJson)]
+struct Nothing;
+
+#[test]
+fn field_attributes() {
+    let jobj = parse(r#"{"name": "png", "w": 1300, "origin": [-1, 2], "cached": "ignored", "type": "raster"}"#);
+
+    let image: Image = from_json_object(&jobj).unwrap();
+
+    assert_eq!(image, Image {
+        name: "png".to_string(),
+        width: 1300,
+        tags: Vec::new(),
+        depth: 7,
+        cached: None,
+        extra: Extra { origin: (-1, 2) },
+        comment: None,
+        r#type: "raster".to_string(),
+    });
+}
+
+#[test]
+fn struct_shapes() {
+    #[derive(Debug, PartialEq, FromJson)]
+    struct Shapes {
+        length: Meters,
+        pair: Pair,
+        nothing: Nothing,
+    }
+
+    let shapes: Shapes = from_json_object(&parse(r#"{"length": 2.5, "pair": [3, true], "nothing": null}"#)).unwrap();
+
+    assert_eq!(shapes, Shapes { length: Meters(2.5), pair: Pair(3, true), nothing: Nothing });
+}
+
+#[test]
+fn errors_carry_the_path() {
+    let missing = from_json_object::<Image>(&parse(r#"{"name": "png", "origin": [0, 0], "type": "raster"}"#)).unwrap_err();
+    assert_eq!(missing.to_string(), "/w: missing member");
+
+    let wrong = from_json_object::<Image>(&parse(r#"{"name": "png", "w": 1, "origin": [0, "x"], "type": "raster"}"#)).unwrap_err();
+    assert_eq!(wrong.pointer(), "/origin/1");
+    assert_eq!(wrong.kind, FromJsonErrorKind::Type("a number"));
+
+    #[derive(Debug, FromJson)]
+    #[allow(dead_code)]
+    struct Small {
+        n: u8,
+    }
+
+    let overflow = from_json_object::<Small>(&parse(r#"{"n": 300}"#)).unwrap_err();
+    assert_eq!(overflow.kind, FromJsonErrorKind::Number("300".to_string()));
+}
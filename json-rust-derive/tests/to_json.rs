@@ -0,0 +1,145 @@
+/*
+    json-rust-derive/tests/to_json.rs
+    This file is part of the JSON-rust crate.
+    Written by, Q@khaa.pk
+ */
+
+/*
+    What #[derive(ToJson)] writes, for each way of tagging an enum, and that #[derive(FromJson)] reads it back.
+ */
+
+use std::fmt::Debug;
+
+use json_rust::from_json::{from_json_object, FromJson, FromJsonErrorKind};
+use json_rust::json::{parse_str, ParserOptions};
+use json_rust::json_object::JsonObject;
+use json_rust::to_json::{to_json_object, ToJson};
+
+fn parse(text: &str) -> JsonObject {
+    *parse_str(text, &ParserOptions::default()).unwrap().unwrap()
+}
+
+// Writes the value as the member "v" of a document, checks the text, then reads both the tree and the text back
+fn round_trip<T: ToJson + FromJson + PartialEq + Debug>(value: T, expected: &str) {
+    #[derive(ToJson, FromJson)]
+    struct Document<T> {
+        v: T,
+    }
+
+    let jobj = to_json_object(&Document { v: value }).unwrap();
+    let text = jobj.to_json_string();
+    assert_eq!(text, format!(r#"{{"v":{}}}"#, expected));
+
+    let Document { v } = from_json_object::<Document<T>>(&jobj).unwrap();
+    let Document { v: reparsed } = from_json_object::<Document<T>>(&parse(&text)).unwrap();
+    assert_eq!(v, reparsed);
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+enum External {
+    Unit,
+    Newtype(u32),
+    Tuple(u32, String),
+    Struct { x: i32, y: i32 },
+    #[json(rename = "other")]
+    Renamed,
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+#[json(tag = "type")]
+enum Internal {
+    Unit,
+    Struct { x: i32, y: i32 },
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+#[json(tag = "t", content = "c")]
+enum Adjacent {
+    Unit,
+    Newtype(u32),
+    Tuple(u32, String),
+    Struct { x: i32, y: i32 },
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+#[json(untagged)]
+enum Untagged {
+    Number(u32),
+    Text(String),
+    Point { x: i32, y: i32 },
+    Nothing,
+}
+
+#[test]
+fn external() {
+    round_trip(External::Unit, r#""Unit""#);
+    round_trip(External::Newtype(1), r#"{"Newtype":1}"#);
+    round_trip(External::Tuple(1, "a".to_string()), r#"{"Tuple":[1,"a"]}"#);
+    round_trip(External::Struct { x: 1, y: -2 }, r#"{"Struct":{"x":1,"y":-2}}"#);
+    round_trip(External::Renamed, r#""other""#);
+}
+
+#[test]
+fn internal() {
+    round_trip(Internal::Unit, r#"{"type":"Unit"}"#);
+    round_trip(Internal::Struct { x: 1, y: -2 }, r#"{"type":"Struct","x":1,"y":-2}"#);
+}
+
+#[test]
+fn adjacent() {
+    // A unit variant has no content member
+    round_trip(Adjacent::Unit, r#"{"t":"Unit"}"#);
+    round_trip(Adjacent::Newtype(1), r#"{"t":"Newtype","c":1}"#);
+    round_trip(Adjacent::Tuple(1, "a".to_string()), r#"{"t":"Tuple","c":[1,"a"]}"#);
+    round_trip(Adjacent::Struct { x: 1, y: -2 }, r#"{"t":"Struct","c":{"x":1,"y":-2}}"#);
+}
+
+#[test]
+fn untagged() {
+    round_trip(Untagged::Number(1), "1");
+    round_trip(Untagged::Text("a".to_string()), r#""a""#);
+    round_trip(Untagged::Point { x: 1, y: -2 }, r#"{"x":1,"y":-2}"#);
+    round_trip(Untagged::Nothing, "null");
+}
+
+#[test]
+fn unknown_variants() {
+    #[derive(Debug, FromJson)]
+    #[allow(dead_code)]
+    struct Document {
+        e: External,
+        i: Internal,
+    }
+
+    let error = from_json_object::<Document>(&parse(r#"{"e": "Missing", "i": {"type": "Unit"}}"#)).unwrap_err();
+    assert_eq!(error.kind, FromJsonErrorKind::UnknownVariant("Missing".to_string()));
+
+    let error = from_json_object::<Document>(&parse(r#"{"e": "Unit", "i": {"type": "Missing"}}"#)).unwrap_err();
+    assert_eq!(error.pointer(), "/i");
+    assert_eq!(error.kind, FromJsonErrorKind::UnknownVariant("Missing".to_string()));
+}
+
+#[test]
+fn struct_attributes() {
+    #[derive(Debug, PartialEq, ToJson, FromJson)]
+    struct Inner {
+        a: u8,
+    }
+
+    #[derive(Debug, PartialEq, ToJson, FromJson)]
+    struct Outer {
+        #[json(rename = "n")]
+        name: String,
+        #[json(skip)]
+        cache: u32,
+        #[json(flatten)]
+        inner: Inner,
+        list: Vec<Option<bool>>,
+    }
+
+    let outer = Outer { name: "x".to_string(), cache: 0, inner: Inner { a: 1 }, list: vec![Some(true), None] };
+
+    let jobj = to_json_object(&outer).unwrap();
+    assert_eq!(jobj.to_json_string(), r#"{"n":"x","a":1,"list":[true,null]}"#);
+    assert_eq!(from_json_object::<Outer>(&jobj).unwrap(), outer);
+}
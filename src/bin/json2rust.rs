@@ -0,0 +1,97 @@
+/*
+    src/bin/json2rust.rs
+    This file is part of the JSON-rust crate.
+    Written by, Q@khaa.pk
+ */
+
+/*
+    Prints Rust structs and enums for the documents given on the command line, along with the code to load them.
+    Usage: json2rust [--name RootName] [--json5] [--keep-numbers] file.json [more.json ...] > types.rs
+    Every file is a sample of the same kind of document, members missing from some of them become Option<T>.
+    Samples have to be strict JSON, unless --json5 is given. --keep-numbers accepts literals that are not
+    JSON numbers (e.g. the "..." of a shortened example) instead of stopping at them, they are taken as numbers.
+    Without it, DOCUMENTS/very-complex-example.json stops with: invalid literal "..." at line 8, column 58
+ */
+
+use std::{env, process};
+
+use json_rust::codegen::generate;
+use json_rust::json::{NumberMode, ParseError};
+use json_rust::json_object::JsonObject;
+use json_rust::parser::Parser;
+
+fn main() {
+
+    let mut root_name = String::from("Root");
+    let mut files: Vec<String> = Vec::new();
+    let mut builder = Parser::builder();
+    let mut keep_numbers = false;
+
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+
+        match arg.as_str() {
+
+            "--name" => match args.next() {
+
+                Some(name) => root_name = name,
+                None => usage(),
+            },
+            "--json5" => builder = builder.json5(true),
+            "--keep-numbers" => {
+
+                builder = builder.numbers(NumberMode::Keep);
+                keep_numbers = true;
+            },
+            "-h" | "--help" => usage(),
+            _ => files.push(arg),
+        }
+    }
+
+    if files.is_empty() {
+
+        usage();
+    }
+
+    let mut parser = builder.build();
+    let mut samples: Vec<Box<JsonObject>> = Vec::new();
+
+    for file in &files {
+
+        match parser.parse_file(file) {
+
+            Ok(Some(jobj)) => samples.push(jobj),
+            Ok(None) => {
+
+                eprintln!("{}: no JSON object found", file);
+                process::exit(1);
+            },
+            Err(e) => {
+
+                eprintln!("{}: {}", file, e);
+
+                if matches!(e, ParseError::InvalidNumber(_, _)) && !keep_numbers {
+
+                    eprintln!("if the sample is a shortened example, try again with --keep-numbers");
+                }
+
+                process::exit(1);
+            },
+        }
+    }
+
+    let samples: Vec<&JsonObject> = samples.iter().map(|jobj| jobj.as_ref()).collect();
+
+    print!("{}", generate(&samples, &root_name));
+}
+
+fn usage() -> ! {
+
+    eprintln!("Usage: json2rust [--name RootName] [--json5] [--keep-numbers] file.json [more.json ...]");
+    eprintln!("  --json5         read the samples as JSON5 instead of strict JSON");
+    eprintln!("  --keep-numbers  take literals that are not JSON numbers as numbers instead of failing");
+    eprintln!("                  shortened examples need it, e.g. the ... placeholders of DOCUMENTS/very-complex-example.json");
+    eprintln!("                  otherwise stop the tool with: invalid literal \"...\" at line 8, column 58");
+    process::exit(2);
+}
@@ -0,0 +1,354 @@
+/*
+    src/canonical.rs
+    This file is part of the JSON-rust crate.
+    Written by, Q@khaa.pk
+ */
+
+/*
+    Canonical serialization of a parsed JSON tree, as defined by RFC 8785 (JSON Canonicalization Scheme, JCS).
+    - Members of every object are sorted by their names, compared as arrays of UTF-16 code units.
+    - Numbers are written the way ECMAScript's Number.prototype.toString() writes them (shortest round trip).
+    - Strings use the minimal escaping of the JCS specification.
+    - No whitespace is emitted anywhere.
+    Two documents that differ only in member order, number spelling ("1.0" vs "1", "1e2" vs "100") or
+    escaping ("\u0041" vs "A") produce byte identical output, which makes the output suitable for hashing and signing.
+ */
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::constants::CAPTURE_NUMBER_PATTERN;
+use crate::escape::{escape, unescape, EscapeError};
+use crate::json_object::{ValueType, Key, JsonObject};
+
+/// Errors that can occur while producing the canonical form of a JSON tree.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CanonicalError {
+    InvalidNumber(String),              // The value is not a JSON number, or it does not fit in an IEEE 754 double (e.g. 1e400)
+    InvalidString(String, EscapeError), // The raw text of a name or a string value has a bad escape sequence
+    DuplicateKey(String),               // JCS requires member names to be unique within an object
+}
+
+/// Produces the RFC 8785 canonical form of a JSON object.
+///
+/// # Arguments
+/// * `jobj` - The root object, as returned by `json::parser`
+///
+/// # Returns
+/// * `Ok(String)` holding the canonical text
+/// * `Err(CanonicalError)` if the tree holds something JCS cannot represent
+pub fn to_canonical_string(jobj: &JsonObject) -> Result<String, CanonicalError> {
+
+    let mut ret = String::new();
+
+    write_object(&mut ret, jobj.iter())?;
+
+    Ok(ret)
+}
+
+/// Produces the RFC 8785 canonical form of the value of a single key.
+/// The name of the key itself is not part of the output.
+///
+/// # Arguments
+/// * `key` - The key whose value is serialized
+pub fn key_to_canonical_string(key: &Key) -> Result<String, CanonicalError> {
+
+    let mut ret = String::new();
+
+    write_value(&mut ret, key)?;
+
+    Ok(ret)
+}
+
+/// Formats a JSON number the way ECMAScript's Number.prototype.toString() does.
+///
+/// # Arguments
+/// * `raw` - The text of the number, as stored in `Key::get_value()`
+///
+/// # Examples
+/// `1.0` becomes `1`, `1E3` becomes `1000`, `0.0000001` becomes `1e-7` and `1e21` becomes `1e+21`.
+pub fn format_number(raw: &str) -> Result<String, CanonicalError> {
+
+    static NUMBER: OnceLock<Regex> = OnceLock::new();
+
+    let raw = raw.trim();
+
+    let number = NUMBER.get_or_init(|| Regex::new(&format!("^{}$", CAPTURE_NUMBER_PATTERN)).unwrap());
+
+    if !number.is_match(raw) {
+
+        return Err(CanonicalError::InvalidNumber(raw.to_string()));
+    }
+
+    let value: f64 = raw.parse().map_err(|_| CanonicalError::InvalidNumber(raw.to_string()))?;
+
+    if !value.is_finite() {
+
+        return Err(CanonicalError::InvalidNumber(raw.to_string()));
+    }
+
+    Ok(format_f64(value))
+}
+
+/*
+    Rust's "{:e}" formatting already yields the shortest digit string that round trips, what remains is
+    to lay those digits out following the rules of the ECMAScript Number::toString abstract operation.
+    With k digits and the decimal point n places from the left of them...
+ */
+fn format_f64(value: f64) -> String {
+
+    // Covers -0 as well
+    if value == 0.0 {
+
+        return "0".to_string();
+    }
+
+    let sci = format!("{:e}", value.abs());
+    let (mantissa, exponent) = sci.split_once('e').unwrap();
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let exponent: i32 = exponent.parse().unwrap();
+    let digits = even_on_tie(value.abs(), digits, exponent);
+
+    let k = digits.len() as i32;
+    let n = exponent + 1;
+
+    let mut ret = String::new();
+
+    if value < 0.0 {
+
+        ret.push('-');
+    }
+
+    if k <= n && n <= 21 {
+
+        // Integer, padded with zeros
+        ret.push_str(&digits);
+        ret.push_str(&"0".repeat((n - k) as usize));
+    } else if 0 < n && n <= 21 {
+
+        // Decimal point inside the digits
+        ret.push_str(&digits[..n as usize]);
+        ret.push('.');
+        ret.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+
+        // Small number, leading zeros after the decimal point
+        ret.push_str("0.");
+        ret.push_str(&"0".repeat((-n) as usize));
+        ret.push_str(&digits);
+    } else {
+
+        // Exponential notation
+        ret.push_str(&digits[..1]);
+
+        if k > 1 {
+
+            ret.push('.');
+            ret.push_str(&digits[1..]);
+        }
+
+        ret.push('e');
+        ret.push(if n - 1 < 0 { '-' } else { '+' });
+        ret.push_str(&(n - 1).abs().to_string());
+    }
+
+    ret
+}
+
+/*
+    When the value lies exactly halfway between two shortest digit strings that both read back as it,
+    e.g. 1424953923781206.25, Rust takes the upper one and ECMAScript the even one (1424953923781206.2).
+ */
+fn even_on_tie(value: f64, digits: String, exponent: i32) -> String {
+
+    let k = digits.len();
+
+    // One digit more than the shortest form, a tie shows up as a 5 there
+    let sci = format!("{:.*e}", k, value);
+    let (mantissa, e) = sci.split_once('e').unwrap();
+
+    if !mantissa.ends_with('5') || e.parse::<i32>() != Ok(exponent) {
+
+        return digits;
+    }
+
+    // Only a tie if the exact expansion stops at that 5, every double has fewer than 800 significant digits
+    let exact = format!("{:.800e}", value);
+    let (exact_mantissa, _) = exact.split_once('e').unwrap();
+    let exact_digits: String = exact_mantissa.chars().filter(|c| *c != '.').collect();
+    let exact_digits = exact_digits.trim_end_matches('0');
+
+    if exact_digits.len() != k + 1 {
+
+        return digits;
+    }
+
+    let lower = &exact_digits[..k];
+
+    let is_even = lower.ends_with(['0', '2', '4', '6', '8']);
+    let reads_back = format!("0.{}e{}", lower, exponent + 1).parse::<f64>() == Ok(value);
+
+    if lower != digits && is_even && reads_back { lower.to_string() } else { digits }
+}
+
+fn write_string(out: &mut String, raw: &str) -> Result<(), CanonicalError> {
+
+    let decoded = unescape(raw).map_err(|e| CanonicalError::InvalidString(raw.to_string(), e))?;
+
+    out.push('"');
+    out.push_str(&escape(&decoded));
+    out.push('"');
+
+    Ok(())
+}
+
+fn write_object<'a>(out: &mut String, members: impl Iterator<Item = &'a Key>) -> Result<(), CanonicalError> {
+
+    // Decode the names first, the sort order is defined on the actual characters and not on their escaped form
+    let mut sorted: Vec<(Vec<u16>, String, &Key)> = Vec::new();
+
+    for member in members {
+
+        let name = unescape(member.get_name()).map_err(|e| CanonicalError::InvalidString(member.get_name().to_string(), e))?;
+
+        sorted.push((name.encode_utf16().collect(), name, member));
+    }
+
+    sorted.sort_by(|a, b| a.0.cmp(&b.0));
+
+    if let Some(pair) = sorted.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+
+        return Err(CanonicalError::DuplicateKey(pair[0].1.clone()));
+    }
+
+    out.push('{');
+
+    for (i, (_, name, member)) in sorted.iter().enumerate() {
+
+        if i > 0 {
+
+            out.push(',');
+        }
+
+        out.push('"');
+        out.push_str(&escape(name));
+        out.push_str("\":");
+
+        write_value(out, member)?;
+    }
+
+    out.push('}');
+
+    Ok(())
+}
+
+fn write_value(out: &mut String, key: &Key) -> Result<(), CanonicalError> {
+
+    match key.get_value_type() {
+
+        ValueType::StringType => write_string(out, key.get_value())?,
+        ValueType::NumberType => out.push_str(&format_number(key.get_value())?),
+        ValueType::BooleanType => out.push_str(key.get_value().trim()),
+        ValueType::NullType => out.push_str("null"),
+        ValueType::ObjectType => write_object(out, key.iter())?,
+        ValueType::ArrayType => {
+
+            out.push('[');
+
+            for (i, element) in key.iter().enumerate() {
+
+                if i > 0 {
+
+                    out.push(',');
+                }
+
+                write_value(out, element)?;
+            }
+
+            out.push(']');
+        },
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::{parse_str, ParserOptions};
+
+    fn parse(text: &str) -> JsonObject {
+        *parse_str(text, &ParserOptions::default()).unwrap().unwrap()
+    }
+
+    // RFC 8785 Appendix B, the IEEE 754 bits of each number and its canonical text
+    #[test]
+    fn rfc8785_numbers() {
+        let cases: [(u64, &str); 24] = [
+            (0x0000000000000000, "0"),
+            (0x8000000000000000, "0"),
+            (0x0000000000000001, "5e-324"),
+            (0x8000000000000001, "-5e-324"),
+            (0x7fefffffffffffff, "1.7976931348623157e+308"),
+            (0xffefffffffffffff, "-1.7976931348623157e+308"),
+            (0x4340000000000000, "9007199254740992"),
+            (0xc340000000000000, "-9007199254740992"),
+            (0x4430000000000000, "295147905179352830000"),
+            (0x44b52d02c7e14af5, "9.999999999999997e+22"),
+            (0x44b52d02c7e14af6, "1e+23"),
+            (0x44b52d02c7e14af7, "1.0000000000000001e+23"),
+            (0x444b1ae4d6e2ef4e, "999999999999999700000"),
+            (0x444b1ae4d6e2ef4f, "999999999999999900000"),
+            (0x444b1ae4d6e2ef50, "1e+21"),
+            (0x3eb0c6f7a0b5ed8c, "9.999999999999997e-7"),
+            (0x3eb0c6f7a0b5ed8d, "0.000001"),
+            (0x41b3de4355555553, "333333333.3333332"),
+            (0x41b3de4355555554, "333333333.33333325"),
+            (0x41b3de4355555555, "333333333.3333333"),
+            (0x41b3de4355555556, "333333333.3333334"),
+            (0x41b3de4355555557, "333333333.33333343"),
+            (0xbecbf647612f3696, "-0.0000033333333333333333"),
+            (0x43143ff3c1cb0959, "1424953923781206.2"),
+        ];
+
+        for (bits, expected) in cases {
+            // Display writes every digit, without an exponent
+            let raw = f64::from_bits(bits).to_string();
+
+            assert_eq!(format_number(&raw).unwrap(), expected, "{:016x}", bits);
+            assert_eq!(format_number(expected).unwrap(), expected);
+        }
+
+        assert_eq!(format_number("1E3").unwrap(), "1000");
+        assert_eq!(format_number("0.125").unwrap(), "0.125");
+        assert_eq!(format_number("1e400"), Err(CanonicalError::InvalidNumber("1e400".to_string())));
+        assert_eq!(format_number("01"), Err(CanonicalError::InvalidNumber("01".to_string())));
+    }
+
+    // RFC 8785 3.2.2.2
+    #[test]
+    fn rfc8785_strings() {
+        let key = Key::new(String::new(), ValueType::StringType, r#"\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/"#.to_string());
+
+        assert_eq!(key_to_canonical_string(&key).unwrap(), "\"\u{20ac}$\\u000f\\nA'B\\\"\\\\\\\\\\\"/\"");
+    }
+
+    // RFC 8785 3.2.3, names are sorted by their UTF-16 code units
+    #[test]
+    fn rfc8785_sorting() {
+        let jobj = parse(r#"{"\u20ac": "Euro Sign", "\r": "Carriage Return", "\ufb33": "Hebrew Letter Dalet With Dagesh", "1": "One", "\ud83d\ude00": "Emoji: Grinning Face", "\u0080": "Control", "\u00f6": "Latin Small Letter O With Diaeresis"}"#);
+
+        assert_eq!(
+            to_canonical_string(&jobj).unwrap(),
+            "{\"\\r\":\"Carriage Return\",\"1\":\"One\",\"\u{80}\":\"Control\",\"\u{f6}\":\"Latin Small Letter O With Diaeresis\",\"\u{20ac}\":\"Euro Sign\",\"\u{1f600}\":\"Emoji: Grinning Face\",\"\u{fb33}\":\"Hebrew Letter Dalet With Dagesh\"}"
+        );
+    }
+
+    #[test]
+    fn duplicate_names_are_refused() {
+        let jobj = parse(r#"{"a": 1, "a": 2}"#);
+
+        assert_eq!(to_canonical_string(&jobj), Err(CanonicalError::DuplicateKey("a".to_string())));
+    }
+}
@@ -0,0 +1,556 @@
+/*
+    src/codegen.rs
+    This file is part of the JSON-rust crate.
+    Written by, Q@khaa.pk
+ */
+
+/*
+    Rust code generation from sample documents, or from a JSON Schema.
+    The samples go through schema::infer() first, the schema is then turned into type definitions:
+        objects -> structs, named after the member holding them
+        arrays -> Vec<T>
+        strings taking a few repeated values ("enum" in the schema) -> enums with a variant per value
+        members holding more than one type -> enums with a variant per type
+        nullable or optional members -> Option<T>
+        anything else (members always null, elements of arrays always empty) -> Key, as it is in the tree
+    Every struct and enum gets a from_key() that loads it from the Key tree, errors name the JSON Pointer of
+    the offending value. The root struct also gets from_json_object(), which takes what json::parser returns.
+    Only the keywords produced by infer() are understood, other schemas ($ref, ...) fall back to Key.
+ */
+
+use crate::escape::unescape_lossy;
+use crate::json_object::{ValueType, Key, JsonObject, Iter};
+use crate::pointer::escape_token;
+use crate::schema::infer;
+
+// Names the generated code uses for types of its own, or imports
+const RESERVED_NAMES: [&str; 9] = ["String", "Vec", "Option", "Result", "Box", "Key", "JsonObject", "ValueType", "Iter"];
+
+const KEYWORDS: [&str; 51] = [
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for", "if", "impl", "in",
+    "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super",
+    "trait", "true", "type", "unsafe", "use", "where", "while", "async", "await", "dyn", "abstract", "become", "box",
+    "do", "final", "macro", "override", "priv", "typeof", "unsized", "virtual", "yield", "try",
+];
+
+// Functions the generated code calls to load values, only the ones it calls are emitted
+const LOADERS: [(&str, &str); 8] = [
+    ("json_string", "fn json_string(key: &Key, path: &str) -> Result<String, String> {
+    match key.get_value_type() {
+        ValueType::StringType => Ok(unescape_lossy(key.get_value())),
+        _ => Err(format!(\"{}: expected a string\", path)),
+    }
+}"),
+    ("json_i64", "fn json_i64(key: &Key, path: &str) -> Result<i64, String> {
+    match key.get_value_type() {
+        ValueType::NumberType => key.get_value().trim().parse().map_err(|_| format!(\"{}: expected an integer\", path)),
+        _ => Err(format!(\"{}: expected an integer\", path)),
+    }
+}"),
+    ("json_f64", "fn json_f64(key: &Key, path: &str) -> Result<f64, String> {
+    match key.get_value_type() {
+        ValueType::NumberType => key.get_value().trim().parse().map_err(|_| format!(\"{}: expected a number\", path)),
+        _ => Err(format!(\"{}: expected a number\", path)),
+    }
+}"),
+    ("json_bool", "fn json_bool(key: &Key, path: &str) -> Result<bool, String> {
+    match key.get_value_type() {
+        ValueType::BooleanType => Ok(key.get_value().trim() == \"true\"),
+        _ => Err(format!(\"{}: expected a boolean\", path)),
+    }
+}"),
+    ("json_key", "fn json_key(key: &Key, _path: &str) -> Result<Key, String> {
+    Ok(key.clone_detached())
+}"),
+    ("json_members", "fn json_members<'a>(key: &'a Key, path: &str) -> Result<Iter<'a>, String> {
+    match key.get_value_type() {
+        ValueType::ObjectType => Ok(key.iter()),
+        _ => Err(format!(\"{}: expected an object\", path)),
+    }
+}"),
+    ("json_vec", "fn json_vec<T>(key: &Key, path: &str, load: impl Fn(&Key, &str) -> Result<T, String>) -> Result<Vec<T>, String> {
+    match key.get_value_type() {
+        ValueType::ArrayType => key.iter().enumerate().map(|(index, element)| load(element, &format!(\"{}/{}\", path, index))).collect(),
+        _ => Err(format!(\"{}: expected an array\", path)),
+    }
+}"),
+    ("json_option", "fn json_option<T>(key: &Key, path: &str, load: impl Fn(&Key, &str) -> Result<T, String>) -> Result<Option<T>, String> {
+    match key.get_value_type() {
+        ValueType::NullType => Ok(None),
+        _ => load(key, path).map(Some),
+    }
+}"),
+];
+
+/// Generates Rust types, and the code to load them, from sample documents.
+///
+/// # Arguments
+/// * `samples` - Parsed documents, the more the better, see `schema::infer`
+/// * `root_name` - Name of the struct for the whole document
+///
+/// # Returns
+/// The source code of a Rust module
+pub fn generate(samples: &[&JsonObject], root_name: &str) -> String {
+
+    generate_from_schema(&infer(samples), root_name)
+}
+
+/// Generates Rust types, and the code to load them, from a JSON Schema as produced by `schema::infer`.
+///
+/// # Arguments
+/// * `schema` - The schema of the documents
+/// * `root_name` - Name of the struct for the whole document
+///
+/// # Returns
+/// The source code of a Rust module
+pub fn generate_from_schema(schema: &JsonObject, root_name: &str) -> String {
+
+    let mut generator = Generator { used_names: RESERVED_NAMES.iter().map(|name| name.to_string()).collect(), definitions: Vec::new() };
+
+    let root = generator.object(&pascal_case(root_name), "", schema.iter());
+
+    // The root struct takes what json::parser returns
+    generator.definitions[0].push_str(&format!("
+
+impl {} {{
+    /// Loads from a parsed document.
+    pub fn from_json_object(jobj: &JsonObject) -> Result<Self, String> {{
+        Self::from_members(jobj.iter(), \"\")
+    }}
+}}", root));
+
+    let definitions = generator.definitions.join("\n\n");
+
+    let mut ret = String::from("// Generated by json_rust::codegen, edit as needed.\n\n");
+    ret.push_str("#![allow(dead_code)]\n\n");
+    ret.push_str("use json_rust::escape::unescape_lossy;\n");
+    ret.push_str("use json_rust::json_object::{JsonObject, Key, ValueType, Iter};\n\n");
+    ret.push_str(&definitions);
+
+    for (name, code) in LOADERS {
+
+        if definitions.contains(&format!("{}(", name)) || definitions.contains(&format!(", {})", name)) {
+
+            ret.push_str("\n\n");
+            ret.push_str(code);
+        }
+    }
+
+    ret.push('\n');
+
+    ret
+}
+
+// A Rust type for the values at one location of the schema
+enum Type {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    Any,             // Key
+    Named(String),   // A generated struct or enum
+    Vec(Box<Type>),
+    Option(Box<Type>),
+}
+
+impl Type {
+    fn rust(&self) -> String {
+
+        match self {
+
+            Type::String => "String".to_string(),
+            Type::Integer => "i64".to_string(),
+            Type::Number => "f64".to_string(),
+            Type::Boolean => "bool".to_string(),
+            Type::Any => "Key".to_string(),
+            Type::Named(name) => name.clone(),
+            Type::Vec(inner) => format!("Vec<{}>", inner.rust()),
+            Type::Option(inner) => format!("Option<{}>", inner.rust()),
+        }
+    }
+
+    // An expression loading a value of this type from `key`, found at `path`
+    fn load(&self, key: &str, path: &str) -> String {
+
+        match self {
+
+            Type::Vec(inner) => format!("json_vec({}, {}, {})", key, path, inner.loader()),
+            Type::Option(inner) => format!("json_option({}, {}, {})", key, path, inner.loader()),
+            _ => format!("{}({}, {})", self.loader(), key, path),
+        }
+    }
+
+    // A function, or a closure, that loads a value of this type
+    fn loader(&self) -> String {
+
+        match self {
+
+            Type::String => "json_string".to_string(),
+            Type::Integer => "json_i64".to_string(),
+            Type::Number => "json_f64".to_string(),
+            Type::Boolean => "json_bool".to_string(),
+            Type::Any => "json_key".to_string(),
+            Type::Named(name) => format!("{}::from_key", name),
+            Type::Vec(_) | Type::Option(_) => format!("|key: &Key, path: &str| {}", self.load("key", "path")),
+        }
+    }
+}
+
+struct Generator {
+    used_names: Vec<String>,
+    definitions: Vec<String>,
+}
+
+impl Generator {
+    // A type name nobody uses yet, the parent's name goes in front of names already taken
+    fn unique_name(&mut self, wanted: &str, parent: &str) -> String {
+
+        let mut name = wanted.to_string();
+
+        if self.used_names.contains(&name) {
+
+            name = format!("{}{}", parent, wanted);
+        }
+
+        let base = name.clone();
+        let mut suffix = 2;
+
+        while self.used_names.contains(&name) {
+
+            name = format!("{}{}", base, suffix);
+            suffix += 1;
+        }
+
+        self.used_names.push(name.clone());
+
+        name
+    }
+
+    // The type for a schema, `name` is what to call a struct or an enum if one is needed
+    fn schema_type(&mut self, schema: Iter<'_>, name: &str, parent: &str) -> Type {
+
+        let keywords: Vec<&Key> = schema.clone().collect();
+        let keyword = |wanted: &str| keywords.iter().find(|key| unescape_lossy(key.get_name()) == wanted).copied();
+
+        let types: Vec<String> = match keyword("type") {
+
+            Some(key) if *key.get_value_type() == ValueType::ArrayType => key.iter().map(|t| unescape_lossy(t.get_value())).collect(),
+            Some(key) => vec![unescape_lossy(key.get_value())],
+            None => Vec::new(),
+        };
+
+        let nullable = types.iter().any(|t| t == "null");
+        let others: Vec<&str> = types.iter().map(String::as_str).filter(|t| *t != "null").collect();
+
+        let ret = match (others.as_slice(), keyword("enum")) {
+
+            (["string"], Some(values)) if values.iter().all(|value| matches!(value.get_value_type(), ValueType::StringType | ValueType::NullType)) => {
+
+                Type::Named(self.string_enum(name, parent, values))
+            },
+            ([], _) => return Type::Any,
+            (["string"], _) => Type::String,
+            (["integer"], _) => Type::Integer,
+            (["number"], _) => Type::Number,
+            (["boolean"], _) => Type::Boolean,
+            (["object"], _) => Type::Named(self.object(name, parent, schema)),
+            (["array"], _) => Type::Vec(Box::new(self.items(keyword("items"), name))),
+            _ => Type::Named(self.union(name, parent, &others, schema, keyword("items"))),
+        };
+
+        if nullable { Type::Option(Box::new(ret)) } else { ret }
+    }
+
+    fn items(&mut self, items: Option<&Key>, name: &str) -> Type {
+
+        match items {
+
+            Some(items) if *items.get_value_type() == ValueType::ObjectType => self.schema_type(items.iter(), &singular(name), name),
+            _ => Type::Any,
+        }
+    }
+
+    // A struct for an object schema, `schema` holds its keywords, the members of the root object or of a Key
+    fn object(&mut self, name: &str, parent: &str, schema: Iter<'_>) -> String {
+
+        let name = self.unique_name(name, parent);
+
+        // Reserve the place of the definition, nested ones come after it
+        let index = self.definitions.len();
+        self.definitions.push(String::new());
+
+        let keywords: Vec<&Key> = schema.collect();
+        let keyword = |wanted: &str| keywords.iter().find(|key| unescape_lossy(key.get_name()) == wanted).copied();
+
+        // Schemas of objects hold "properties", the root schema holds them directly
+        let properties: Vec<&Key> = keyword("properties").map(|key| key.iter().collect()).unwrap_or_default();
+        let required: Vec<String> = keyword("required").map(|key| key.iter().map(|name| unescape_lossy(name.get_value())).collect()).unwrap_or_default();
+
+        let mut fields = String::new();
+        let mut locals = String::new();
+        let mut arms = String::new();
+        let mut values = String::new();
+        let mut field_names: Vec<String> = Vec::new();
+
+        for property in properties {
+
+            let member = unescape_lossy(property.get_name());
+            let field = unique_field(&snake_case(&member), &field_names);
+            let local = format!("member_{}", field.trim_start_matches("r#"));
+            let member_type = self.schema_type(property.iter(), &pascal_case(&member), &name);
+            let path = format!("&format!(\"{{}}/{{}}\", path, {:?})", escape_token(&member));
+
+            field_names.push(field.clone());
+
+            let (field_type, value) = if required.contains(&member) {
+
+                (member_type.rust(), format!("match {} {{ Some(key) => {}?, None => return Err(format!(\"{{}}: missing member {{:?}}\", path, {:?})) }}", local, member_type.load("key", &path), member))
+            } else if let Type::Option(_) = member_type {
+
+                (member_type.rust(), format!("match {} {{ Some(key) => {}?, None => None }}", local, member_type.load("key", &path)))
+            } else {
+
+                (format!("Option<{}>", member_type.rust()), format!("match {} {{ Some(key) => Some({}?), None => None }}", local, member_type.load("key", &path)))
+            };
+
+            // The name of the member, when it is not the name of the field
+            let comment = if field.trim_start_matches("r#") == member { String::new() } else { format!(" // {:?}", member) };
+
+            fields.push_str(&format!("    pub {}: {},{}\n", field, field_type, comment));
+            locals.push_str(&format!("        let mut {} = None;\n", local));
+            arms.push_str(&format!("                {:?} => {} = Some(member),\n", member, local));
+            values.push_str(&format!("            {}: {},\n", field, value));
+        }
+
+        // An object that never had any member
+        let body = if field_names.is_empty() {
+
+            format!("    pub fn from_members(_members: Iter<'_>, _path: &str) -> Result<Self, String> {{
+        Ok({name} {{}})
+    }}")
+        } else {
+
+            format!("    pub fn from_members(members: Iter<'_>, path: &str) -> Result<Self, String> {{
+{locals}
+        for member in members {{
+            match unescape_lossy(member.get_name()).as_str() {{
+{arms}                _ => {{}},
+            }}
+        }}
+
+        Ok({name} {{
+{values}        }})
+    }}")
+        };
+
+        self.definitions[index] = format!("#[derive(Debug, Clone, PartialEq)]
+pub struct {name} {{
+{fields}}}
+
+impl {name} {{
+    /// Loads from a key holding an object, `path` is the JSON Pointer of the key, for error messages.
+    pub fn from_key(key: &Key, path: &str) -> Result<Self, String> {{
+        Self::from_members(json_members(key, path)?, path)
+    }}
+
+    /// Loads from the members of an object.
+{body}
+}}");
+
+        name
+    }
+
+    // An enum with a variant for each of the strings
+    fn string_enum(&mut self, name: &str, parent: &str, values: &Key) -> String {
+
+        let name = self.unique_name(name, parent);
+
+        let mut variants = String::new();
+        let mut arms = String::new();
+        let mut variant_names: Vec<String> = Vec::new();
+
+        for value in values.iter().filter(|value| *value.get_value_type() == ValueType::StringType) {
+
+            let text = unescape_lossy(value.get_value());
+
+            let mut variant = pascal_case(&text);
+            let base = variant.clone();
+            let mut suffix = 2;
+
+            while variant_names.contains(&variant) {
+
+                variant = format!("{}{}", base, suffix);
+                suffix += 1;
+            }
+
+            variants.push_str(&format!("    {}, // {:?}\n", variant, text));
+            arms.push_str(&format!("            {:?} => Ok({}::{}),\n", text, name, variant));
+            variant_names.push(variant);
+        }
+
+        self.definitions.push(format!("#[derive(Debug, Clone, PartialEq)]
+pub enum {name} {{
+{variants}}}
+
+impl {name} {{
+    /// Loads from a key holding one of its strings, `path` is the JSON Pointer of the key, for error messages.
+    pub fn from_key(key: &Key, path: &str) -> Result<Self, String> {{
+        match json_string(key, path)?.as_str() {{
+{arms}            other => Err(format!(\"{{}}: unexpected value {{:?}}\", path, other)),
+        }}
+    }}
+}}"));
+
+        name
+    }
+
+    // An enum with a variant for each of the types
+    fn union(&mut self, name: &str, parent: &str, types: &[&str], schema: Iter<'_>, items: Option<&Key>) -> String {
+
+        let name = self.unique_name(name, parent);
+
+        let index = self.definitions.len();
+        self.definitions.push(String::new());
+
+        let mut variants = String::new();
+        let mut arms = String::new();
+
+        for t in types {
+
+            let (variant, value_type, variant_type) = match *t {
+
+                "string" => ("String", "StringType", Type::String),
+                "integer" => ("Integer", "NumberType", Type::Integer),
+                "number" => ("Number", "NumberType", Type::Number),
+                "boolean" => ("Boolean", "BooleanType", Type::Boolean),
+                "object" => ("Object", "ObjectType", Type::Named(self.object(&format!("{}Object", name), &name, schema.clone()))),
+                "array" => ("Array", "ArrayType", Type::Vec(Box::new(self.items(items, &name)))),
+                _ => continue,
+            };
+
+            variants.push_str(&format!("    {}({}),\n", variant, variant_type.rust()));
+            arms.push_str(&format!("            ValueType::{} => Ok({}::{}({}?)),\n", value_type, name, variant, variant_type.load("key", "path")));
+        }
+
+        self.definitions[index] = format!("#[derive(Debug, Clone, PartialEq)]
+pub enum {name} {{
+{variants}}}
+
+impl {name} {{
+    /// Loads from a key holding any of its types, `path` is the JSON Pointer of the key, for error messages.
+    pub fn from_key(key: &Key, path: &str) -> Result<Self, String> {{
+        match key.get_value_type() {{
+{arms}            _ => Err(format!(\"{{}}: unexpected type\", path)),
+        }}
+    }}
+}}");
+
+        name
+    }
+}
+
+// "image_data" -> "ImageData", also used for enum variants ("image/png" -> "ImagePng")
+fn pascal_case(text: &str) -> String {
+
+    let mut ret = String::new();
+
+    for word in text.split(|ch: char| !ch.is_alphanumeric()).filter(|word| !word.is_empty()) {
+
+        let mut chars = word.chars();
+
+        if let Some(first) = chars.next() {
+
+            ret.extend(first.to_uppercase());
+            ret.push_str(chars.as_str());
+        }
+    }
+
+    if ret.is_empty() {
+
+        ret.push_str("Value");
+    }
+
+    if ret.starts_with(|ch: char| ch.is_ascii_digit()) {
+
+        ret.insert(0, 'V');
+    }
+
+    ret
+}
+
+// "frameId" -> "frame_id", Rust keywords are kept as raw identifiers
+fn snake_case(text: &str) -> String {
+
+    let mut ret = String::new();
+    let mut previous_lowercase = false;
+
+    for ch in text.chars() {
+
+        if ch.is_uppercase() {
+
+            if previous_lowercase {
+
+                ret.push('_');
+            }
+
+            ret.extend(ch.to_lowercase());
+            previous_lowercase = false;
+        } else if ch.is_alphanumeric() {
+
+            ret.push(ch);
+            previous_lowercase = true;
+        } else {
+
+            if !ret.ends_with('_') {
+
+                ret.push('_');
+            }
+
+            previous_lowercase = false;
+        }
+    }
+
+    let mut ret = ret.trim_matches('_').to_string();
+
+    if ret.is_empty() {
+
+        ret.push_str("field");
+    }
+
+    if ret.starts_with(|ch: char| ch.is_ascii_digit()) {
+
+        ret.insert(0, '_');
+    }
+
+    match ret.as_str() {
+
+        // Can not be raw identifiers
+        "self" | "super" | "crate" => format!("{}_", ret),
+        other if KEYWORDS.contains(&other) => format!("r#{}", ret),
+        _ => ret,
+    }
+}
+
+fn unique_field(wanted: &str, taken: &[String]) -> String {
+
+    let mut name = wanted.to_string();
+    let mut suffix = 2;
+
+    while taken.contains(&name) {
+
+        name = format!("{}_{}", wanted, suffix);
+        suffix += 1;
+    }
+
+    name
+}
+
+// Name for the elements of an array, "frames" -> "Frame"
+fn singular(name: &str) -> String {
+
+    match name.strip_suffix('s') {
+
+        Some(stem) if !stem.is_empty() && !stem.ends_with('s') => stem.to_string(),
+        _ => format!("{}Item", name),
+    }
+}
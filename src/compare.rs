@@ -0,0 +1,399 @@
+/*
+    src/compare.rs
+    This file is part of the JSON-rust crate.
+    Written by, Q@khaa.pk
+ */
+
+/*
+    Semantic comparison of parsed JSON trees.
+    Keys keep the raw text of their values, so comparing them field by field would tell "1.0" from "1",
+    "\u0041" from "A", and two objects whose raw text differs only in whitespace. The functions here compare
+    what the values mean instead, and say where the first difference is when there is one.
+ */
+
+use crate::escape::unescape_lossy;
+use crate::json_object::{ValueType, Key, JsonObject, Iter};
+use crate::pointer::Path;
+
+/// Options controlling what counts as a difference.
+#[derive(Debug, Clone)]
+pub struct EqualityOptions {
+    pub ordered_members: bool,   // Objects with the same members in a different order are different, off by default
+    pub normalize_numbers: bool, // Compare numbers by their exact decimal value ("1.0" == "1" == "1e0"), on by default, otherwise by their text
+    pub count_duplicates: bool,  // Every occurrence of a repeated member name matters, on by default, otherwise only the first one does
+}
+
+impl Default for EqualityOptions {
+    fn default() -> Self {
+        EqualityOptions { ordered_members: false, normalize_numbers: true, count_duplicates: true }
+    }
+}
+
+/// What differs at the location of a `Difference`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DifferenceKind {
+    TypeMismatch(ValueType, ValueType), // Not the same kind of value, left and right
+    ValueMismatch(String, String),      // Same scalar type, different value, left and right raw text
+    MissingMember,                      // The member exists on the left side only
+    ExtraMember,                        // The member exists on the right side only
+    MemberCountMismatch(usize, usize),  // The name occurs a different number of times on each side
+    MemberOrderMismatch(String),        // With ordered members, a different member name at this position (the right side's name)
+    LengthMismatch(usize, usize),       // Arrays of different lengths, left and right
+}
+
+/// The first difference found between two trees.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difference {
+    pub path: Path,
+    pub kind: DifferenceKind,
+}
+
+impl Difference {
+    /// Returns the location of the difference as a JSON Pointer.
+    pub fn pointer(&self) -> String {
+        self.path.to_pointer()
+    }
+}
+
+/// Compares two documents.
+///
+/// # Arguments
+/// * `a` - Left document
+/// * `b` - Right document
+/// * `options` - What counts as a difference
+pub fn equal(a: &JsonObject, b: &JsonObject, options: &EqualityOptions) -> bool {
+
+    first_difference(a, b, options).is_none()
+}
+
+/// Compares two documents and reports the first difference, in document order of the left side.
+///
+/// # Arguments
+/// * `a` - Left document
+/// * `b` - Right document
+/// * `options` - What counts as a difference
+///
+/// # Returns
+/// * `None` if the documents are equal
+/// * `Some(Difference)` with the location and the nature of the first difference
+pub fn first_difference(a: &JsonObject, b: &JsonObject, options: &EqualityOptions) -> Option<Difference> {
+
+    compare_members(a.iter(), b.iter(), &Path::new(), options)
+}
+
+/// Compares the values of two keys, the names of the keys themselves are ignored.
+///
+/// # Arguments
+/// * `a` - Left value
+/// * `b` - Right value
+/// * `options` - What counts as a difference
+pub fn values_equal(a: &Key, b: &Key, options: &EqualityOptions) -> bool {
+
+    value_difference(a, b, &Path::new(), options).is_none()
+}
+
+/// Compares the values of two keys and reports the first difference, relative to the keys.
+///
+/// # Arguments
+/// * `a` - Left value
+/// * `b` - Right value
+/// * `path` - Location of the keys, prepended to the location of the difference
+/// * `options` - What counts as a difference
+pub fn value_difference(a: &Key, b: &Key, path: &Path, options: &EqualityOptions) -> Option<Difference> {
+
+    let difference = |kind| Some(Difference { path: path.clone(), kind });
+
+    if a.get_value_type() != b.get_value_type() {
+
+        return difference(DifferenceKind::TypeMismatch(a.get_value_type().clone(), b.get_value_type().clone()));
+    }
+
+    let same = match a.get_value_type() {
+
+        ValueType::StringType => unescape_lossy(a.get_value()) == unescape_lossy(b.get_value()),
+        ValueType::NumberType => {
+
+            match (options.normalize_numbers, exact_number(a.get_value()), exact_number(b.get_value())) {
+
+                (true, Some(x), Some(y)) => x == y,
+                _ => a.get_value().trim() == b.get_value().trim(),
+            }
+        },
+        ValueType::BooleanType => a.get_value().trim() == b.get_value().trim(),
+        ValueType::NullType => true,
+        ValueType::ObjectType => return compare_members(a.iter(), b.iter(), path, options),
+        ValueType::ArrayType => {
+
+            for (index, (x, y)) in a.iter().zip(b.iter()).enumerate() {
+
+                if let Some(found) = value_difference(x, y, &path.with_index(index), options) {
+
+                    return Some(found);
+                }
+            }
+
+            if a.get_n() != b.get_n() {
+
+                return difference(DifferenceKind::LengthMismatch(a.get_n(), b.get_n()));
+            }
+
+            true
+        },
+    };
+
+    if same {
+
+        None
+    } else {
+
+        difference(DifferenceKind::ValueMismatch(a.get_value().to_string(), b.get_value().to_string()))
+    }
+}
+
+/*
+    The exact value of a JSON number as its sign, its significant digits without leading or trailing zeros,
+    and the power of ten of the last of them, e.g. "-12.50e1" is (true, "125", 0) and "0.0" is (false, "", 0).
+    Going through f64 would make 12345678901234567890 and 12345678901234567891 equal.
+    None for anything that is not a number of the JSON grammar, or whose exponent does not fit an i64.
+ */
+fn exact_number(raw: &str) -> Option<(bool, String, i64)> {
+
+    let raw = raw.trim();
+
+    let (negative, rest) = match raw.strip_prefix('-') {
+
+        Some(rest) => (true, rest),
+        None => (false, raw),
+    };
+
+    let (mantissa, exponent) = match rest.find(['e', 'E']) {
+
+        Some(index) => (&rest[..index], Some(&rest[index + 1..])),
+        None => (rest, None),
+    };
+
+    let (int, frac) = match mantissa.split_once('.') {
+
+        Some((int, frac)) => (int, Some(frac)),
+        None => (mantissa, None),
+    };
+
+    let is_digits = |text: &str| !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit());
+
+    if !is_digits(int) || (int.len() > 1 && int.starts_with('0')) || frac.is_some_and(|frac| !is_digits(frac)) {
+
+        return None;
+    }
+
+    let mut exponent: i64 = match exponent {
+
+        Some(text) => {
+
+            let digits = text.strip_prefix(['+', '-']).unwrap_or(text);
+
+            if !is_digits(digits) {
+
+                return None;
+            }
+
+            text.parse().ok()?
+        },
+        None => 0,
+    };
+
+    let frac = frac.unwrap_or("");
+    exponent = exponent.checked_sub(frac.len() as i64)?;
+
+    let digits = format!("{}{}", int, frac);
+    let digits = digits.trim_start_matches('0');
+    let significant = digits.trim_end_matches('0');
+
+    if significant.is_empty() {
+
+        // Zero, whatever its sign and exponent
+        return Some((false, String::new(), 0));
+    }
+
+    exponent = exponent.checked_add((digits.len() - significant.len()) as i64)?;
+
+    Some((negative, significant.to_string(), exponent))
+}
+
+// Members with their decoded names, only the first occurrence of each name when duplicates do not count
+fn members<'a>(members: Iter<'a>, options: &EqualityOptions) -> Vec<(String, &'a Key)> {
+
+    let mut ret: Vec<(String, &Key)> = Vec::new();
+
+    for member in members {
+
+        let name = unescape_lossy(member.get_name());
+
+        if options.count_duplicates || !ret.iter().any(|(other, _)| *other == name) {
+
+            ret.push((name, member));
+        }
+    }
+
+    ret
+}
+
+fn compare_members(a: Iter<'_>, b: Iter<'_>, path: &Path, options: &EqualityOptions) -> Option<Difference> {
+
+    let a = members(a, options);
+    let b = members(b, options);
+
+    if options.ordered_members {
+
+        for (i, (name, x)) in a.iter().enumerate() {
+
+            match b.get(i) {
+
+                Some((other, y)) if other == name => {
+
+                    if let Some(found) = value_difference(x, y, &path.with_key(name), options) {
+
+                        return Some(found);
+                    }
+                },
+                Some((other, _)) => return Some(Difference { path: path.with_key(name), kind: DifferenceKind::MemberOrderMismatch(other.clone()) }),
+                None => return Some(Difference { path: path.with_key(name), kind: DifferenceKind::MissingMember }),
+            }
+        }
+
+        return b.get(a.len()).map(|(name, _)| Difference { path: path.with_key(name), kind: DifferenceKind::ExtraMember });
+    }
+
+    for (i, (name, _)) in a.iter().enumerate() {
+
+        // Every occurrence of a name is handled when its first occurrence is met
+        if a[..i].iter().any(|(other, _)| other == name) {
+
+            continue;
+        }
+
+        let left: Vec<&Key> = a[i..].iter().filter(|(other, _)| other == name).map(|(_, key)| *key).collect();
+        let right: Vec<&Key> = b.iter().filter(|(other, _)| other == name).map(|(_, key)| *key).collect();
+
+        if right.is_empty() {
+
+            return Some(Difference { path: path.with_key(name), kind: DifferenceKind::MissingMember });
+        }
+
+        for (x, y) in left.iter().zip(right.iter()) {
+
+            if let Some(found) = value_difference(x, y, &path.with_key(name), options) {
+
+                return Some(found);
+            }
+        }
+
+        if left.len() != right.len() {
+
+            return Some(Difference { path: path.with_key(name), kind: DifferenceKind::MemberCountMismatch(left.len(), right.len()) });
+        }
+    }
+
+    b.iter().find(|(name, _)| !a.iter().any(|(other, _)| other == name)).map(|(name, _)| Difference { path: path.with_key(name), kind: DifferenceKind::ExtraMember })
+}
+
+/// Semantic equality with the default `EqualityOptions`: member order does not matter,
+/// numbers are compared by their exact decimal value and strings by their decoded characters.
+impl PartialEq for JsonObject {
+    fn eq(&self, other: &Self) -> bool {
+        equal(self, other, &EqualityOptions::default())
+    }
+}
+
+/// Semantic equality of the names and the values of two keys, with the default `EqualityOptions`.
+/// Siblings (the keys that follow in the list) are not part of the comparison.
+impl PartialEq for Key {
+    fn eq(&self, other: &Self) -> bool {
+        unescape_lossy(self.get_name()) == unescape_lossy(other.get_name()) && values_equal(self, other, &EqualityOptions::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::{parse_str, ParserOptions};
+
+    fn parse(text: &str) -> JsonObject {
+        *parse_str(text, &ParserOptions::default()).unwrap().unwrap()
+    }
+
+    fn number(value: &str) -> Key {
+        Key::new(String::new(), ValueType::NumberType, value.to_string())
+    }
+
+    #[test]
+    fn numbers_are_compared_exactly() {
+        let options = EqualityOptions::default();
+        let same = |a, b| values_equal(&number(a), &number(b), &options);
+
+        assert!(same("1", "1.0"));
+        assert!(same("1", "1e0"));
+        assert!(same("100", "1E2"));
+        assert!(same("0.5", "5e-1"));
+        assert!(same("-12.50e1", "-125"));
+        assert!(same("0", "-0.0e5"));
+
+        assert!(!same("12345678901234567890", "12345678901234567891"));
+        assert!(!same("9007199254740993", "9007199254740992"));
+        assert!(!same("1", "-1"));
+        assert!(!same("1e400", "1e401"));
+
+        assert!(parse(r#"{"id": 12345678901234567890}"#) != parse(r#"{"id": 12345678901234567891}"#));
+        assert!(parse(r#"{"id": 1.0}"#) == parse(r#"{"id": 1}"#));
+
+        let text = EqualityOptions { normalize_numbers: false, ..EqualityOptions::default() };
+        assert!(!values_equal(&number("1"), &number("1.0"), &text));
+    }
+
+    #[test]
+    fn member_order() {
+        let a = parse(r#"{"a": 1, "b": 2}"#);
+        let b = parse(r#"{"b": 2, "a": 1}"#);
+
+        assert!(equal(&a, &b, &EqualityOptions::default()));
+
+        let ordered = EqualityOptions { ordered_members: true, ..EqualityOptions::default() };
+        let found = first_difference(&a, &b, &ordered).unwrap();
+
+        assert_eq!(found.pointer(), "/a");
+        assert_eq!(found.kind, DifferenceKind::MemberOrderMismatch("b".to_string()));
+    }
+
+    #[test]
+    fn duplicate_counts() {
+        let a = parse(r#"{"a": 1, "a": 2}"#);
+        let b = parse(r#"{"a": 1}"#);
+
+        let found = first_difference(&a, &b, &EqualityOptions::default()).unwrap();
+        assert_eq!(found.kind, DifferenceKind::MemberCountMismatch(2, 1));
+
+        let first_only = EqualityOptions { count_duplicates: false, ..EqualityOptions::default() };
+        assert!(equal(&a, &b, &first_only));
+    }
+
+    #[test]
+    fn differences() {
+        let a = parse(r#"{"a": {"b": [1, "x"]}, "c": true}"#);
+
+        let found = first_difference(&a, &parse(r#"{"a": {"b": [1, "y"]}, "c": true}"#), &EqualityOptions::default()).unwrap();
+        assert_eq!((found.pointer(), found.kind), ("/a/b/1".to_string(), DifferenceKind::ValueMismatch("x".to_string(), "y".to_string())));
+
+        let found = first_difference(&a, &parse(r#"{"a": {"b": [1]}, "c": true}"#), &EqualityOptions::default()).unwrap();
+        assert_eq!((found.pointer(), found.kind), ("/a/b".to_string(), DifferenceKind::LengthMismatch(2, 1)));
+
+        let found = first_difference(&a, &parse(r#"{"a": {"b": [1, "x"]}, "c": "true"}"#), &EqualityOptions::default()).unwrap();
+        assert_eq!((found.pointer(), found.kind), ("/c".to_string(), DifferenceKind::TypeMismatch(ValueType::BooleanType, ValueType::StringType)));
+
+        let found = first_difference(&a, &parse(r#"{"a": {"b": [1, "x"]}}"#), &EqualityOptions::default()).unwrap();
+        assert_eq!((found.pointer(), found.kind), ("/c".to_string(), DifferenceKind::MissingMember));
+
+        let found = first_difference(&a, &parse(r#"{"a": {"b": [1, "x"]}, "c": true, "d": null}"#), &EqualityOptions::default()).unwrap();
+        assert_eq!((found.pointer(), found.kind), ("/d".to_string(), DifferenceKind::ExtraMember));
+
+        assert!(equal(&a, &parse(r#"{"c": true, "a": {"b": [1.0, "\u0078"]}}"#), &EqualityOptions::default()));
+    }
+}
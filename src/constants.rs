@@ -54,7 +54,7 @@ pub const JSON_QUOTED_CONTENT_PATTERN: &str = r#""([^"]*)""#;
 
 // JSON number as value in key-value pair (with colon and optional comma)
 pub const JSON_VALUE_TYPE_NUMERIC_PATTERN: &str = r":\s*(-?(?:0|[1-9]\d*)(?:\.\d+)?(?:[eE][+-]?\d+)?)\s*,?\s*$";
-// Just capture any number from anywhere in the line
+// Just capture any number from anywhere in the line, anchored (^...$) the parser checks number literals against it
 pub const CAPTURE_NUMBER_PATTERN: &str = r"(-?(?:0|[1-9]\d*)(?:\.\d+)?(?:[eE][+-]?\d+)?)";
 
 pub const JSON_VALUE_TYPE_NULL_PATTERN: &str = r#":\s*null\s*,?\s*$"#;
@@ -72,3 +72,5 @@ pub const JSON_SINGLE_LINE_ARRAY_TYPE_PATTERN_VALUE_STRING: &str = r":\s*\[([^\]
 /* Process Single Line JSON Ibject, divide line in two groups; first group contains key and the other group contains value */  
 pub const JSON_SINGLE_LINE_OBJECT_TYPE_KEY_NAME_WITH_OPENING_CLOSING_BRACE_PATTERN: &str = r#""([^"]+)"\s*:\s*\{(.*)\}\s*,?\s*$"#;
 
+// Deepest nesting of objects and arrays accepted by default, by the parser as well as by the traversal functions in helper.rs
+pub const DEFAULT_MAX_DEPTH: usize = 128;
@@ -0,0 +1,146 @@
+/*
+    src/escape.rs
+    This file is part of the JSON-rust crate.
+    Written by, Q@khaa.pk
+ */
+
+/*
+    The parser stores the text of a string value exactly as it appeared between the quotation marks,
+    escape sequences included. The functions in this file convert between that raw text and the
+    actual characters the string stands for.
+ */
+
+/// Errors that can occur while decoding the escape sequences of a JSON string.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EscapeError {
+    InvalidEscape(usize),        // Unknown character after a backslash, at the given byte offset
+    InvalidUnicodeEscape(usize), // Malformed \uXXXX sequence or unpaired surrogate, at the given byte offset
+    UnterminatedEscape,          // Backslash at the very end of the text
+}
+
+/// Decodes the escape sequences of the raw text of a JSON string.
+///
+/// # Arguments
+/// * `raw` - The text found between the quotation marks of a JSON string
+///
+/// # Returns
+/// * `Ok(String)` holding the decoded characters
+/// * `Err(EscapeError)` if an escape sequence is not valid JSON
+pub fn unescape(raw: &str) -> Result<String, EscapeError> {
+
+    let mut ret = String::with_capacity(raw.len());
+    let mut chars = raw.char_indices();
+
+    while let Some((offset, ch)) = chars.next() {
+
+        if ch != '\\' {
+
+            ret.push(ch);
+
+            continue;
+        }
+
+        match chars.next() {
+
+            Some((_, '"')) => ret.push('"'),
+            Some((_, '\\')) => ret.push('\\'),
+            Some((_, '/')) => ret.push('/'),
+            Some((_, 'b')) => ret.push('\u{0008}'),
+            Some((_, 'f')) => ret.push('\u{000C}'),
+            Some((_, 'n')) => ret.push('\n'),
+            Some((_, 'r')) => ret.push('\r'),
+            Some((_, 't')) => ret.push('\t'),
+            Some((_, 'u')) => {
+
+                let high = read_hex4(&mut chars).ok_or(EscapeError::InvalidUnicodeEscape(offset))?;
+
+                let code_point = if (0xD800..0xDC00).contains(&high) {
+
+                    // A high surrogate has to be followed by an escaped low surrogate
+                    match (chars.next(), chars.next()) {
+
+                        (Some((_, '\\')), Some((_, 'u'))) => {
+
+                            let low = read_hex4(&mut chars).ok_or(EscapeError::InvalidUnicodeEscape(offset))?;
+
+                            if !(0xDC00..0xE000).contains(&low) {
+
+                                return Err(EscapeError::InvalidUnicodeEscape(offset));
+                            }
+
+                            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+                        },
+                        _ => return Err(EscapeError::InvalidUnicodeEscape(offset)),
+                    }
+                } else if (0xDC00..0xE000).contains(&high) {
+
+                    return Err(EscapeError::InvalidUnicodeEscape(offset));
+                } else {
+
+                    high
+                };
+
+                ret.push(char::from_u32(code_point).ok_or(EscapeError::InvalidUnicodeEscape(offset))?);
+            },
+            Some(_) => return Err(EscapeError::InvalidEscape(offset)),
+            None => return Err(EscapeError::UnterminatedEscape),
+        }
+    }
+
+    Ok(ret)
+}
+
+/// Decodes the escape sequences of the raw text of a JSON string, falling back to the raw text
+/// itself when it holds an invalid escape sequence.
+///
+/// # Arguments
+/// * `raw` - The text found between the quotation marks of a JSON string
+pub fn unescape_lossy(raw: &str) -> String {
+
+    unescape(raw).unwrap_or_else(|_| raw.to_string())
+}
+
+/// Encodes a string as the text of a JSON string, without the surrounding quotation marks.
+///
+/// Only the characters JSON requires to be escaped are escaped: the quotation mark, the backslash
+/// and the control characters below U+0020. Control characters with a short form (\b, \t, \n, \f, \r)
+/// use it, the others use \u00xx with lower case hexadecimal digits.
+///
+/// # Arguments
+/// * `s` - The decoded string
+pub fn escape(s: &str) -> String {
+
+    let mut ret = String::with_capacity(s.len());
+
+    for ch in s.chars() {
+
+        match ch {
+
+            '"' => ret.push_str("\\\""),
+            '\\' => ret.push_str("\\\\"),
+            '\u{0008}' => ret.push_str("\\b"),
+            '\t' => ret.push_str("\\t"),
+            '\n' => ret.push_str("\\n"),
+            '\u{000C}' => ret.push_str("\\f"),
+            '\r' => ret.push_str("\\r"),
+            c if (c as u32) < 0x20 => ret.push_str(&format!("\\u{:04x}", c as u32)),
+            c => ret.push(c),
+        }
+    }
+
+    ret
+}
+
+fn read_hex4(chars: &mut std::str::CharIndices) -> Option<u32> {
+
+    let mut value = 0;
+
+    for _ in 0..4 {
+
+        let (_, ch) = chars.next()?;
+
+        value = value * 16 + ch.to_digit(16)?;
+    }
+
+    Some(value)
+}
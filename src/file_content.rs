@@ -4,6 +4,7 @@ use std::io;
 pub struct FileContent {
     content: String,
     lines: Vec<String>,
+    line_offsets: Vec<usize>, // Byte offset of the start of each line in content
 
     current_line_index: usize,
 }
@@ -12,10 +13,58 @@ impl FileContent {
     
     pub fn from_file(path: &str) -> Result<Self, io::Error> {
 
-        let content = fs::read_to_string(path)?;
-        let lines = content.lines().map(|s| s.to_string()).collect();
-                
-        Ok(FileContent { content, lines, current_line_index: 0 })
+        Ok(FileContent::from_string(fs::read_to_string(path)?))
+    }
+
+    // The same, for text that is already in memory
+    pub fn from_string(content: String) -> Self {
+
+        let mut ret = FileContent { content, lines: Vec::new(), line_offsets: Vec::new(), current_line_index: 0 };
+        ret.split_lines();
+
+        ret
+    }
+
+    // Replaces the text with another one, the memory of the previous one is reused
+    pub fn load(&mut self, text: &str) {
+
+        self.content.clear();
+        self.content.push_str(text);
+        self.current_line_index = 0;
+
+        self.split_lines();
+    }
+
+    fn split_lines(&mut self) {
+
+        let mut count = 0;
+
+        for line in self.content.lines() {
+
+            if count < self.lines.len() {
+
+                self.lines[count].clear();
+                self.lines[count].push_str(line);
+            } else {
+
+                self.lines.push(line.to_string());
+            }
+
+            count += 1;
+        }
+
+        self.lines.truncate(count);
+
+        // lines() drops the line endings, "\n" or "\r\n", keep track of where each line starts
+        let mut offset = 0;
+
+        self.line_offsets.clear();
+
+        for line in self.content.split_inclusive('\n') {
+
+            self.line_offsets.push(offset);
+            offset += line.len();
+        }
     }
 
     pub fn count_lines(&self) -> usize {
@@ -29,7 +78,7 @@ impl FileContent {
     }
     
     // Methods for traversing and parsing
-    fn get_content(&self) -> &str {
+    pub fn get_content(&self) -> &str {
 
         &self.content
     }
@@ -56,6 +105,12 @@ impl FileContent {
         None
     }
     
+    // index originates at 0, the offset is in bytes from the start of the file
+    pub fn get_line_offset(&self, index: usize) -> Option<usize> {
+
+        self.line_offsets.get(index).copied()
+    }
+
     fn get_lines(&self) -> &[String] {
 
         &self.lines
@@ -0,0 +1,390 @@
+/*
+    src/flatten.rs
+    This file is part of the JSON-rust crate.
+    Written by, Q@khaa.pk
+ */
+
+/*
+    Conversion between a tree and a flat list of (dotted key, value) pairs, e.g.
+        "chunks.0.data.0.width" = 1300
+    as used by env files, key-value stores and spreadsheet-like editors.
+    Only leaves become entries: strings, numbers, booleans, nulls, and empty objects and arrays (so that they
+    survive a round trip). Member names are written as they are, a name holding the separator, or with the
+    dotted style a name made of digits only, can not be told apart from the structure when reading back.
+ */
+
+use crate::escape::{escape, unescape_lossy};
+use crate::json_object::{ValueType, Key, JsonObject, Iter};
+use crate::pointer::parse_index;
+
+/// How array indices appear in a flattened key.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IndexStyle {
+    Dotted,    // chunks.0.data.0.width
+    Bracketed, // chunks[0].data[0].width
+}
+
+/// Options shared by `flatten_with_options` and `unflatten_with_options`.
+#[derive(Debug, Clone)]
+pub struct FlattenOptions {
+    pub separator: String,       // Between member names, "." by default
+    pub index_style: IndexStyle, // Dotted by default
+}
+
+impl Default for FlattenOptions {
+    fn default() -> Self {
+        FlattenOptions { separator: ".".to_string(), index_style: IndexStyle::Dotted }
+    }
+}
+
+/// Errors that can occur while rebuilding a tree from flattened entries, or with options that can not be used.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FlattenError {
+    Conflict(String),   // The key clashes with another one: same key twice, or a place used both as a value and as a container, or both as an object and an array
+    InvalidKey(String), // The key is empty, has an empty segment, a malformed [index] or an index too far past the end of its array
+    EmptySeparator,     // The options have an empty separator, keys could not be split back into segments
+}
+
+/// Flattens a tree with the default options, see `flatten_with_options`.
+///
+/// # Arguments
+/// * `jobj` - The tree to flatten
+pub fn flatten(jobj: &JsonObject) -> Vec<(String, Key)> {
+
+    // The default separator is not empty, this can not fail
+    flatten_with_options(jobj, &FlattenOptions::default()).unwrap_or_default()
+}
+
+/// Flattens a tree into (key, value) pairs, in document order.
+/// The values are detached copies of the leaf keys; names are decoded in the flattened keys.
+///
+/// # Arguments
+/// * `jobj` - The tree to flatten
+/// * `options` - Separator and index style
+///
+/// # Returns
+/// * `Ok(Vec<(String, Key)>)` holding the pairs
+/// * `Err(FlattenError::EmptySeparator)` if the separator is empty
+pub fn flatten_with_options(jobj: &JsonObject, options: &FlattenOptions) -> Result<Vec<(String, Key)>, FlattenError> {
+
+    if options.separator.is_empty() {
+
+        return Err(FlattenError::EmptySeparator);
+    }
+
+    let mut ret = Vec::new();
+
+    // Prefix of the keys found in each container on the way down, whether it is an array, and the next index
+    let mut stack: Vec<(String, Iter, bool, usize)> = vec![(String::new(), jobj.iter(), false, 0)];
+
+    while let Some((prefix, children, is_array, index)) = stack.last_mut() {
+
+        let key = match children.next() {
+
+            Some(key) => key,
+            None => {
+
+                stack.pop();
+
+                continue;
+            },
+        };
+
+        let flat = if *is_array {
+
+            match options.index_style {
+
+                IndexStyle::Dotted => format!("{}{}{}", prefix, options.separator, index),
+                IndexStyle::Bracketed => format!("{}[{}]", prefix, index),
+            }
+        } else if prefix.is_empty() {
+
+            unescape_lossy(key.get_name())
+        } else {
+
+            format!("{}{}{}", prefix, options.separator, unescape_lossy(key.get_name()))
+        };
+
+        *index += 1;
+
+        match key.get_value_type() {
+
+            ValueType::ObjectType | ValueType::ArrayType if key.get_n() > 0 => {
+
+                stack.push((flat, key.iter(), *key.get_value_type() == ValueType::ArrayType, 0));
+            },
+            _ => ret.push((flat, key.clone_detached())),
+        }
+    }
+
+    Ok(ret)
+}
+
+/// Rebuilds a tree from flattened entries with the default options, see `unflatten_with_options`.
+///
+/// # Arguments
+/// * `entries` - The (key, value) pairs
+pub fn unflatten(entries: &[(String, Key)]) -> Result<JsonObject, FlattenError> {
+
+    unflatten_with_options(entries, &FlattenOptions::default())
+}
+
+/// Rebuilds a tree from flattened entries, in any order.
+///
+/// Members appear in the order their first entry does. Array elements that no entry mentions
+/// (e.g. "a.0" and "a.2" without "a.1") are filled with null. An index can be at most as many places
+/// past the end of its array as there are entries, a single "a.4000000000" would otherwise fill billions of them.
+///
+/// # Arguments
+/// * `entries` - The (key, value) pairs
+/// * `options` - Separator and index style, the ones the entries were flattened with
+///
+/// # Returns
+/// * `Ok(JsonObject)` holding the rebuilt tree
+/// * `Err(FlattenError)` naming the first offending key, or `EmptySeparator`
+pub fn unflatten_with_options(entries: &[(String, Key)], options: &FlattenOptions) -> Result<JsonObject, FlattenError> {
+
+    if options.separator.is_empty() {
+
+        return Err(FlattenError::EmptySeparator);
+    }
+
+    let mut root = Node::Object(Vec::new());
+
+    for (flat, value) in entries {
+
+        let segments = split(flat, options).ok_or_else(|| FlattenError::InvalidKey(flat.clone()))?;
+
+        insert(&mut root, &segments, value, flat, entries.len())?;
+    }
+
+    let mut ret = JsonObject::new();
+
+    if let Node::Object(members) = root {
+
+        ret.set_keys(members.into_iter().map(|(name, node)| node.into_key(escape(&name))).collect());
+    }
+
+    Ok(ret)
+}
+
+enum Segment {
+    Name(String),
+    Index(usize),
+}
+
+// The tree being rebuilt, Hole is a place nothing has been put at yet
+enum Node {
+    Hole,
+    Leaf(Key),
+    Object(Vec<(String, Node)>),
+    Array(Vec<Node>),
+}
+
+impl Node {
+    fn into_key(self, name: String) -> Key {
+
+        let (value_type, children) = match self {
+
+            Node::Hole => return Key::new(name, ValueType::NullType, "null".to_string()),
+            Node::Leaf(mut key) => {
+
+                key.set_name(name);
+
+                return key;
+            },
+            Node::Object(members) => (ValueType::ObjectType, members.into_iter().map(|(name, node)| node.into_key(escape(&name))).collect()),
+            Node::Array(elements) => (ValueType::ArrayType, elements.into_iter().map(|node| node.into_key(String::new())).collect()),
+        };
+
+        let mut key = Key::new(name, value_type, String::new());
+        key.set_keys(children);
+        key.set_value(key.to_json_string());
+
+        key
+    }
+}
+
+fn split(flat: &str, options: &FlattenOptions) -> Option<Vec<Segment>> {
+
+    let mut ret = Vec::new();
+
+    for part in flat.split(options.separator.as_str()) {
+
+        match options.index_style {
+
+            IndexStyle::Dotted => {
+
+                if part.is_empty() {
+
+                    return None;
+                }
+
+                // The first segment is a member of the root object, whatever it looks like
+                match parse_index(part) {
+
+                    Some(index) if !ret.is_empty() => ret.push(Segment::Index(index)),
+                    _ => ret.push(Segment::Name(part.to_string())),
+                }
+            },
+            IndexStyle::Bracketed => {
+
+                // name[0][1], the name can only be left out after an index, as in a[0].[1] which is not produced by flatten
+                let (name, mut rest) = part.split_at(part.find('[').unwrap_or(part.len()));
+
+                if name.is_empty() && ret.is_empty() {
+
+                    return None;
+                }
+
+                if !name.is_empty() {
+
+                    ret.push(Segment::Name(name.to_string()));
+                }
+
+                while !rest.is_empty() {
+
+                    let close = rest.find(']')?;
+
+                    if !rest.starts_with('[') {
+
+                        return None;
+                    }
+
+                    ret.push(Segment::Index(parse_index(&rest[1..close])?));
+
+                    rest = &rest[close + 1..];
+                }
+            },
+        }
+    }
+
+    Some(ret)
+}
+
+// max_gap is how far past the end of an array an index can be
+fn insert(node: &mut Node, segments: &[Segment], value: &Key, flat: &str, max_gap: usize) -> Result<(), FlattenError> {
+
+    let conflict = || FlattenError::Conflict(flat.to_string());
+
+    let (segment, rest) = match segments.split_first() {
+
+        Some(split) => split,
+        None => {
+
+            // End of the key, the value goes here
+            return match node {
+
+                Node::Hole => {
+
+                    *node = Node::Leaf(value.clone_detached());
+
+                    Ok(())
+                },
+                _ => Err(conflict()),
+            };
+        },
+    };
+
+    if let Node::Hole = node {
+
+        *node = match segment {
+
+            Segment::Name(_) => Node::Object(Vec::new()),
+            Segment::Index(_) => Node::Array(Vec::new()),
+        };
+    }
+
+    let child = match (node, segment) {
+
+        (Node::Object(members), Segment::Name(name)) => {
+
+            let position = match members.iter().position(|(other, _)| other == name) {
+
+                Some(position) => position,
+                None => {
+
+                    members.push((name.clone(), Node::Hole));
+                    members.len() - 1
+                },
+            };
+
+            &mut members[position].1
+        },
+        (Node::Array(elements), Segment::Index(index)) => {
+
+            if *index > elements.len() + max_gap {
+
+                return Err(FlattenError::InvalidKey(flat.to_string()));
+            }
+
+            while elements.len() <= *index {
+
+                elements.push(Node::Hole);
+            }
+
+            &mut elements[*index]
+        },
+        _ => return Err(conflict()),
+    };
+
+    insert(child, rest, value, flat, max_gap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::{parse_str, ParserOptions};
+
+    fn parse(text: &str) -> JsonObject {
+        *parse_str(text, &ParserOptions::default()).unwrap().unwrap()
+    }
+
+    fn number(value: &str) -> Key {
+        Key::new(String::new(), ValueType::NumberType, value.to_string())
+    }
+
+    #[test]
+    fn round_trip() {
+        let jobj = parse(r#"{"name": "png", "chunks": [{"width": 1300, "tags": []}, 2], "empty": {}}"#);
+
+        for index_style in [IndexStyle::Dotted, IndexStyle::Bracketed] {
+            let options = FlattenOptions { separator: ".".to_string(), index_style };
+            let entries = flatten_with_options(&jobj, &options).unwrap();
+
+            assert_eq!(unflatten_with_options(&entries, &options).unwrap().to_json_string(), jobj.to_json_string());
+        }
+
+        let names: Vec<String> = flatten(&jobj).into_iter().map(|(flat, _)| flat).collect();
+        assert_eq!(names, ["name", "chunks.0.width", "chunks.0.tags", "chunks.1", "empty"]);
+    }
+
+    #[test]
+    fn holes_are_null() {
+        let entries = vec![("a.2".to_string(), number("3")), ("a.0".to_string(), number("1"))];
+
+        assert_eq!(unflatten(&entries).unwrap().to_json_string(), r#"{"a":[1,null,3]}"#);
+    }
+
+    #[test]
+    fn sparse_index_is_rejected() {
+        let entries = vec![("a.4000000000".to_string(), number("1"))];
+
+        assert_eq!(unflatten(&entries).unwrap_err(), FlattenError::InvalidKey("a.4000000000".to_string()));
+    }
+
+    #[test]
+    fn empty_separator_is_rejected() {
+        let options = FlattenOptions { separator: String::new(), index_style: IndexStyle::Dotted };
+
+        assert_eq!(flatten_with_options(&parse(r#"{"a": 1}"#), &options).unwrap_err(), FlattenError::EmptySeparator);
+        assert_eq!(unflatten_with_options(&[("a".to_string(), number("1"))], &options).unwrap_err(), FlattenError::EmptySeparator);
+    }
+
+    #[test]
+    fn conflicts() {
+        let entries = vec![("a".to_string(), number("1")), ("a.b".to_string(), number("2"))];
+
+        assert_eq!(unflatten(&entries).unwrap_err(), FlattenError::Conflict("a.b".to_string()));
+    }
+}
@@ -0,0 +1,420 @@
+/*
+    src/from_json.rs
+    This file is part of the JSON-rust crate.
+    Written by, Q@khaa.pk
+ */
+
+/*
+    Typed extraction of parsed JSON.
+    FromJson maps a value of the tree onto a Rust type, it is implemented here for strings, numbers, booleans,
+    Option, Vec, HashMap, BTreeMap and tuples, and can be derived for structs and enums with #[derive(FromJson)].
+    A failure carries the Path of the value that could not be converted, e.g. "/chunks/0/data/0/width: expected a number".
+
+    The derive understands the following field attributes,
+    #[json(rename = "name")]    the member is looked up under the given name instead of the field name
+    #[json(default)]            a missing member gives Default::default()
+    #[json(default = "path")]   a missing member gives the result of calling the given function
+    #[json(skip)]               the field is not read at all, it gets Default::default()
+    #[json(flatten)]            the field is read from the same object as the struct itself
+    Enums are read back the way to_json.rs writes them.
+ */
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::BuildHasher;
+
+use crate::escape::{unescape, EscapeError};
+use crate::json_object::{Iter, JsonObject, Key, ValueType};
+use crate::pointer::Path;
+
+pub use json_rust_derive::FromJson;
+
+/// A value of a parsed tree, the root object or any key below it.
+#[derive(Debug, Clone, Copy)]
+pub enum Value<'a> {
+    Root(&'a JsonObject), // The root object of a document
+    Key(&'a Key),         // A member of an object or an element of an array
+}
+
+impl<'a> Value<'a> {
+    /// Returns the type of the value, the root is always an object.
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            Value::Root(_) => ValueType::ObjectType,
+            Value::Key(key) => key.get_value_type().clone(),
+        }
+    }
+
+    /// Returns the members of an object or the elements of an array, nothing for other values.
+    pub fn iter(&self) -> Iter<'a> {
+        match self {
+            Value::Root(jobj) => jobj.iter(),
+            Value::Key(key) => key.iter(),
+        }
+    }
+
+    /// Returns the first member with the given name, names are compared once their escape sequences are decoded.
+    ///
+    /// # Arguments
+    /// * `name` - The decoded member name
+    pub fn get(&self, name: &str) -> Option<&'a Key> {
+        self.iter().find(|member| match unescape(member.get_name()) {
+            Ok(decoded) => decoded == name,
+            Err(_) => member.get_name() == name,
+        })
+    }
+}
+
+/// Why a value could not be converted.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FromJsonErrorKind {
+    Missing,                   // A required member is not there
+    Type(&'static str),        // The value is of another type, what was expected is given
+    Number(String),            // A number that does not fit the Rust type, the number is given
+    Escape(EscapeError),       // A string with an invalid escape sequence
+    Length(usize, usize),      // An array with the wrong number of elements for a tuple, expected and found
+    UnknownVariant(String),    // An enum tag that names none of the variants, the tag is given
+    Custom(String),            // Reported by a hand written FromJson implementation
+}
+
+/// A failed conversion, with the location of the value that could not be converted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FromJsonError {
+    pub path: Path,
+    pub kind: FromJsonErrorKind,
+}
+
+impl FromJsonError {
+    /// Creates an error for the value at the given path.
+    pub fn new(path: &Path, kind: FromJsonErrorKind) -> Self {
+        FromJsonError { path: path.clone(), kind }
+    }
+
+    /// Returns the location of the value as a JSON Pointer.
+    pub fn pointer(&self) -> String {
+        self.path.to_pointer()
+    }
+}
+
+impl fmt::Display for FromJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.pointer())?;
+
+        match &self.kind {
+            FromJsonErrorKind::Missing => write!(f, "missing member"),
+            FromJsonErrorKind::Type(expected) => write!(f, "expected {}", expected),
+            FromJsonErrorKind::Number(number) => write!(f, "{} does not fit the type", number),
+            FromJsonErrorKind::Escape(e) => write!(f, "invalid string, {:?}", e),
+            FromJsonErrorKind::Length(expected, found) => write!(f, "expected {} elements, found {}", expected, found),
+            FromJsonErrorKind::UnknownVariant(tag) => write!(f, "unknown variant \"{}\"", tag),
+            FromJsonErrorKind::Custom(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for FromJsonError {}
+
+/// Conversion of a value of a parsed tree into a Rust type.
+pub trait FromJson: Sized {
+    /// Converts the value.
+    ///
+    /// # Arguments
+    /// * `value` - The value to convert
+    /// * `path` - Where the value is in the document, used to report errors
+    fn from_json(value: Value<'_>, path: &Path) -> Result<Self, FromJsonError>;
+
+    /// What a member of this type becomes when it is not there, an error unless the type is optional.
+    ///
+    /// # Arguments
+    /// * `path` - Where the member would have been
+    fn from_missing(path: &Path) -> Result<Self, FromJsonError> {
+        Err(FromJsonError::new(path, FromJsonErrorKind::Missing))
+    }
+}
+
+/// Converts a whole document.
+///
+/// # Arguments
+/// * `jobj` - The root object of the document
+///
+/// # Returns
+/// * `Ok(T)` holding the converted document
+/// * `Err(FromJsonError)` for the first value that could not be converted
+pub fn from_json_object<T: FromJson>(jobj: &JsonObject) -> Result<T, FromJsonError> {
+    T::from_json(Value::Root(jobj), &Path::new())
+}
+
+/// Checks the type of a value, returning a Type error naming what was expected otherwise.
+///
+/// # Arguments
+/// * `value` - The value to check
+/// * `value_type` - The type it has to be of
+/// * `expected` - How the error describes the type, e.g. "an object"
+/// * `path` - Where the value is in the document
+pub fn expect_type(value: Value<'_>, value_type: ValueType, expected: &'static str, path: &Path) -> Result<(), FromJsonError> {
+    if value.value_type() == value_type {
+        Ok(())
+    } else {
+        Err(FromJsonError::new(path, FromJsonErrorKind::Type(expected)))
+    }
+}
+
+/// Converts the member with the given name, used by derived implementations.
+/// A missing member is handed to `T::from_missing`.
+///
+/// # Arguments
+/// * `value` - The object holding the member
+/// * `name` - The decoded member name
+/// * `path` - Where the object is in the document
+pub fn member<T: FromJson>(value: Value<'_>, name: &str, path: &Path) -> Result<T, FromJsonError> {
+    let path = path.with_key(name);
+
+    match value.get(name) {
+        Some(key) => T::from_json(Value::Key(key), &path),
+        None => T::from_missing(&path),
+    }
+}
+
+/// Converts the member with the given name, or calls `default` when it is not there.
+///
+/// # Arguments
+/// * `value` - The object holding the member
+/// * `name` - The decoded member name
+/// * `path` - Where the object is in the document
+/// * `default` - Gives the value of a missing member
+pub fn member_or<T: FromJson>(value: Value<'_>, name: &str, path: &Path, default: impl FnOnce() -> T) -> Result<T, FromJsonError> {
+    match value.get(name) {
+        Some(key) => T::from_json(Value::Key(key), &path.with_key(name)),
+        None => Ok(default()),
+    }
+}
+
+/// Decodes the name of a member, used by derived implementations to read the tag of an enum.
+///
+/// # Arguments
+/// * `key` - The member
+/// * `path` - Where the object holding the member is in the document
+pub fn member_name(key: &Key, path: &Path) -> Result<String, FromJsonError> {
+    unescape(key.get_name()).map_err(|e| FromJsonError::new(path, FromJsonErrorKind::Escape(e)))
+}
+
+// The raw text of a number, after checking that the value is one
+fn number<'a>(value: &Value<'a>, path: &Path) -> Result<&'a str, FromJsonError> {
+    match value {
+        Value::Key(key) if *key.get_value_type() == ValueType::NumberType => Ok(key.get_value().trim()),
+        _ => Err(FromJsonError::new(path, FromJsonErrorKind::Type("a number"))),
+    }
+}
+
+macro_rules! from_json_number {
+    ($($t:ty),*) => {
+        $(
+            impl FromJson for $t {
+                fn from_json(value: Value<'_>, path: &Path) -> Result<Self, FromJsonError> {
+                    let raw = number(&value, path)?;
+                    raw.parse::<$t>().map_err(|_| FromJsonError::new(path, FromJsonErrorKind::Number(raw.to_string())))
+                }
+            }
+        )*
+    };
+}
+
+from_json_number!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);
+
+impl FromJson for bool {
+    fn from_json(value: Value<'_>, path: &Path) -> Result<Self, FromJsonError> {
+        match value {
+            Value::Key(key) if *key.get_value_type() == ValueType::BooleanType => Ok(key.get_value().trim() == "true"),
+            _ => Err(FromJsonError::new(path, FromJsonErrorKind::Type("a boolean"))),
+        }
+    }
+}
+
+impl FromJson for String {
+    fn from_json(value: Value<'_>, path: &Path) -> Result<Self, FromJsonError> {
+        match value {
+            Value::Key(key) if *key.get_value_type() == ValueType::StringType => {
+                unescape(key.get_value()).map_err(|e| FromJsonError::new(path, FromJsonErrorKind::Escape(e)))
+            },
+            _ => Err(FromJsonError::new(path, FromJsonErrorKind::Type("a string"))),
+        }
+    }
+}
+
+impl FromJson for char {
+    fn from_json(value: Value<'_>, path: &Path) -> Result<Self, FromJsonError> {
+        let s = String::from_json(value, path)?;
+        let mut chars = s.chars();
+
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(FromJsonError::new(path, FromJsonErrorKind::Type("a single character string"))),
+        }
+    }
+}
+
+impl FromJson for () {
+    fn from_json(value: Value<'_>, path: &Path) -> Result<Self, FromJsonError> {
+        expect_type(value, ValueType::NullType, "null", path)
+    }
+}
+
+/// null and a missing member both become None.
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(value: Value<'_>, path: &Path) -> Result<Self, FromJsonError> {
+        if value.value_type() == ValueType::NullType {
+            Ok(None)
+        } else {
+            T::from_json(value, path).map(Some)
+        }
+    }
+
+    fn from_missing(_path: &Path) -> Result<Self, FromJsonError> {
+        Ok(None)
+    }
+}
+
+impl<T: FromJson> FromJson for Box<T> {
+    fn from_json(value: Value<'_>, path: &Path) -> Result<Self, FromJsonError> {
+        T::from_json(value, path).map(Box::new)
+    }
+
+    fn from_missing(path: &Path) -> Result<Self, FromJsonError> {
+        T::from_missing(path).map(Box::new)
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: Value<'_>, path: &Path) -> Result<Self, FromJsonError> {
+        expect_type(value, ValueType::ArrayType, "an array", path)?;
+
+        value.iter().enumerate().map(|(i, element)| T::from_json(Value::Key(element), &path.with_index(i))).collect()
+    }
+}
+
+/// Keys are the decoded member names, of a duplicated name the first member wins (as with `Value::get`), the later ones are not read.
+impl<T: FromJson, S: BuildHasher + Default> FromJson for HashMap<String, T, S> {
+    fn from_json(value: Value<'_>, path: &Path) -> Result<Self, FromJsonError> {
+        expect_type(value, ValueType::ObjectType, "an object", path)?;
+
+        let mut ret = HashMap::with_hasher(S::default());
+
+        for member in value.iter() {
+
+            let name = member_name(member, path)?;
+
+            if ret.contains_key(&name) {
+
+                continue;
+            }
+
+            let element = T::from_json(Value::Key(member), &path.with_key(&name))?;
+
+            ret.insert(name, element);
+        }
+
+        Ok(ret)
+    }
+}
+
+/// Keys are the decoded member names, of a duplicated name the first member wins (as with `Value::get`), the later ones are not read.
+impl<T: FromJson> FromJson for BTreeMap<String, T> {
+    fn from_json(value: Value<'_>, path: &Path) -> Result<Self, FromJsonError> {
+        expect_type(value, ValueType::ObjectType, "an object", path)?;
+
+        let mut ret = BTreeMap::new();
+
+        for member in value.iter() {
+
+            let name = member_name(member, path)?;
+
+            if ret.contains_key(&name) {
+
+                continue;
+            }
+
+            let element = T::from_json(Value::Key(member), &path.with_key(&name))?;
+
+            ret.insert(name, element);
+        }
+
+        Ok(ret)
+    }
+}
+
+/// The whole value, detached from its siblings, for fields that keep part of a document as it is.
+impl FromJson for Key {
+    fn from_json(value: Value<'_>, _path: &Path) -> Result<Self, FromJsonError> {
+        Ok(match value {
+            Value::Root(jobj) => {
+                let mut key = Key::new(String::new(), ValueType::ObjectType, String::new());
+                key.set_keys(jobj.iter().map(Key::clone_detached).collect());
+                key.set_value(key.to_json_string());
+                key
+            },
+            Value::Key(key) => key.clone_detached(),
+        })
+    }
+}
+
+// Tuples are read from arrays of exactly as many elements
+macro_rules! from_json_tuple {
+    ($n:expr => $($t:ident),+) => {
+        impl<$($t: FromJson),+> FromJson for ($($t,)+) {
+            fn from_json(value: Value<'_>, path: &Path) -> Result<Self, FromJsonError> {
+                expect_type(value, ValueType::ArrayType, "an array", path)?;
+
+                let found = value.iter().count();
+                if found != $n {
+                    return Err(FromJsonError::new(path, FromJsonErrorKind::Length($n, found)));
+                }
+
+                let mut elements = value.iter().enumerate();
+
+                Ok(($({
+                    let (i, element) = elements.next().unwrap();
+                    $t::from_json(Value::Key(element), &path.with_index(i))?
+                },)+))
+            }
+        }
+    };
+}
+
+from_json_tuple!(1 => A);
+from_json_tuple!(2 => A, B);
+from_json_tuple!(3 => A, B, C);
+from_json_tuple!(4 => A, B, C, D);
+from_json_tuple!(5 => A, B, C, D, E);
+from_json_tuple!(6 => A, B, C, D, E, F);
+from_json_tuple!(7 => A, B, C, D, E, F, G);
+from_json_tuple!(8 => A, B, C, D, E, F, G, H);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::{parse_str, ParserOptions};
+
+    fn parse(text: &str) -> JsonObject {
+        *parse_str(text, &ParserOptions::default()).unwrap().unwrap()
+    }
+
+    #[test]
+    fn maps_take_the_first_member() {
+        let jobj = parse(r#"{"role": "user", "id": 1, "role": "admin"}"#);
+
+        let map: HashMap<String, Key> = from_json_object(&jobj).unwrap();
+        assert_eq!(map["role"].get_value(), "user");
+
+        let map: BTreeMap<String, Key> = from_json_object(&jobj).unwrap();
+        assert_eq!(map["role"].get_value(), "user");
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn later_duplicates_are_not_read() {
+        let jobj = parse(r#"{"a": 1, "a": "not a number"}"#);
+
+        let map: BTreeMap<String, u32> = from_json_object(&jobj).unwrap();
+        assert_eq!(map["a"], 1);
+    }
+}
@@ -4,8 +4,12 @@
     Written by, Q@khaa.pk
  */
 
+use std::collections::VecDeque;
 use std::io;
-use crate::json_object::{ValueType, Key, JsonKeyPtr, JsonObject}; 
+use crate::constants::DEFAULT_MAX_DEPTH;
+use crate::escape::unescape_lossy;
+use crate::json_object::{ValueType, Key, JsonObject, Iter};
+use crate::pointer::{Path, PathSegment};
 
 // Define a custom error type for your traverse function
 #[derive(Debug)]
@@ -14,108 +18,942 @@ pub enum TraverseError {
     //IoError(io::Error),
     NoJsonObject,
 
+    // Malformed trees, the String is the name of the offending key ("" for array elements and for the root), the usize is its depth
+    MissingChildren(String, usize),               // n > 0 but ptr is None
+    CountMismatch(String, usize, usize, usize),   // n says one number of children (3rd field), the list holds another (4th field)
+    UnexpectedChildren(String, usize),            // A string, number, boolean or null key with nested keys
+
+    DepthLimitExceeded(usize),                    // Objects and arrays are nested deeper than the limit, which is given
+
     // Add other error variants as needed
 }
 
+/// Order in which `walk` visits the keys of a tree.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TraversalOrder {
+    PreOrder,     // Depth first, a key before its children
+    PostOrder,    // Depth first, a key after its children
+    BreadthFirst, // Level by level, every key of a depth before any key of the next depth
+}
+
 impl From<io::Error> for TraverseError {
     fn from(err: io::Error) -> Self {
         TraverseError::IoError(err.to_string())
     }
 }
 
+/// What the traversal should do after a `Visitor` method returns.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VisitAction {
+    Continue,    // Go on as usual
+    SkipSubtree, // Returned by enter_object/enter_array: do not visit the children, and do not call the matching leave_*
+    Stop,        // End the traversal right away
+}
+
+/// Callbacks invoked by `traverse` for every key of a tree, in document order.
+/// Every method has a default implementation that does nothing, so a visitor only implements what it needs.
+pub trait Visitor {
+    /// Called for a key holding an object, before its members.
+    fn enter_object(&mut self, _key: &Key) -> VisitAction {
+        VisitAction::Continue
+    }
+
+    /// Called for a key holding an object, after its members.
+    fn leave_object(&mut self, _key: &Key) -> VisitAction {
+        VisitAction::Continue
+    }
+
+    /// Called for a key holding an array, before its elements.
+    fn enter_array(&mut self, _key: &Key) -> VisitAction {
+        VisitAction::Continue
+    }
+
+    /// Called for a key holding an array, after its elements.
+    fn leave_array(&mut self, _key: &Key) -> VisitAction {
+        VisitAction::Continue
+    }
+
+    /// Called for a key holding a string, a number, a boolean or null.
+    fn visit_scalar(&mut self, _key: &Key) -> VisitAction {
+        VisitAction::Continue
+    }
+}
+
+/// A visitor that prints the name of every key it is shown, along with its number of children.
+pub struct PrintVisitor;
+
+impl PrintVisitor {
+    fn print(&self, key: &Key) -> VisitAction {
+
+        println!("--> Processing node: {}, n = {}", key.get_name(), key.get_n());
+
+        VisitAction::Continue
+    }
+}
+
+impl Visitor for PrintVisitor {
+    fn enter_object(&mut self, key: &Key) -> VisitAction {
+        self.print(key)
+    }
+
+    fn enter_array(&mut self, key: &Key) -> VisitAction {
+        self.print(key)
+    }
+
+    fn visit_scalar(&mut self, key: &Key) -> VisitAction {
+        self.print(key)
+    }
+}
+
  /*
-    A depth-first traversal using a combination of recursion and iteration with a stack
+    Checks that the linked list under a key agrees with the key itself: the count n matches the number of
+    nested keys, and only objects and arrays have nested keys. A malformed tree is reported instead of being
+    trusted, so that nothing further down has to unwrap a pointer.
  */
-fn worker<'a>(node_stack: &mut Vec<&'a Box<Key>>, mut ptr: &'a Box<Key>) {
-
-    /*
-        - Loop through siblings at current level
-        - For each sibling: if it has children, recursively process all its children
-        - After recursion returns: continue to next sibling
-        - When no more siblings: return to parent level
-     */
-    loop {
+fn check(name: &str, value_type: &ValueType, n: usize, first: Option<&Key>, depth: usize) -> Result<(), TraverseError> {
+
+    match value_type {
+
+        ValueType::ObjectType | ValueType::ArrayType => {
+
+            if n > 0 && first.is_none() {
+
+                return Err(TraverseError::MissingChildren(name.to_string(), depth));
+            }
+
+            let mut found = 0;
+            let mut current = first;
+
+            while let Some(key) = current {
+
+                found += 1;
+                current = key.get_next().map(|next| next.as_ref());
+            }
+
+            if found != n {
+
+                return Err(TraverseError::CountMismatch(name.to_string(), depth, n, found));
+            }
+        },
+        _ => {
+
+            if first.is_some() {
+
+                return Err(TraverseError::UnexpectedChildren(name.to_string(), depth));
+            }
+        },
+    }
 
-        println!("--> Processing node: {}, n = {}", ptr.get_name(), ptr.get_n());
+    Ok(())
+}
+
+/*
+    Same as check(), plus the nesting limit. A key at depth d (0 for the top level keys) holds an object or an array
+    at nesting level d + 2, the root object being level 1, which is how the parser counts levels as well.
+ */
+fn check_key(key: &Key, depth: usize, max_depth: usize) -> Result<(), TraverseError> {
+
+    if (*key.get_value_type() == ValueType::ObjectType || *key.get_value_type() == ValueType::ArrayType) && depth + 2 > max_depth {
 
-        /* If the node has children then process them */
-        if ptr.get_n() > 0 {
+        return Err(TraverseError::DepthLimitExceeded(max_depth));
+    }
 
-            // Push parent node
-            node_stack.push(ptr);
+    check(key.get_name(), key.get_value_type(), key.get_n(), key.get_ptr().as_deref(), depth)
+}
 
-            // Get first child
-            ptr = ptr.get_ptr().as_ref().unwrap();
+fn check_root(jobj: &JsonObject) -> Result<(), TraverseError> {
 
-            // Do the same for the child, if each child has a child of its own then do the same for that child, if not then stop processing and return             
-            worker (node_stack, ptr);
+    check("", &ValueType::ObjectType, jobj.get_n(), jobj.get_ptr().as_deref(), 0)
+}
 
-            // After returning pop the parent of the last child, now go get the sibling of this parent 
-            ptr = node_stack.pop().unwrap();
-        } 
-        
-        // Now go to the next sibling
-        match ptr.get_next() {
+/// Walks a parsed JSON tree depth first, in document order, and drives a `Visitor` over every key.
+///
+/// The walk keeps its own stack instead of recursing, and it never panics: a key whose count `n`
+/// disagrees with its list of nested keys ends the walk with an error, and so does nesting deeper
+/// than `constants::DEFAULT_MAX_DEPTH`, the parser's default limit.
+///
+/// # Arguments
+/// * `jobj` - The root object, e.g. as returned by `json::parser`
+/// * `visitor` - The callbacks to invoke
+///
+/// # Returns
+/// * `Ok(())` once the traversal is complete, or was stopped by the visitor
+/// * `Err(TraverseError)` if the tree is malformed or too deep
+pub fn traverse<V: Visitor>(jobj: &JsonObject, visitor: &mut V) -> Result<(), TraverseError> {
+
+    traverse_with_max_depth(jobj, visitor, DEFAULT_MAX_DEPTH)
+}
 
-            Some(next) => {
+/// Same as `traverse`, with a nesting limit other than the default one. Use the `max_depth` the tree
+/// was parsed with (`ParserOptions::max_depth`) to accept every tree the parser accepts.
+///
+/// # Arguments
+/// * `jobj` - The root object
+/// * `visitor` - The callbacks to invoke
+/// * `max_depth` - How deep objects and arrays may be nested, the root object counts as the first level
+pub fn traverse_with_max_depth<V: Visitor>(jobj: &JsonObject, visitor: &mut V, max_depth: usize) -> Result<(), TraverseError> {
 
-                ptr = next;
+    check_root(jobj)?;
+
+    // Containers being visited along with what is left of their children, the root has no Key
+    let mut node_stack: Vec<(Option<&Key>, Iter)> = vec![(None, jobj.iter())];
+
+    while let Some((parent, children)) = node_stack.last_mut() {
+
+        let parent = *parent;
+
+        let key = match children.next() {
+
+            Some(key) => key,
+            None => {
+
+                // No more siblings, leave the parent and get back to its own siblings
+                node_stack.pop();
+
+                let leave = match parent {
+
+                    Some(key) if *key.get_value_type() == ValueType::ObjectType => visitor.leave_object(key),
+                    Some(key) => visitor.leave_array(key),
+                    None => VisitAction::Continue,
+                };
+
+                if leave == VisitAction::Stop {
+
+                    return Ok(());
+                }
+
+                continue;
             },
+        };
+
+        let depth = node_stack.len() - 1;
+
+        check_key(key, depth, max_depth)?;
+
+        let enter = match key.get_value_type() {
+
+            ValueType::ObjectType => visitor.enter_object(key),
+            ValueType::ArrayType => visitor.enter_array(key),
+            _ => {
+
+                if visitor.visit_scalar(key) == VisitAction::Stop {
+
+                    return Ok(());
+                }
 
-            None => break, // No more siblings, break the loop and return to parent of the parent
+                continue;
+            },
+        };
+
+        match enter {
+
+            VisitAction::Stop => return Ok(()),
+            VisitAction::SkipSubtree => {},
+            VisitAction::Continue => node_stack.push((Some(key), key.iter())),
         }
     }
+
+    Ok(())
+}
+
+/// Walks a parsed JSON tree in the given order and calls `f` for every key, along with its depth
+/// (0 for the top level keys). The walk keeps its own stack or queue instead of recursing, and never panics.
+///
+/// `f` returns a `VisitAction`: `Stop` ends the walk, `SkipSubtree` leaves out the children of the key
+/// in pre-order and breadth-first order (in post-order they have already been visited, so it is the same as `Continue`).
+///
+/// # Arguments
+/// * `jobj` - The root object
+/// * `order` - Pre-order, post-order or breadth-first
+/// * `f` - Called for every key
+///
+/// # Returns
+/// * `Ok(())` once the walk is complete, or was stopped
+/// * `Err(TraverseError)` if the tree is malformed, or nested deeper than `constants::DEFAULT_MAX_DEPTH`
+pub fn walk<F>(jobj: &JsonObject, order: TraversalOrder, f: F) -> Result<(), TraverseError>
+where
+    F: FnMut(&Key, usize) -> VisitAction,
+{
+    walk_with_max_depth(jobj, order, f, DEFAULT_MAX_DEPTH)
 }
 
-pub fn traverse (parsed_json_of_very_simple_file: &Result<Option<Box<JsonObject>>, io::Error>) -> Result<(), TraverseError> {
+/// Same as `walk`, with a nesting limit other than the default one, see `traverse_with_max_depth`.
+///
+/// # Arguments
+/// * `jobj` - The root object
+/// * `order` - Pre-order, post-order or breadth-first
+/// * `f` - Called for every key
+/// * `max_depth` - How deep objects and arrays may be nested, the root object counts as the first level
+pub fn walk_with_max_depth<F>(jobj: &JsonObject, order: TraversalOrder, mut f: F, max_depth: usize) -> Result<(), TraverseError>
+where
+    F: FnMut(&Key, usize) -> VisitAction,
+{
+    check_root(jobj)?;
+
+    if order == TraversalOrder::BreadthFirst {
+
+        let mut queue: VecDeque<(&Key, usize)> = jobj.iter().map(|key| (key, 0)).collect();
+
+        while let Some((key, depth)) = queue.pop_front() {
+
+            check_key(key, depth, max_depth)?;
 
-    let mut node_stack: Vec<&Box<Key>> = Vec::new();
+            match f(key, depth) {
 
-    match parsed_json_of_very_simple_file {
+                VisitAction::Stop => return Ok(()),
+                VisitAction::SkipSubtree => {},
+                VisitAction::Continue => queue.extend(key.iter().map(|child| (child, depth + 1))),
+            }
+        }
+
+        return Ok(());
+    }
+
+    let mut node_stack: Vec<(Option<&Key>, Iter)> = vec![(None, jobj.iter())];
 
-        Ok(Some(jobj)) => {
+    while let Some((parent, children)) = node_stack.last_mut() {
 
-            let mut ptr = jobj.get_ptr().as_ref().unwrap(); 
-           
-            loop {
+        let parent = *parent;
 
-                // Process node here                
-                println!("-> Processing node: {}, n = {}", ptr.get_name(), ptr.get_n());
+        match children.next() {
 
-                if ptr.get_n() > 0 {
-                
-                    node_stack.push(ptr);
-                
-                    ptr = ptr.get_ptr().as_ref().unwrap();  
-                    
-                    worker (&mut node_stack, &ptr);
+            Some(key) => {
 
-                    ptr = node_stack.pop().unwrap();
+                let depth = node_stack.len() - 1;
+
+                check_key(key, depth, max_depth)?;
+
+                if order == TraversalOrder::PreOrder {
+
+                    match f(key, depth) {
+
+                        VisitAction::Stop => return Ok(()),
+                        VisitAction::SkipSubtree => continue,
+                        VisitAction::Continue => {},
+                    }
                 }
 
-                match ptr.get_next() {
+                node_stack.push((Some(key), key.iter()));
+            },
+            None => {
+
+                node_stack.pop();
+
+                if let (TraversalOrder::PostOrder, Some(key)) = (&order, parent) {
+
+                    if f(key, node_stack.len() - 1) == VisitAction::Stop {
+
+                        return Ok(());
+                    }
+                }
+            },
+        }
+    }
+
+    Ok(())
+}
+
+/// Where a key handed out by `walk_with_paths` sits in the tree.
+pub struct Location<'a, 'b> {
+    path: &'b Path,
+    parents: &'b [&'a Key],
+}
+
+impl<'a, 'b> Location<'a, 'b> {
+    /// Returns the typed path from the root down to the key, array indices included.
+    pub fn path(&self) -> &Path {
+        self.path
+    }
+
+    /// Returns the path as a JSON Pointer, e.g. "/chunks/0/data/0/width".
+    pub fn pointer(&self) -> String {
+        self.path.to_pointer()
+    }
+
+    /// Returns the objects and arrays holding the key, from the top level one down to its direct parent.
+    /// The root object is not a Key and is not part of the chain, so top level keys have no parents.
+    pub fn parents(&self) -> &[&'a Key] {
+        self.parents
+    }
+
+    /// Returns the object or array directly holding the key, None for a top level key.
+    pub fn parent(&self) -> Option<&'a Key> {
+        self.parents.last().copied()
+    }
+
+    /// Returns the nesting depth of the key, 0 for a top level key.
+    pub fn depth(&self) -> usize {
+        self.parents.len()
+    }
+}
+
+/// Walks a parsed JSON tree in pre-order and calls `f` for every key along with its `Location`:
+/// its path (typed and as a JSON Pointer) and the chain of keys holding it.
+/// Returning `SkipSubtree` leaves out the children of the key, `Stop` ends the walk.
+/// Like `walk`, it keeps its own stack and never panics.
+///
+/// # Arguments
+/// * `jobj` - The root object
+/// * `f` - Called for every key
+///
+/// # Returns
+/// * `Ok(())` once the walk is complete, or was stopped
+/// * `Err(TraverseError)` if the tree is malformed, or nested deeper than `constants::DEFAULT_MAX_DEPTH`
+pub fn walk_with_paths<'a, F>(jobj: &'a JsonObject, f: F) -> Result<(), TraverseError>
+where
+    F: FnMut(&'a Key, &Location<'a, '_>) -> VisitAction,
+{
+    walk_with_paths_and_max_depth(jobj, f, DEFAULT_MAX_DEPTH)
+}
+
+/// Same as `walk_with_paths`, with a nesting limit other than the default one, see `traverse_with_max_depth`.
+///
+/// # Arguments
+/// * `jobj` - The root object
+/// * `f` - Called for every key
+/// * `max_depth` - How deep objects and arrays may be nested, the root object counts as the first level
+pub fn walk_with_paths_and_max_depth<'a, F>(jobj: &'a JsonObject, mut f: F, max_depth: usize) -> Result<(), TraverseError>
+where
+    F: FnMut(&'a Key, &Location<'a, '_>) -> VisitAction,
+{
+    check_root(jobj)?;
+
+    let mut path = Path::new();
+    let mut parents: Vec<&Key> = Vec::new();
+
+    // What is left of the children of each container on the way down, along with the index of the next one
+    let mut node_stack: Vec<(Iter, bool, usize)> = vec![(jobj.iter(), false, 0)];
+
+    while let Some((children, is_array, index)) = node_stack.last_mut() {
+
+        let key = match children.next() {
+
+            Some(key) => key,
+            None => {
+
+                node_stack.pop();
+
+                // Back to the siblings of the parent
+                parents.pop();
+                path.pop();
+
+                continue;
+            },
+        };
+
+        path.push(if *is_array { PathSegment::Index(*index) } else { PathSegment::Key(unescape_lossy(key.get_name())) });
+        *index += 1;
+
+        check_key(key, parents.len(), max_depth)?;
+
+        match f(key, &Location { path: &path, parents: &parents }) {
+
+            VisitAction::Stop => return Ok(()),
+            VisitAction::Continue if key.get_n() > 0 => {
+
+                parents.push(key);
+                node_stack.push((key.iter(), *key.get_value_type() == ValueType::ArrayType, 0));
+
+                // The segment of this key stays on the path while its children are walked
+                continue;
+            },
+            _ => {},
+        }
+
+        path.pop();
+    }
+
+    Ok(())
+}
+
+/// What `transform` should do with the key it just handed to the callback.
+#[derive(Debug)]
+pub enum TransformAction {
+    Continue,     // Keep the key, as modified by the callback, and go on with its children
+    SkipSubtree,  // Keep the key but do not walk its children
+    Remove,       // Delete the key and everything under it
+    Replace(Key), // Put this key in its place (it takes over the name of the key it replaces), its children are not walked
+    Stop,         // Keep the key and end the walk, the rest of the tree is left as it is
+}
+
+// A container whose children are being transformed, the root has no Key
+struct Frame {
+    owner: Option<Key>,
+    is_array: bool,
+    pending: std::vec::IntoIter<Key>,
+    done: Vec<Key>,
+}
+
+/// Walks a tree in pre-order and lets a callback rewrite it in the same pass.
+///
+/// The callback gets each key mutably, along with its location, and can change its value or its
+/// `ValueType`, delete it or replace it with a whole new subtree. Changes to a key are made before
+/// its children are walked, so the children seen are the ones the key has after the callback.
+/// Array indices in the paths are positions in the array being rebuilt, i.e. they take earlier
+/// removals into account. Counts, sibling links and the raw text of every object and array are
+/// updated as the walk goes back up. The walk keeps its own stack instead of recursing.
+///
+/// # Arguments
+/// * `jobj` - The tree to transform
+/// * `f` - Called for every key, with its path from the root
+///
+/// # Examples
+/// Stripping every null member:
+/// ```
+/// # use json_rust::json_object::{JsonObject, ValueType};
+/// # use json_rust::helper::{transform, TransformAction};
+/// # let mut jobj = JsonObject::new();
+/// transform(&mut jobj, |key, _path| {
+///     if *key.get_value_type() == ValueType::NullType { TransformAction::Remove } else { TransformAction::Continue }
+/// });
+/// ```
+pub fn transform<F>(jobj: &mut JsonObject, mut f: F)
+where
+    F: FnMut(&mut Key, &Path) -> TransformAction,
+{
+    let mut path = Path::new();
+    let mut stopped = false;
+
+    let mut stack: Vec<Frame> = vec![Frame { owner: None, is_array: false, pending: jobj.take_keys().into_iter(), done: Vec::new() }];
+
+    loop {
+
+        let frame = stack.last_mut().unwrap();
 
-                    Some(next) => {
+        let next = if stopped { None } else { frame.pending.next() };
 
-                        ptr = next;                            
+        let mut key = match next {
+
+            Some(key) => key,
+            None => {
+
+                // Every child of this container has been handled, link them back under it
+                let mut frame = stack.pop().unwrap();
+
+                frame.done.extend(frame.pending);
+
+                match frame.owner {
+
+                    None => {
+
+                        jobj.set_keys(frame.done);
+
+                        return;
+                    },
+                    Some(mut owner) => {
+
+                        owner.set_keys(frame.done);
+                        owner.set_value(owner.to_json_string());
+
+                        path.pop();
+
+                        stack.last_mut().unwrap().done.push(owner);
                     },
+                }
+
+                continue;
+            },
+        };
+
+        path.push(if frame.is_array { PathSegment::Index(frame.done.len()) } else { PathSegment::Key(unescape_lossy(key.get_name())) });
+
+        match f(&mut key, &path) {
+
+            TransformAction::Continue => {
+
+                let is_array = match key.get_value_type() {
+
+                    ValueType::ObjectType => false,
+                    ValueType::ArrayType => true,
+                    _ => {
+
+                        // A container turned into a scalar drops the children it had, they are not walked
+                        key.take_keys();
+
+                        frame.done.push(key);
+                        path.pop();
+
+                        continue;
+                    },
+                };
+
+                // Descend, the path keeps the segment of this key until its frame is done
+                let children = key.take_keys();
+
+                stack.push(Frame { owner: Some(key), is_array, pending: children.into_iter(), done: Vec::new() });
+
+                continue;
+            },
+            TransformAction::SkipSubtree => frame.done.push(key),
+            TransformAction::Remove => {},
+            TransformAction::Replace(mut replacement) => {
+
+                replacement.set_name(key.get_name().to_string());
+                frame.done.push(replacement);
+            },
+            TransformAction::Stop => {
+
+                frame.done.push(key);
+                stopped = true;
+            },
+        }
+
+        path.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::{parse_str, ParserOptions};
+
+    fn parse(text: &str) -> JsonObject {
+        *parse_str(text, &ParserOptions::default()).unwrap().unwrap()
+    }
+
+    #[test]
+    fn transform_removes_nulls() {
+        let mut jobj = parse(r#"{"a": null, "b": {"c": null, "d": [1, null, 2]}}"#);
 
-                    None => break,                    
-                }                                
+        transform(&mut jobj, |key, _path| {
+            if *key.get_value_type() == ValueType::NullType { TransformAction::Remove } else { TransformAction::Continue }
+        });
+
+        assert_eq!(jobj.to_json_string(), r#"{"b":{"d":[1,2]}}"#);
+    }
+
+    #[test]
+    fn container_turned_scalar_drops_its_children() {
+        let mut jobj = parse(r#"{"a": {"b": 1, "c": {"d": 2}}, "e": [3, 4]}"#);
+        let mut visited = Vec::new();
+
+        transform(&mut jobj, |key, path| {
+            visited.push(path.to_string());
+
+            if *key.get_value_type() != ValueType::NumberType {
+                key.set_value_type(ValueType::NumberType);
+                key.set_value("0".to_string());
             }
 
-            Ok(())
-        },
-        Ok(None) => {
+            TransformAction::Continue
+        });
 
-            // Return an error indicating no JSON object was found
-            return Err(TraverseError::NoJsonObject);
-        },
-        Err(e) => {
+        assert_eq!(visited, ["/a", "/e"]);
+        assert_eq!(jobj.to_json_string(), r#"{"a":0,"e":0}"#);
+        assert!(jobj.iter().all(|key| key.get_n() == 0));
+    }
+
+    // Member names, or the values of array elements
+    fn label(key: &Key) -> String {
+        if key.get_name().is_empty() { key.get_value().to_string() } else { key.get_name().to_string() }
+    }
+
+    fn walked(jobj: &JsonObject, order: TraversalOrder) -> Vec<(String, usize)> {
+        let mut ret = Vec::new();
+
+        walk(jobj, order, |key, depth| {
+            ret.push((label(key), depth));
+            VisitAction::Continue
+        }).unwrap();
+
+        ret
+    }
+
+    fn names(visited: &[(String, usize)]) -> Vec<&str> {
+        visited.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    #[test]
+    fn walk_orders() {
+        let jobj = parse(r#"{"a": {"b": 1, "c": [2, 3]}, "d": 4}"#);
+
+        let pre = walked(&jobj, TraversalOrder::PreOrder);
+        assert_eq!(names(&pre), ["a", "b", "c", "2", "3", "d"]);
+        assert_eq!(pre.iter().map(|(_, depth)| *depth).collect::<Vec<_>>(), [0, 1, 1, 2, 2, 0]);
+
+        let post = walked(&jobj, TraversalOrder::PostOrder);
+        assert_eq!(names(&post), ["b", "2", "3", "c", "a", "d"]);
+        assert_eq!(post.iter().map(|(_, depth)| *depth).collect::<Vec<_>>(), [1, 2, 2, 1, 0, 0]);
+
+        let breadth = walked(&jobj, TraversalOrder::BreadthFirst);
+        assert_eq!(names(&breadth), ["a", "d", "b", "c", "2", "3"]);
+        assert_eq!(breadth.iter().map(|(_, depth)| *depth).collect::<Vec<_>>(), [0, 0, 1, 1, 2, 2]);
+    }
+
+    #[test]
+    fn walk_skip_and_stop() {
+        let jobj = parse(r#"{"a": {"b": 1}, "c": [2], "d": 3}"#);
+
+        for order in [TraversalOrder::PreOrder, TraversalOrder::BreadthFirst] {
+            let mut visited = Vec::new();
+
+            walk(&jobj, order, |key, _| {
+                visited.push(label(key));
+                if key.get_name() == "a" { VisitAction::SkipSubtree } else { VisitAction::Continue }
+            }).unwrap();
+
+            assert!(!visited.contains(&"b".to_string()));
+            assert!(visited.contains(&"2".to_string()));
+        }
+
+        let mut visited = Vec::new();
+
+        walk(&jobj, TraversalOrder::PostOrder, |key, _| {
+            visited.push(label(key));
+            if key.get_name() == "a" { VisitAction::Stop } else { VisitAction::Continue }
+        }).unwrap();
+
+        assert_eq!(visited, ["b", "a"]);
+    }
+
+    #[test]
+    fn walk_malformed_trees() {
+        let orders = [TraversalOrder::PreOrder, TraversalOrder::PostOrder, TraversalOrder::BreadthFirst];
+
+        // n > 0 without any key in the list
+        let mut jobj = JsonObject::new();
+        jobj.set_n(2);
+
+        for order in orders.clone() {
+            assert!(matches!(walk(&jobj, order, |_, _| VisitAction::Continue), Err(TraverseError::MissingChildren(_, 0))));
+        }
+
+        // n disagrees with the list
+        let mut jobj = parse(r#"{"a": 1}"#);
+        jobj.set_n(3);
+
+        for order in orders.clone() {
+            assert!(matches!(walk(&jobj, order, |_, _| VisitAction::Continue), Err(TraverseError::CountMismatch(_, 0, 3, 1))));
+        }
+
+        // A string with nested keys
+        let mut key = Key::new("s".to_string(), ValueType::StringType, "x".to_string());
+        key.set_keys(vec![Key::new(String::new(), ValueType::NullType, "null".to_string())]);
 
-            // Convert the io::Error to TraverseError and return it
-            //return Err(TraverseError::IoError((*e).clone()));
-            return Err(TraverseError::IoError(e.to_string()));
+        let mut jobj = JsonObject::new();
+        jobj.set_keys(vec![key]);
+
+        for order in orders {
+            assert!(matches!(walk(&jobj, order, |_, _| VisitAction::Continue), Err(TraverseError::UnexpectedChildren(name, 0)) if name == "s"));
+        }
+    }
+
+    #[test]
+    fn walk_depth_limit() {
+        let depth = DEFAULT_MAX_DEPTH + 10;
+        let text = format!("{{\"a\": {}1{}}}", "[".repeat(depth), "]".repeat(depth));
+
+        let options = ParserOptions { max_depth: depth + 2, ..ParserOptions::default() };
+        let jobj = *parse_str(&text, &options).unwrap().unwrap();
+
+        assert!(matches!(walk(&jobj, TraversalOrder::PreOrder, |_, _| VisitAction::Continue), Err(TraverseError::DepthLimitExceeded(DEFAULT_MAX_DEPTH))));
+
+        for order in [TraversalOrder::PreOrder, TraversalOrder::PostOrder, TraversalOrder::BreadthFirst] {
+            let mut count = 0;
+
+            walk_with_max_depth(&jobj, order, |_, _| { count += 1; VisitAction::Continue }, options.max_depth).unwrap();
+
+            assert_eq!(count, depth + 1);
+        }
+    }
+
+    #[test]
+    fn locations() {
+        let jobj = parse(r#"{"a/b": [{"c~d": 1}, [2]], "e": {"f": null}}"#);
+        let mut visited = Vec::new();
+
+        walk_with_paths(&jobj, |_, location| {
+            let parents: Vec<String> = location.parents().iter().map(|parent| parent.get_name().to_string()).collect();
+
+            assert_eq!(location.depth(), parents.len());
+            assert_eq!(location.parent().map(|parent| parent.get_name().to_string()), parents.last().cloned());
+            assert_eq!(location.path().to_pointer(), location.pointer());
+
+            visited.push((location.pointer(), parents));
+            VisitAction::Continue
+        }).unwrap();
+
+        let expected: [(&str, &[&str]); 7] = [
+            ("/a~1b", &[]),
+            ("/a~1b/0", &["a/b"]),
+            ("/a~1b/0/c~0d", &["a/b", ""]),
+            ("/a~1b/1", &["a/b"]),
+            ("/a~1b/1/0", &["a/b", ""]),
+            ("/e", &[]),
+            ("/e/f", &["e"]),
+        ];
+
+        assert_eq!(visited.len(), expected.len());
+
+        for ((pointer, parents), (expected_pointer, expected_parents)) in visited.iter().zip(expected) {
+            assert_eq!(pointer, expected_pointer);
+            assert_eq!(parents, expected_parents);
         }
-    }    
+    }
+
+    #[test]
+    fn locations_skip_stop_and_depth() {
+        let jobj = parse(r#"{"a": [1, 2], "b": {"c": 3}, "d": 4}"#);
+        let mut visited = Vec::new();
+
+        walk_with_paths(&jobj, |key, location| {
+            visited.push(location.pointer());
+            match key.get_name() {
+                "a" => VisitAction::SkipSubtree,
+                "c" => VisitAction::Stop,
+                _ => VisitAction::Continue,
+            }
+        }).unwrap();
+
+        assert_eq!(visited, ["/a", "/b", "/b/c"]);
+
+        let text = format!("{{\"a\": {}1{}}}", "[".repeat(DEFAULT_MAX_DEPTH), "]".repeat(DEFAULT_MAX_DEPTH));
+        let max_depth = DEFAULT_MAX_DEPTH + 2;
+        let jobj = *parse_str(&text, &ParserOptions { max_depth, ..ParserOptions::default() }).unwrap().unwrap();
+
+        assert!(matches!(walk_with_paths(&jobj, |_, _| VisitAction::Continue), Err(TraverseError::DepthLimitExceeded(DEFAULT_MAX_DEPTH))));
+
+        let mut deepest = String::new();
+        walk_with_paths_and_max_depth(&jobj, |_, location| { deepest = location.pointer(); VisitAction::Continue }, max_depth).unwrap();
+        assert_eq!(deepest, format!("/a{}", "/0".repeat(DEFAULT_MAX_DEPTH)));
+    }
+
+    // Records every call, and answers with the action given for it, Continue otherwise
+    struct Recorder {
+        events: Vec<String>,
+        actions: Vec<(&'static str, VisitAction)>,
+    }
+
+    impl Recorder {
+        fn new(actions: &[(&'static str, VisitAction)]) -> Self {
+            Recorder { events: Vec::new(), actions: actions.to_vec() }
+        }
+
+        fn record(&mut self, call: &str, key: &Key) -> VisitAction {
+            let event = format!("{} {}", call, label(key));
+            let action = self.actions.iter().find(|(other, _)| *other == event).map_or(VisitAction::Continue, |(_, action)| action.clone());
+
+            self.events.push(event);
+
+            action
+        }
+    }
+
+    impl Visitor for Recorder {
+        fn enter_object(&mut self, key: &Key) -> VisitAction {
+            self.record("enter_object", key)
+        }
+
+        fn leave_object(&mut self, key: &Key) -> VisitAction {
+            self.record("leave_object", key)
+        }
+
+        fn enter_array(&mut self, key: &Key) -> VisitAction {
+            self.record("enter_array", key)
+        }
+
+        fn leave_array(&mut self, key: &Key) -> VisitAction {
+            self.record("leave_array", key)
+        }
+
+        fn visit_scalar(&mut self, key: &Key) -> VisitAction {
+            self.record("visit_scalar", key)
+        }
+    }
+
+    fn traversed(jobj: &JsonObject, actions: &[(&'static str, VisitAction)]) -> Vec<String> {
+        let mut recorder = Recorder::new(actions);
+
+        traverse(jobj, &mut recorder).unwrap();
+
+        recorder.events
+    }
+
+    const DOCUMENT: &str = r#"{"a": {"b": 1, "c": [2, {"d": true}]}, "e": [], "f": null}"#;
+
+    #[test]
+    fn traverse_order() {
+        let events = traversed(&parse(DOCUMENT), &[]);
+
+        assert_eq!(events, [
+            "enter_object a",
+            "visit_scalar b",
+            "enter_array c",
+            "visit_scalar 2",
+            "enter_object {\"d\": true}",
+            "visit_scalar d",
+            "leave_object {\"d\": true}",
+            "leave_array c",
+            "leave_object a",
+            "enter_array e",
+            "leave_array e",
+            "visit_scalar f",
+        ]);
+
+        // Every leave_* closes the last container entered and not yet left
+        let mut open = Vec::new();
+
+        for event in &events {
+            if let Some(container) = event.strip_prefix("enter_") {
+                open.push(container.to_string());
+            } else if let Some(container) = event.strip_prefix("leave_") {
+                assert_eq!(open.pop().as_deref(), Some(container));
+            }
+        }
+
+        assert!(open.is_empty());
+    }
+
+    #[test]
+    fn traverse_skip() {
+        let jobj = parse(DOCUMENT);
+
+        // Neither the children nor the matching leave_* of a skipped container are visited
+        let events = traversed(&jobj, &[("enter_array c", VisitAction::SkipSubtree)]);
+        assert_eq!(events, ["enter_object a", "visit_scalar b", "enter_array c", "leave_object a", "enter_array e", "leave_array e", "visit_scalar f"]);
+
+        let events = traversed(&jobj, &[("enter_object a", VisitAction::SkipSubtree), ("enter_array e", VisitAction::SkipSubtree)]);
+        assert_eq!(events, ["enter_object a", "enter_array e", "visit_scalar f"]);
+
+        // There is nothing left to skip after a scalar or a leave_*, it goes on as with Continue
+        let events = traversed(&jobj, &[("visit_scalar b", VisitAction::SkipSubtree), ("leave_array c", VisitAction::SkipSubtree)]);
+        assert_eq!(events, traversed(&jobj, &[]));
+    }
+
+    #[test]
+    fn traverse_stop() {
+        let jobj = parse(DOCUMENT);
+
+        let events = traversed(&jobj, &[("enter_array c", VisitAction::Stop)]);
+        assert_eq!(events, ["enter_object a", "visit_scalar b", "enter_array c"]);
+
+        let events = traversed(&jobj, &[("visit_scalar 2", VisitAction::Stop)]);
+        assert_eq!(events, ["enter_object a", "visit_scalar b", "enter_array c", "visit_scalar 2"]);
+
+        let events = traversed(&jobj, &[("leave_object a", VisitAction::Stop)]);
+        assert_eq!(events.last().map(String::as_str), Some("leave_object a"));
+        assert_eq!(events.len(), 9);
+    }
+
+    #[test]
+    fn traverse_errors_and_depth() {
+        let mut jobj = parse(r#"{"a": [1, 2]}"#);
+        jobj.set_n(2);
+
+        assert!(matches!(traverse(&jobj, &mut Recorder::new(&[])), Err(TraverseError::CountMismatch(_, 0, 2, 1))));
+        assert!(matches!(traverse(&JsonObject::new(), &mut Recorder::new(&[])), Ok(())));
+
+        let text = format!("{{\"a\": {}1{}}}", "[".repeat(DEFAULT_MAX_DEPTH), "]".repeat(DEFAULT_MAX_DEPTH));
+        let max_depth = DEFAULT_MAX_DEPTH + 2;
+        let jobj = *parse_str(&text, &ParserOptions { max_depth, ..ParserOptions::default() }).unwrap().unwrap();
+
+        assert!(matches!(traverse(&jobj, &mut Recorder::new(&[])), Err(TraverseError::DepthLimitExceeded(DEFAULT_MAX_DEPTH))));
+
+        let mut recorder = Recorder::new(&[]);
+        traverse_with_max_depth(&jobj, &mut recorder, max_depth).unwrap();
+
+        let enters = recorder.events.iter().filter(|event| event.starts_with("enter_array")).count();
+        let leaves = recorder.events.iter().filter(|event| event.starts_with("leave_array")).count();
+        assert_eq!((enters, leaves, recorder.events.len()), (DEFAULT_MAX_DEPTH, DEFAULT_MAX_DEPTH, 2 * DEFAULT_MAX_DEPTH + 1));
+    }
 }
@@ -7,14 +7,218 @@
 //use std::fs::File;
 //use std::io::{self, Read}; 
 
-use std::{env, io, fs::File, io::Read};
+use std::{env, fmt, io, fs::File, io::Read, collections::HashMap, sync::OnceLock};
 use regex::Regex;
 
 use crate::file_content::FileContent;
 use crate::constants::{JSON_OPENIING_BRACE, JSON_CLOSING_BRACE, JSON_OPENING_BRACE_REG_EXPR_PATTERN, JSON_CLOSING_BRACE_REG_EXPR_PATTERN, JSON_KEY_REG_EXPR_PATTERN, JSON_OPENING_SQUARE_BRACKET_PATTERN_FOR_ARRAY_TYPE, JSON_CLOSING_SQUARE_BRACKET_PATTERN_FOR_ARRAY_TYPE, JSON_VALUE_TYPE_STRING_REG_EXPR_PATTERN, JSON_QUOTED_CONTENT_PATTERN, JSON_VALUE_TYPE_NUMERIC_PATTERN, JSON_SINGLE_LINE_ARRAY_TYPE_PATTERN, JSON_SINGLE_LINE_ARRAY_TYPE_PATTERN_VALUE_STRING, JSON_VALUE_OPENING_BRACE_REG_EXPR_PATTERN, JSON_VALUE_CLOSING_BRACE_REG_EXPR_PATTERN, JSON_SINGLE_LINE_OBJECT_TYPE_KEY_NAME_WITH_OPENING_CLOSING_BRACE_PATTERN, JSON_VALUE_TYPE_NULL_PATTERN, JSON_VALUE_TYPE_FALSE_PATTERN, JSON_VALUE_TYPE_TRUE_PATTERN};
-use crate::json_object::{ValueType, Key, JsonKeyPtr, JsonObject};
+use crate::json_object::{ValueType, Key, JsonKeyPtr, JsonObject, Position, Span};
+use crate::constants::{DEFAULT_MAX_DEPTH, CAPTURE_NUMBER_PATTERN};
+use crate::escape::{unescape, unescape_lossy, EscapeError};
+use crate::json5;
+
+// Document statistics, reachable as json::stats() next to the parser
+pub use crate::stats::{stats, DocumentStats};
+
+/// Errors that can occur while parsing a JSON file.
+#[derive(Debug)]
+pub enum ParseError {
+    IoError(io::Error),         // The file could not be read
+    DepthLimitExceeded(usize),  // Objects and arrays are nested deeper than the limit, which is given
+    DuplicateKey(String, Position, Position), // A member name used twice in one object, the name and where both members start
+    InvalidNumber(String, Position),          // A literal that is not null, true, false or a number of the JSON grammar, and where it starts
+    InputSizeLimitExceeded(usize),            // The input is larger than the limit, in bytes, which is given
+    StringLengthLimitExceeded(usize, Position), // A string value is longer than the limit, and where it starts
+    KeyLengthLimitExceeded(usize, Position),  // A member name is longer than the limit, and where it starts
+    MemberLimitExceeded(usize, Position),     // An object has more members than the limit, and where it starts
+    ElementLimitExceeded(usize, Position),    // An array has more elements than the limit, and where it starts
+    NodeLimitExceeded(usize),                 // The tree has more keys, all levels together, than the limit
+    InvalidJson5(String, Position),           // JSON5 text that cannot be rewritten as JSON, what is wrong and where
+    InvalidEscape(EscapeError, Position),     // A string or member name with an escape sequence that is not JSON, and where it starts
+    UnterminatedString(Position),             // A string still open at the end of the input, e.g. because its closing quote is escaped, and where it starts
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::IoError(e) => write!(f, "{}", e),
+            ParseError::DepthLimitExceeded(max_depth) => write!(f, "objects and arrays are nested deeper than {} levels", max_depth),
+            ParseError::InvalidNumber(literal, at) => write!(f, "invalid literal \"{}\" at line {}, column {}", literal, at.line, at.column),
+            ParseError::InputSizeLimitExceeded(limit) => write!(f, "the input is larger than {} bytes", limit),
+            ParseError::StringLengthLimitExceeded(limit, at) => write!(f, "string longer than {} bytes at line {}, column {}", limit, at.line, at.column),
+            ParseError::KeyLengthLimitExceeded(limit, at) => write!(f, "member name longer than {} bytes at line {}, column {}", limit, at.line, at.column),
+            ParseError::MemberLimitExceeded(limit, at) => write!(f, "object with more than {} members at line {}, column {}", limit, at.line, at.column),
+            ParseError::ElementLimitExceeded(limit, at) => write!(f, "array with more than {} elements at line {}, column {}", limit, at.line, at.column),
+            ParseError::NodeLimitExceeded(limit) => write!(f, "more than {} keys in the document", limit),
+            ParseError::InvalidJson5(message, at) => write!(f, "{} at line {}, column {}", message, at.line, at.column),
+            ParseError::InvalidEscape(e, at) => write!(f, "invalid string, {:?}, at line {}, column {}", e, at.line, at.column),
+            ParseError::UnterminatedString(at) => write!(f, "string not closed before the end of the input at line {}, column {}", at.line, at.column),
+            ParseError::DuplicateKey(name, first, second) => write!(f, "duplicate member \"{}\" at line {}, column {}, first seen at line {}, column {}", name, second.line, second.column, first.line, first.column),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<io::Error> for ParseError {
+    fn from(err: io::Error) -> Self {
+        ParseError::IoError(err)
+    }
+}
+
+/*
+    parser() predates ParseError and reports every failure as an io::Error. Errors that are not about
+    reading the file become io::ErrorKind::InvalidData, with the ParseError inside. It can be recovered with
+    err.get_ref().and_then(|e| e.downcast_ref::<ParseError>())
+ */
+impl From<ParseError> for io::Error {
+    fn from(err: ParseError) -> Self {
+        match err {
+            ParseError::IoError(e) => e,
+            other => io::Error::new(io::ErrorKind::InvalidData, other),
+        }
+    }
+}
+
+/// What the parser does with an object that has more than one member of the same name.
+/// Names are compared once their escape sequences are decoded. Lookups (from_json, JSON Pointers, patches, ...)
+/// take the first member of a name, so with any policy but KeepAll they see the one the policy chose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateKeyPolicy {
+    Error,     // Fail with ParseError::DuplicateKey, e.g. for {"role": "user", "role": "admin"}
+    FirstWins, // Keep the first member, drop the later ones
+    LastWins,  // Keep the value of the last member, in the place of the first one
+    #[default]
+    KeepAll,   // Keep every member in the order they appear
+}
+
+/// What the parser does with a literal that is not null, true or false.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumberMode {
+    #[default]
+    Validate, // Fail with ParseError::InvalidNumber unless it is a number of the JSON grammar
+    Keep,     // Keep it as a NumberType key whatever it is, what the parser did before numbers were validated
+}
+
+/// What the parser does with the escape sequences of strings and member names, which are kept as written either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EscapeMode {
+    #[default]
+    Keep,     // Leave them to whatever decodes the string later
+    Validate, // Fail with ParseError::InvalidEscape unless they all decode
+}
+
+/// Settings that change how a JSON file is parsed.
+/// The limits guard against hostile input, all but `max_depth` are off (usize::MAX) by default.
+/// Lengths are in bytes of the raw text, escape sequences as they are written.
+#[derive(Debug, Clone)]
+pub struct ParserOptions {
+    pub max_depth: usize,                   // How deep objects and arrays may be nested, the root object counts as the first level
+    pub duplicate_keys: DuplicateKeyPolicy, // What to do with a member name used twice in one object
+    pub max_input_size: usize,              // Size of the file or text, in bytes
+    pub max_string_length: usize,           // Length of a string value
+    pub max_key_length: usize,              // Length of a member name
+    pub max_members: usize,                 // Members of one object, the root object included
+    pub max_elements: usize,                // Elements of one array
+    pub max_nodes: usize,                   // Keys in the whole tree, members and elements at every level
+    pub json5: bool,                        // Read the input as JSON5, see json5.rs, instead of JSON
+    pub numbers: NumberMode,                // What to do with a literal that is not a number of the JSON grammar
+    pub escapes: EscapeMode,                // Whether escape sequences are checked
+    pub keep_raw: bool,                     // Keep the text of objects and arrays as the value of their keys, get_value() is empty otherwise
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        ParserOptions {
+            max_depth: DEFAULT_MAX_DEPTH,
+            duplicate_keys: DuplicateKeyPolicy::default(),
+            max_input_size: usize::MAX,
+            max_string_length: usize::MAX,
+            max_key_length: usize::MAX,
+            max_members: usize::MAX,
+            max_elements: usize::MAX,
+            max_nodes: usize::MAX,
+            json5: false,
+            numbers: NumberMode::default(),
+            escapes: EscapeMode::default(),
+            keep_raw: true,
+        }
+    }
+}
+
+/// Buffers the parser reads into, a Parser keeps them from one parse to the next so they are allocated once.
+#[derive(Default)]
+pub(crate) struct Scratch {
+    positions: Vec<Position>, // Where each character of a top level object or array is
+    levels: Vec<Buffers>,     // The buffers of each nesting level, the root object is at 1
+}
+
+// The name, the value and the string being read at one nesting level
+#[derive(Default)]
+struct Buffers {
+    key: String,
+    value: String,
+    neutral: String,
+}
+
+impl Scratch {
+
+    // Takes the buffers of a level out, empty, put() gives them back once the level is done
+    fn take(&mut self, depth: usize) -> Buffers {
+
+        if self.levels.len() <= depth {
+
+            self.levels.resize_with(depth + 1, Buffers::default);
+        }
+
+        let mut buffers = std::mem::take(&mut self.levels[depth]);
+        buffers.key.clear();
+        buffers.value.clear();
+        buffers.neutral.clear();
+
+        buffers
+    }
+
+    fn put(&mut self, depth: usize, buffers: Buffers) {
+
+        self.levels[depth] = buffers;
+    }
+}
+
+/// Recursive helper that parses the raw text of an object or an array and adds its members or
+/// elements to `key`. Nested objects and arrays are handed to a new call of this function.
+/// The spans of the keys it adds are relative to `line`, taken as the first line of a file.
+///
+/// # Arguments
+/// * `line` - The raw text of the object or array, braces or brackets included
+/// * `key` - The key holding the object or array
+/// * `depth` - The nesting level of the object or array, the root object being at level 1
+/// * `options` - The parser settings, `max_depth` in particular
+///
+/// # Returns
+/// * `Err(ParseError::DepthLimitExceeded)` if `depth`, or the depth of anything nested inside, is over the limit
+pub fn helper_for_object_and_array_types (line: &str, key: &mut Key, depth: usize, options: &ParserOptions) -> Result<(), ParseError> {
+
+    let mut positions = Vec::new();
+
+    for (column, (offset, _)) in line.char_indices().enumerate() {
+
+        positions.push(Position { line: 1, column: column + 1, offset });
+    }
+
+    parse_container(line, &positions, key, depth, options, &mut 0, &mut Scratch::default())
+}
+
+// Does the work of helper_for_object_and_array_types, positions[i] is where the i-th character of line is in the file,
+// nodes counts the keys added so far to the whole tree, scratch holds the buffers of every level
+fn parse_container (line: &str, positions: &[Position], key: &mut Key, depth: usize, options: &ParserOptions, nodes: &mut usize, scratch: &mut Scratch) -> Result<(), ParseError> {
+
+    // Every level of nesting is one more call of this function, bail out before the stack grows any further
+    if depth > options.max_depth {
+
+        return Err(ParseError::DepthLimitExceeded(options.max_depth));
+    }
 
-pub fn helper_for_object_and_array_types (line: &str, key: &mut Key) {
    // State Machine
     let mut array_type_encountered = false;
     let mut array_type_encountered_count: usize = 0;
@@ -28,14 +232,25 @@ pub fn helper_for_object_and_array_types (line: &str, key: &mut Key) {
     let mut start_of_value_string_encountered = false;
     let mut end_of_value_string_encountered = false;
 
+    // A backslash was just read inside a string, the character after it does not end the string
+    let mut escaped = false;
+    // The container being collected is in one of its strings, where brackets, braces and commas are text
+    let mut in_string_of_container = false;
+
     let mut i: usize = 0;
+    // i counts characters, the closing brace or bracket is the last character, not the last byte
+    let last = line.chars().count().saturating_sub(1);
 
-    let mut key_of_pair = String::new();
-    let mut value_of_pair = String::new();
-    let mut neutral_string = String::new();
+    let Buffers { key: mut key_of_pair, value: mut value_of_pair, neutral: mut neutral_string } = scratch.take(depth);
 
     let mut json_object = JsonObject::new();
 
+    // Where the string, scalar or container being read starts and ends, and the span of the name it belongs to
+    let mut token_start = Position::default();
+    let mut token_end = Position::default();
+    let mut name_span: Option<Span> = None;
+    let mut container_start: usize = 0;
+
     // Main parsing loop - processes each line of the file
     
         
@@ -47,6 +262,8 @@ pub fn helper_for_object_and_array_types (line: &str, key: &mut Key) {
         // Process each character in the line
         while let Some(ch) = line_of_peekable_chars.next() {
 
+            let position = positions[i];
+
             //println! ("-------->>>>>>>>>>> {}, {}, {}", line.chars().count(), i, line.len());
 
             /*if i == (line.len() - 1) && ch == '}' {
@@ -55,7 +272,7 @@ pub fn helper_for_object_and_array_types (line: &str, key: &mut Key) {
             }*/
                       
             // Handle JSON root object markers (first '{' and last '}')
-            if ((ch == '{' && i == 0) || (ch == '}' && i == (line.len() - 1))) || ((ch == '[' && i == 0) || (ch == ']' && i == (line.len() - 1))) || ch == '\n' { // Ignore JSON root object
+            if ((ch == '{' && i == 0) || (ch == '}' && i == last)) || ((ch == '[' && i == 0) || (ch == ']' && i == last)) || ch == '\n' { // Ignore JSON root object
 
                 //println! ("Hola...... ");
 
@@ -70,8 +287,11 @@ pub fn helper_for_object_and_array_types (line: &str, key: &mut Key) {
 
                     value_of_pair = neutral_string.clone();
                                         
-                    let lkey = Box::new(Key::new(key_of_pair.clone(), ValueType::StringType, value_of_pair.clone()));                   
-                    key.add_key(lkey); 
+                    check_length(&value_of_pair, options.max_string_length, token_start, ParseError::StringLengthLimitExceeded)?;
+                    check_escapes(&value_of_pair, token_start, options)?;
+                    let mut lkey = Box::new(Key::new(key_of_pair.clone(), ValueType::StringType, value_of_pair.clone()));                   
+                    lkey.set_spans(name_span.take(), Some(Span { start: token_start, end: token_end }));
+                    append(key, lkey, options, nodes)?;
 
                     // Cleanup
                     key_of_pair.clear();
@@ -89,22 +309,26 @@ pub fn helper_for_object_and_array_types (line: &str, key: &mut Key) {
 
                     if value_of_pair.clone().trim() == "null" { // Null
 
-                        let lkey = Box::new(Key::new(key_of_pair.clone(), ValueType::NullType, value_of_pair.clone()));
-                        key.add_key(lkey);
+                        let mut lkey = Box::new(Key::new(key_of_pair.clone(), ValueType::NullType, value_of_pair.clone()));
+                        lkey.set_spans(name_span.take(), Some(Span { start: token_start, end: token_end }));
+                        append(key, lkey, options, nodes)?;
   
                     } else if value_of_pair.clone().trim() == "true" || value_of_pair.clone().trim() == "false" { // Boolean
 
                         /*println! ("Yes we are....");*/
 
-                        let lkey = Box::new(Key::new(key_of_pair.clone(), ValueType::BooleanType, value_of_pair.clone()));
-                        key.add_key(lkey);
+                        let mut lkey = Box::new(Key::new(key_of_pair.clone(), ValueType::BooleanType, value_of_pair.clone()));
+                        lkey.set_spans(name_span.take(), Some(Span { start: token_start, end: token_end }));
+                        append(key, lkey, options, nodes)?;
 
                     } else { // Number
                     
                         //if value_of_pair.len() > 0 {
 
-                            let lkey = Box::new(Key::new(key_of_pair.clone(), ValueType::NumberType, value_of_pair.clone()));
-                            key.add_key(lkey);
+                            check_number(&value_of_pair, token_start, options)?;
+                            let mut lkey = Box::new(Key::new(key_of_pair.clone(), ValueType::NumberType, value_of_pair.clone()));
+                            lkey.set_spans(name_span.take(), Some(Span { start: token_start, end: token_end }));
+                            append(key, lkey, options, nodes)?;
                         //}
                     }
 
@@ -118,7 +342,35 @@ pub fn helper_for_object_and_array_types (line: &str, key: &mut Key) {
                 
                 continue;  // Skip processing further below              
             }
-            if !object_type_encountered && ch == '[' {
+            // Inside the strings of the container being collected, brackets and braces are text and \" does not end the string
+            if (array_type_encountered || object_type_encountered) && (in_string_of_container || ch == '"') {
+
+                value_of_pair.push(ch);
+                i += 1;
+
+                if !in_string_of_container {
+
+                    in_string_of_container = true;
+                } else if escaped {
+
+                    escaped = false;
+                } else if ch == '\\' {
+
+                    escaped = true;
+                } else if ch == '"' {
+
+                    in_string_of_container = false;
+                }
+
+                continue;
+            }
+            if !object_type_encountered && !start_of_string_encountered && ch == '[' {
+
+                if array_type_encountered_count == 0 {
+
+                    token_start = position;
+                    container_start = i;
+                }
 
                 value_of_pair.push(ch);
 
@@ -139,12 +391,15 @@ pub fn helper_for_object_and_array_types (line: &str, key: &mut Key) {
 
                     array_type_encountered = false;
 
+                    token_end = position_after(position, ch);
+
                     // Here we have a complete key/value pair of Object type, add it to the json tree
                     //println! ("{} / {}", key_of_pair, value_of_pair);
 
-                    let mut lkey = Box::new(Key::new(key_of_pair.clone(), ValueType::ArrayType, value_of_pair.clone()));
-                    helper_for_object_and_array_types(&value_of_pair.clone(), &mut lkey);
-                    key.add_key(lkey);
+                    let mut lkey = Box::new(Key::new(key_of_pair.clone(), ValueType::ArrayType, raw_text(&value_of_pair, options)));
+                    lkey.set_spans(name_span.take(), Some(Span { start: token_start, end: token_end }));
+                    parse_container(&value_of_pair, &positions[container_start..=i], &mut lkey, depth + 1, options, nodes, scratch)?;
+                    append(key, lkey, options, nodes)?;
 
                     // Cleanup
                     key_of_pair.clear();
@@ -163,7 +418,13 @@ pub fn helper_for_object_and_array_types (line: &str, key: &mut Key) {
 
                 continue;
             }             
-            if !array_type_encountered && ch == '{' {
+            if !array_type_encountered && !start_of_string_encountered && ch == '{' {
+
+                if object_type_encountered_count == 0 {
+
+                    token_start = position;
+                    container_start = i;
+                }
 
                 value_of_pair.push(ch);
 
@@ -184,12 +445,15 @@ pub fn helper_for_object_and_array_types (line: &str, key: &mut Key) {
 
                     object_type_encountered = false;
 
+                    token_end = position_after(position, ch);
+
                     // Here we have a complete key/value pair of Object type, add it to the json tree
                     //println! ("{} / {}", key_of_pair, value_of_pair);
 
-                    let mut lkey = Box::new(Key::new(key_of_pair.clone(), ValueType::ObjectType, value_of_pair.clone()));
-                    helper_for_object_and_array_types(&value_of_pair.clone(), &mut lkey);
-                    key.add_key(lkey);
+                    let mut lkey = Box::new(Key::new(key_of_pair.clone(), ValueType::ObjectType, raw_text(&value_of_pair, options)));
+                    lkey.set_spans(name_span.take(), Some(Span { start: token_start, end: token_end }));
+                    parse_container(&value_of_pair, &positions[container_start..=i], &mut lkey, depth + 1, options, nodes, scratch)?;
+                    append(key, lkey, options, nodes)?;
                                     
                     // Cleanup
                     key_of_pair.clear();
@@ -217,6 +481,7 @@ pub fn helper_for_object_and_array_types (line: &str, key: &mut Key) {
             // Start of string (opening quote)
             if (!array_type_encountered && !object_type_encountered && !start_of_string_encountered) && ch == '"' {
                                                                 
+                token_start = position;
                 start_of_string_encountered = true;
 
                 i = i + 1; 
@@ -224,10 +489,11 @@ pub fn helper_for_object_and_array_types (line: &str, key: &mut Key) {
                 continue;                                
             } 
             // End of string (closing quote), where collected string could be key or value
-            if start_of_string_encountered && ch == '"' {
+            if start_of_string_encountered && ch == '"' && !escaped {
                 
                 // Set
                 end_of_string_encountered = true;
+                token_end = position_after(position, ch);
 
                 // Reset
                 start_of_string_encountered = false;
@@ -240,6 +506,7 @@ pub fn helper_for_object_and_array_types (line: &str, key: &mut Key) {
             if start_of_string_encountered {
                 
                 neutral_string.push(ch);
+                escaped = ch == '\\' && !escaped;
 
                 i = i + 1;
                                 
@@ -249,6 +516,9 @@ pub fn helper_for_object_and_array_types (line: &str, key: &mut Key) {
             if (!array_type_encountered && !object_type_encountered && !start_of_string_encountered && end_of_string_encountered) && ch == ':' {
                                 
                 key_of_pair = neutral_string.clone();
+                check_length(&key_of_pair, options.max_key_length, token_start, ParseError::KeyLengthLimitExceeded)?;
+                check_escapes(&key_of_pair, token_start, options)?;
+                name_span = Some(Span { start: token_start, end: token_end });
                 
                 // Cleanup
                 neutral_string.clear();
@@ -269,8 +539,11 @@ pub fn helper_for_object_and_array_types (line: &str, key: &mut Key) {
 
                 //println! ("{} / {}", key_of_pair, value_of_pair);
                 
-                let lkey = Box::new(Key::new(key_of_pair.clone(), ValueType::StringType, value_of_pair.clone()));                   
-                key.add_key(lkey);
+                check_length(&value_of_pair, options.max_string_length, token_start, ParseError::StringLengthLimitExceeded)?;
+                check_escapes(&value_of_pair, token_start, options)?;
+                let mut lkey = Box::new(Key::new(key_of_pair.clone(), ValueType::StringType, value_of_pair.clone()));                   
+                lkey.set_spans(name_span.take(), Some(Span { start: token_start, end: token_end }));
+                append(key, lkey, options, nodes)?;
 
                 // Cleanup
                 neutral_string.clear();
@@ -288,6 +561,11 @@ pub fn helper_for_object_and_array_types (line: &str, key: &mut Key) {
             if (!array_type_encountered && !object_type_encountered && !start_of_string_encountered && !end_of_string_encountered) /*&& key_of_pair.len() > 0*/ && ch != ' ' && ch != ',' && ch != '\n' {
 
                 //value_of_pair.push(ch);
+                if neutral_string.is_empty() {
+
+                    token_start = position;
+                }
+                token_end = position_after(position, ch);
                 neutral_string.push(ch);
 
                 i = i + 1;
@@ -303,19 +581,23 @@ pub fn helper_for_object_and_array_types (line: &str, key: &mut Key) {
 
                 if value_of_pair.clone().trim() == "null" { // Null
 
-                    let lkey = Box::new(Key::new(key_of_pair.clone(), ValueType::NullType, value_of_pair.clone()));
-                    key.add_key(lkey);
+                    let mut lkey = Box::new(Key::new(key_of_pair.clone(), ValueType::NullType, value_of_pair.clone()));
+                    lkey.set_spans(name_span.take(), Some(Span { start: token_start, end: token_end }));
+                    append(key, lkey, options, nodes)?;
   
                 } else if value_of_pair.clone().trim() == "true" || value_of_pair.clone().trim() == "false" { // Boolean
 
-                    let lkey = Box::new(Key::new(key_of_pair.clone(), ValueType::BooleanType, value_of_pair.clone()));
-                    key.add_key(lkey);
+                    let mut lkey = Box::new(Key::new(key_of_pair.clone(), ValueType::BooleanType, value_of_pair.clone()));
+                    lkey.set_spans(name_span.take(), Some(Span { start: token_start, end: token_end }));
+                    append(key, lkey, options, nodes)?;
 
                 } else { // Number
                     //if value_of_pair.len() > 0 {
                         
-                        let lkey = Box::new(Key::new(key_of_pair.clone(), ValueType::NumberType, value_of_pair.clone()));
-                        key.add_key(lkey);
+                        check_number(&value_of_pair, token_start, options)?;
+                        let mut lkey = Box::new(Key::new(key_of_pair.clone(), ValueType::NumberType, value_of_pair.clone()));
+                        lkey.set_spans(name_span.take(), Some(Span { start: token_start, end: token_end }));
+                        append(key, lkey, options, nodes)?;
                     //}
                 }
                 
@@ -330,7 +612,17 @@ pub fn helper_for_object_and_array_types (line: &str, key: &mut Key) {
             }
             
             i = i + 1;                            
-        }           
+        }
+
+    if *key.get_value_type() == ValueType::ObjectType && options.duplicate_keys != DuplicateKeyPolicy::KeepAll {
+
+        let members = resolve_duplicate_keys(key.take_keys(), options.duplicate_keys)?;
+        key.set_keys(members);
+    }
+
+    scratch.put(depth, Buffers { key: key_of_pair, value: value_of_pair, neutral: neutral_string });
+
+    Ok(())
 }
 
 /// The main entry point for parsing a JSON file.
@@ -345,29 +637,29 @@ pub fn helper_for_object_and_array_types (line: &str, key: &mut Key) {
 /// # Returns
 /// * `Ok(Some(Box<JsonObject>))` if parsing is successful, containing the root `JsonObject`.
 /// * `Ok(None)` if the file is empty or does not form a valid object (though current implementation always returns a `JsonObject`).
-/// * `Err(io::Error)` if the file cannot be read.
+/// * `Err(io::Error)` if the file cannot be read, or of kind `InvalidData` wrapping a `ParseError` if it cannot be parsed.
 pub fn parser (file_name: &str) -> Result<Option<Box<JsonObject>>, io::Error> {
 
-    // State Machine
-    let mut array_type_encountered = false;
-    let mut array_type_encountered_count: usize = 0;
-    let mut object_type_encountered = false;
-    let mut object_type_encountered_count: usize = 0;
-
-    let mut start_of_string_encountered = false;
-    let mut start_of_key_name_encountered = false;
-    let mut end_of_string_encountered = false;
-    let mut end_of_key_name_encountered = false;
-    let mut start_of_value_string_encountered = false;
-    let mut end_of_value_string_encountered = false;
+    Ok(parser_with_options(file_name, &ParserOptions::default())?)
+}
 
-    let mut i: usize = 0;
+/// Same as `parser`, with settings other than the defaults and errors reported as a `ParseError`.
+///
+/// # Arguments
+/// * `file_name` - A string slice that holds the path to the JSON file to be parsed.
+/// * `options` - The parser settings
+///
+/// # Returns
+/// * `Ok(Some(Box<JsonObject>))` if parsing is successful, containing the root `JsonObject`.
+/// * `Ok(None)` if the file holds no key at all.
+/// * `Err(ParseError)` if the file cannot be read or parsed.
+pub fn parser_with_options (file_name: &str, options: &ParserOptions) -> Result<Option<Box<JsonObject>>, ParseError> {
 
-    let mut key_of_pair = String::new();
-    let mut value_of_pair = String::new();
-    let mut neutral_string = String::new();
+    // Refuse a file over the size limit before reading any of it
+    if std::fs::metadata(file_name)?.len() > options.max_input_size as u64 {
 
-    let mut json_object = JsonObject::new();
+        return Err(ParseError::InputSizeLimitExceeded(options.max_input_size));
+    }
 
     /*
         Rust does not have exceptions. It has panics, but their use for error-handling is discouraged (they are meant for unrecoverable errors).
@@ -391,9 +683,91 @@ pub fn parser (file_name: &str) -> Result<Option<Box<JsonObject>>, io::Error> {
     let file_content = match FileContent::from_file(file_name) {
 
         Ok(content) => { content },
-        Err(e) => { return Err(e) }
+        Err(e) => { return Err(ParseError::IoError(e)) }
     };
 
+    if options.json5 {
+
+        return parse_file_content(&FileContent::from_string(json5::to_json(file_content.get_content())?), options, &mut Scratch::default());
+    }
+
+    parse_file_content(&file_content, options, &mut Scratch::default())
+}
+
+/// Same as `parser_with_options`, for JSON text that is already in memory.
+///
+/// # Arguments
+/// * `text` - The JSON text
+/// * `options` - The parser settings
+///
+/// # Returns
+/// * `Ok(Some(Box<JsonObject>))` if parsing is successful, containing the root `JsonObject`.
+/// * `Ok(None)` if the text holds no key at all.
+/// * `Err(ParseError)` if the text cannot be parsed.
+pub fn parse_str (text: &str, options: &ParserOptions) -> Result<Option<Box<JsonObject>>, ParseError> {
+
+    if text.len() > options.max_input_size {
+
+        return Err(ParseError::InputSizeLimitExceeded(options.max_input_size));
+    }
+
+    let text = if options.json5 { json5::to_json(text)? } else { text.to_string() };
+
+    parse_file_content(&FileContent::from_string(text), options, &mut Scratch::default())
+}
+
+// Does the work of parser_with_options, parse_str and Parser, on the lines of the input
+pub(crate) fn parse_file_content (file_content: &FileContent, options: &ParserOptions, scratch: &mut Scratch) -> Result<Option<Box<JsonObject>>, ParseError> {
+
+    // State Machine
+    let mut array_type_encountered = false;
+    let mut array_type_encountered_count: usize = 0;
+    let mut object_type_encountered = false;
+    let mut object_type_encountered_count: usize = 0;
+
+    let mut start_of_string_encountered = false;
+    let mut start_of_key_name_encountered = false;
+    let mut end_of_string_encountered = false;
+    let mut end_of_key_name_encountered = false;
+    let mut start_of_value_string_encountered = false;
+    let mut end_of_value_string_encountered = false;
+
+    // A backslash was just read inside a string, the character after it does not end the string
+    let mut escaped = false;
+    // The container being collected is in one of its strings, where brackets, braces and commas are text
+    let mut in_string_of_container = false;
+    let mut string_start = Position::default();
+
+    let mut i: usize = 0;
+
+    let Buffers { key: mut key_of_pair, value: mut value_of_pair, neutral: mut neutral_string } = scratch.take(1);
+
+    let mut json_object = JsonObject::new();
+    let mut nodes: usize = 0;
+
+    // Where the string, scalar or container being read starts and ends, and the span of the name it belongs to
+    let mut token_start = Position::default();
+    let mut token_end = Position::default();
+    let mut name_span: Option<Span> = None;
+    // Where each character of value_of_pair is in the file, the lines of a container are joined without their line endings
+    let mut value_positions = std::mem::take(&mut scratch.positions);
+    value_positions.clear();
+
+    // The root braces are the first and the last character that is not white space, as (line, byte index)
+    let mut root_start: Option<(usize, usize)> = None;
+    let mut root_end: Option<(usize, usize)> = None;
+
+    for index in 0..file_content.count_lines() {
+
+        let line = file_content.get_line_by_index(index).map(String::as_str).unwrap_or_default();
+
+        if let Some((byte_index, _)) = line.char_indices().find(|(_, c)| !c.is_whitespace()) {
+
+            root_start = root_start.or(Some((index, byte_index)));
+            root_end = line.char_indices().rev().find(|(_, c)| !c.is_whitespace()).map(|(byte_index, _)| (index, byte_index));
+        }
+    }
+
     // Main parsing loop - processes each line of the file
     loop {
         
@@ -405,13 +779,18 @@ pub fn parser (file_name: &str) -> Result<Option<Box<JsonObject>>, io::Error> {
         }
 
         // Create a peekable iterator for the current line's characters
-        let mut line_of_peekable_chars = line.unwrap().chars().peekable();
+        let mut line_of_peekable_chars = line.unwrap().char_indices().peekable();
+        let line_offset = file_content.get_line_offset(i).unwrap_or(0);
+        let mut column: usize = 0;
 
         // Process each character in the line
-        while let Some(ch) = line_of_peekable_chars.next() {
+        while let Some((byte_index, ch)) = line_of_peekable_chars.next() {
+
+            column += 1;
+            let position = Position { line: i + 1, column, offset: line_offset + byte_index };
           
             // Handle JSON root object markers (first '{' and last '}')
-            if ( (ch == '{' && i == 0) || (ch == '}' && i == (file_content.count_lines() - 1)) ) || ( (ch == '[' && i == 0) || (ch == ']' && i == (file_content.count_lines() - 1)) ) || ch == '\n' { // Ignore JSON root object
+            if ( (ch == '{' || ch == '[') && Some((i, byte_index)) == root_start ) || ( (ch == '}' || ch == ']') && Some((i, byte_index)) == root_end ) || ch == '\n' { // Ignore JSON root object
 
                 /*println! ("--> k = {} / n = {} / v = {} and {}", key_of_pair, neutral_string, value_of_pair, end_of_string_encountered);*/
 
@@ -422,8 +801,11 @@ pub fn parser (file_name: &str) -> Result<Option<Box<JsonObject>>, io::Error> {
 
                     value_of_pair = neutral_string.clone();
                                         
-                    let key = Box::new(Key::new(key_of_pair.clone(), ValueType::StringType, value_of_pair.clone()));                   
-                    json_object.add_key(key); 
+                    check_length(&value_of_pair, options.max_string_length, token_start, ParseError::StringLengthLimitExceeded)?;
+                    check_escapes(&value_of_pair, token_start, options)?;
+                    let mut key = Box::new(Key::new(key_of_pair.clone(), ValueType::StringType, value_of_pair.clone()));                   
+                    key.set_spans(name_span.take(), Some(Span { start: token_start, end: token_end }));
+                    append_member(&mut json_object, key, options, &mut nodes)?;
 
                     // Cleanup
                     neutral_string.clear();
@@ -440,18 +822,22 @@ pub fn parser (file_name: &str) -> Result<Option<Box<JsonObject>>, io::Error> {
 
                     if value_of_pair.clone().trim() == "null" { // Null
 
-                        let key = Box::new(Key::new(key_of_pair.clone(), ValueType::NullType, value_of_pair.clone()));
-                        json_object.add_key(key);
+                        let mut key = Box::new(Key::new(key_of_pair.clone(), ValueType::NullType, value_of_pair.clone()));
+                        key.set_spans(name_span.take(), Some(Span { start: token_start, end: token_end }));
+                        append_member(&mut json_object, key, options, &mut nodes)?;
   
                     } else if value_of_pair.clone().trim() == "true" || value_of_pair.clone().trim() == "false" { // Boolean
 
-                        let key = Box::new(Key::new(key_of_pair.clone(), ValueType::BooleanType, value_of_pair.clone()));
-                        json_object.add_key(key);
+                        let mut key = Box::new(Key::new(key_of_pair.clone(), ValueType::BooleanType, value_of_pair.clone()));
+                        key.set_spans(name_span.take(), Some(Span { start: token_start, end: token_end }));
+                        append_member(&mut json_object, key, options, &mut nodes)?;
 
                     } else { // Number
                     
-                        let key = Box::new(Key::new(key_of_pair.clone(), ValueType::NumberType, value_of_pair.clone()));
-                        json_object.add_key(key);
+                        check_number(&value_of_pair, token_start, options)?;
+                        let mut key = Box::new(Key::new(key_of_pair.clone(), ValueType::NumberType, value_of_pair.clone()));
+                        key.set_spans(name_span.take(), Some(Span { start: token_start, end: token_end }));
+                        append_member(&mut json_object, key, options, &mut nodes)?;
                     }
 
                     // Cleanup
@@ -462,9 +848,39 @@ pub fn parser (file_name: &str) -> Result<Option<Box<JsonObject>>, io::Error> {
                 
                 continue;  // Skip processing further below              
             }
-            if !object_type_encountered && ch == '[' {
+            // Inside the strings of the container being collected, brackets and braces are text and \" does not end the string
+            if (array_type_encountered || object_type_encountered) && (in_string_of_container || ch == '"') {
+
+                value_of_pair.push(ch);
+                value_positions.push(position);
+
+                if !in_string_of_container {
+
+                    in_string_of_container = true;
+                    string_start = position;
+                } else if escaped {
+
+                    escaped = false;
+                } else if ch == '\\' {
+
+                    escaped = true;
+                } else if ch == '"' {
+
+                    in_string_of_container = false;
+                }
+
+                continue;
+            }
+            if !object_type_encountered && !start_of_string_encountered && ch == '[' {
+
+                if array_type_encountered_count == 0 {
+
+                    token_start = position;
+                    value_positions.clear();
+                }
 
                 value_of_pair.push(ch);
+                value_positions.push(position);
 
                 array_type_encountered = true;
                 array_type_encountered_count += 1;
@@ -474,6 +890,7 @@ pub fn parser (file_name: &str) -> Result<Option<Box<JsonObject>>, io::Error> {
             if array_type_encountered && ch == ']' {
 
                 value_of_pair.push(ch);
+                value_positions.push(position);
 
                 array_type_encountered_count -= 1;
 
@@ -481,12 +898,15 @@ pub fn parser (file_name: &str) -> Result<Option<Box<JsonObject>>, io::Error> {
 
                     array_type_encountered = false;
 
+                    token_end = position_after(position, ch);
+
                     // Here we have a complete key/value pair of Object type, add it to the json tree
                     //println! ("{} / {}", key_of_pair, value_of_pair);
 
-                    let mut key = Box::new(Key::new(key_of_pair.clone(), ValueType::ArrayType, value_of_pair.clone()));
-                    helper_for_object_and_array_types(&value_of_pair.clone(), &mut key);
-                    json_object.add_key(key);
+                    let mut key = Box::new(Key::new(key_of_pair.clone(), ValueType::ArrayType, raw_text(&value_of_pair, options)));
+                    key.set_spans(name_span.take(), Some(Span { start: token_start, end: token_end }));
+                    parse_container(&value_of_pair, &value_positions, &mut key, 2, options, &mut nodes, scratch)?;
+                    append_member(&mut json_object, key, options, &mut nodes)?;
 
                     // Cleanup
                     key_of_pair.clear();
@@ -498,12 +918,20 @@ pub fn parser (file_name: &str) -> Result<Option<Box<JsonObject>>, io::Error> {
             if array_type_encountered {
 
                 value_of_pair.push(ch);
+                value_positions.push(position);
 
                 continue;
             }
-            if !array_type_encountered && ch == '{' {
+            if !array_type_encountered && !start_of_string_encountered && ch == '{' {
+
+                if object_type_encountered_count == 0 {
+
+                    token_start = position;
+                    value_positions.clear();
+                }
 
                 value_of_pair.push(ch);
+                value_positions.push(position);
 
                 object_type_encountered = true;
                 object_type_encountered_count += 1;
@@ -513,6 +941,7 @@ pub fn parser (file_name: &str) -> Result<Option<Box<JsonObject>>, io::Error> {
             if object_type_encountered && ch == '}' {
 
                 value_of_pair.push(ch);
+                value_positions.push(position);
 
                 object_type_encountered_count -= 1;
 
@@ -520,12 +949,15 @@ pub fn parser (file_name: &str) -> Result<Option<Box<JsonObject>>, io::Error> {
 
                     object_type_encountered = false;
 
+                    token_end = position_after(position, ch);
+
                     // Here we have a complete key/value pair of Object type, add it to the json tree
                     //println! ("{} / {}", key_of_pair, value_of_pair);
 
-                    let mut key = Box::new(Key::new(key_of_pair.clone(), ValueType::ObjectType, value_of_pair.clone()));
-                    helper_for_object_and_array_types(&value_of_pair.clone(), &mut key);
-                    json_object.add_key(key);
+                    let mut key = Box::new(Key::new(key_of_pair.clone(), ValueType::ObjectType, raw_text(&value_of_pair, options)));
+                    key.set_spans(name_span.take(), Some(Span { start: token_start, end: token_end }));
+                    parse_container(&value_of_pair, &value_positions, &mut key, 2, options, &mut nodes, scratch)?;
+                    append_member(&mut json_object, key, options, &mut nodes)?;
                                     
                     // Cleanup
                     key_of_pair.clear();
@@ -537,6 +969,7 @@ pub fn parser (file_name: &str) -> Result<Option<Box<JsonObject>>, io::Error> {
             if object_type_encountered {
 
                 value_of_pair.push(ch);
+                value_positions.push(position);
 
                 continue;
             }
@@ -549,15 +982,18 @@ pub fn parser (file_name: &str) -> Result<Option<Box<JsonObject>>, io::Error> {
             // Start of string (opening quote)
             if (!array_type_encountered && !object_type_encountered && !start_of_string_encountered) && ch == '"' {
                                                                 
+                token_start = position;
+                string_start = position;
                 start_of_string_encountered = true; 
 
                 continue;                                
             }
             // End of string (closing quote), where collected string could be key or value
-            if start_of_string_encountered && ch == '"' {
+            if start_of_string_encountered && ch == '"' && !escaped {
                 
                 // Set
                 end_of_string_encountered = true;
+                token_end = position_after(position, ch);
 
                 // Reset
                 start_of_string_encountered = false;
@@ -568,6 +1004,7 @@ pub fn parser (file_name: &str) -> Result<Option<Box<JsonObject>>, io::Error> {
             if start_of_string_encountered {
                 
                 neutral_string.push(ch);
+                escaped = ch == '\\' && !escaped;
                                 
                 continue;
             }            
@@ -575,6 +1012,9 @@ pub fn parser (file_name: &str) -> Result<Option<Box<JsonObject>>, io::Error> {
             if (!array_type_encountered && !object_type_encountered && !start_of_string_encountered && end_of_string_encountered) && ch == ':' {
                                 
                 key_of_pair = neutral_string.clone();
+                check_length(&key_of_pair, options.max_key_length, token_start, ParseError::KeyLengthLimitExceeded)?;
+                check_escapes(&key_of_pair, token_start, options)?;
+                name_span = Some(Span { start: token_start, end: token_end });
                 
                 // Cleanup
                 neutral_string.clear();
@@ -593,8 +1033,11 @@ pub fn parser (file_name: &str) -> Result<Option<Box<JsonObject>>, io::Error> {
 
                 //println! ("{} / {}", key_of_pair, value_of_pair);
                 
-                let key = Box::new(Key::new(key_of_pair.clone(), ValueType::StringType, value_of_pair.clone()));                   
-                json_object.add_key(key);
+                check_length(&value_of_pair, options.max_string_length, token_start, ParseError::StringLengthLimitExceeded)?;
+                check_escapes(&value_of_pair, token_start, options)?;
+                let mut key = Box::new(Key::new(key_of_pair.clone(), ValueType::StringType, value_of_pair.clone()));                   
+                key.set_spans(name_span.take(), Some(Span { start: token_start, end: token_end }));
+                append_member(&mut json_object, key, options, &mut nodes)?;
 
                 // Cleanup
                 neutral_string.clear();
@@ -610,6 +1053,11 @@ pub fn parser (file_name: &str) -> Result<Option<Box<JsonObject>>, io::Error> {
             if (!array_type_encountered && !object_type_encountered && !start_of_string_encountered && !end_of_string_encountered) /*&& key_of_pair.len() > 0*/ && ch != ' ' && ch != ',' && ch != '\n' {
 
                 //value_of_pair.push(ch);
+                if neutral_string.is_empty() {
+
+                    token_start = position;
+                }
+                token_end = position_after(position, ch);
                 neutral_string.push(ch);
 
                 continue;
@@ -623,19 +1071,23 @@ pub fn parser (file_name: &str) -> Result<Option<Box<JsonObject>>, io::Error> {
 
                 if value_of_pair.clone().trim() == "null" { // Null
 
-                    let key = Box::new(Key::new(key_of_pair.clone(), ValueType::NullType, value_of_pair.clone()));
-                    json_object.add_key(key);
+                    let mut key = Box::new(Key::new(key_of_pair.clone(), ValueType::NullType, value_of_pair.clone()));
+                    key.set_spans(name_span.take(), Some(Span { start: token_start, end: token_end }));
+                    append_member(&mut json_object, key, options, &mut nodes)?;
   
                 } else if value_of_pair.clone().trim() == "true" || value_of_pair.clone().trim() == "false" { // Boolean
 
-                    let key = Box::new(Key::new(key_of_pair.clone(), ValueType::BooleanType, value_of_pair.clone()));
-                    json_object.add_key(key);
+                    let mut key = Box::new(Key::new(key_of_pair.clone(), ValueType::BooleanType, value_of_pair.clone()));
+                    key.set_spans(name_span.take(), Some(Span { start: token_start, end: token_end }));
+                    append_member(&mut json_object, key, options, &mut nodes)?;
 
                 } else { // Number
-                    if value_of_pair.len() > 0 {
+                    if !value_of_pair.is_empty() {
 
-                        let key = Box::new(Key::new(key_of_pair.clone(), ValueType::NumberType, value_of_pair.clone()));
-                        json_object.add_key(key);
+                        check_number(&value_of_pair, token_start, options)?;
+                        let mut key = Box::new(Key::new(key_of_pair.clone(), ValueType::NumberType, value_of_pair.clone()));
+                        key.set_spans(name_span.take(), Some(Span { start: token_start, end: token_end }));
+                        append_member(&mut json_object, key, options, &mut nodes)?;
                     }
                 }
                 
@@ -651,6 +1103,21 @@ pub fn parser (file_name: &str) -> Result<Option<Box<JsonObject>>, io::Error> {
         i = i + 1;
     }
 
+    // A string that never ends, the rest of the input went into it
+    if start_of_string_encountered || in_string_of_container {
+
+        return Err(ParseError::UnterminatedString(string_start));
+    }
+
+    if options.duplicate_keys != DuplicateKeyPolicy::KeepAll {
+
+        let members = resolve_duplicate_keys(json_object.take_keys(), options.duplicate_keys)?;
+        json_object.set_keys(members);
+    }
+
+    scratch.put(1, Buffers { key: key_of_pair, value: value_of_pair, neutral: neutral_string });
+    scratch.positions = value_positions;
+
     if json_object.get_n() > 0 {
 
         Ok(Some(Box::new(json_object)))
@@ -659,6 +1126,342 @@ pub fn parser (file_name: &str) -> Result<Option<Box<JsonObject>>, io::Error> {
         Ok(None)
     }    
 }
-        
 
+// Checks a literal that is not null, true or false against the number grammar of RFC 8259,
+// an optional minus, no leading zeros, at least one digit after a '.' and an optional exponent.
+// JSON5 adds Infinity, -Infinity and NaN, its other numbers have been rewritten by json5::to_json()
+fn check_number(literal: &str, start: Position, options: &ParserOptions) -> Result<(), ParseError> {
+
+    static NUMBER: OnceLock<Regex> = OnceLock::new();
+
+    let number = NUMBER.get_or_init(|| Regex::new(&format!("^{}$", CAPTURE_NUMBER_PATTERN)).unwrap());
+
+    if options.numbers == NumberMode::Keep || number.is_match(literal.trim()) || (options.json5 && matches!(literal.trim(), "Infinity" | "-Infinity" | "NaN")) {
+
+        Ok(())
+    } else {
+
+        Err(ParseError::InvalidNumber(literal.trim().to_string(), start))
+    }
+}
+
+// The value of the key of an object or an array, its text or nothing as options.keep_raw says
+fn raw_text(text: &str, options: &ParserOptions) -> String {
+
+    if options.keep_raw { text.to_string() } else { String::new() }
+}
+
+// Fails with the given error when a string, a value or a member name, is longer than the limit
+fn check_length(s: &str, limit: usize, start: Position, error: fn(usize, Position) -> ParseError) -> Result<(), ParseError> {
+
+    if s.len() > limit {
+
+        return Err(error(limit, start));
+    }
+
+    Ok(())
+}
+
+// Fails when a string, a value or a member name, has an escape sequence that does not decode, if options.escapes asks for it
+fn check_escapes(s: &str, start: Position, options: &ParserOptions) -> Result<(), ParseError> {
+
+    if options.escapes == EscapeMode::Validate {
+
+        unescape(s).map_err(|e| ParseError::InvalidEscape(e, start))?;
+    }
+
+    Ok(())
+}
+
+// Adds a key to an object or an array being parsed, unless it is one member, element or key of the whole tree
+// too many, so that a hostile container fails at the first key over the limit instead of once it is all in memory
+fn append(container: &mut Key, key: Box<Key>, options: &ParserOptions, nodes: &mut usize) -> Result<(), ParseError> {
+
+    let start = container.value_span().map(|span| span.start).unwrap_or_default();
+
+    check_one_more(container.get_value_type(), container.get_n(), start, options, nodes)?;
+
+    container.add_key(key);
+
+    Ok(())
+}
+
+// Same as append(), for the members of the root object
+fn append_member(json_object: &mut JsonObject, key: Box<Key>, options: &ParserOptions, nodes: &mut usize) -> Result<(), ParseError> {
+
+    check_one_more(&ValueType::ObjectType, json_object.get_n(), Position { line: 1, column: 1, offset: 0 }, options, nodes)?;
+
+    json_object.add_key(key);
+
+    Ok(())
+}
+
+// Checks that a container holding n keys can take one more, and counts it among the keys of the whole tree
+fn check_one_more(value_type: &ValueType, n: usize, start: Position, options: &ParserOptions, nodes: &mut usize) -> Result<(), ParseError> {
+
+    match value_type {
+
+        ValueType::ObjectType if n >= options.max_members => return Err(ParseError::MemberLimitExceeded(options.max_members, start)),
+        ValueType::ArrayType if n >= options.max_elements => return Err(ParseError::ElementLimitExceeded(options.max_elements, start)),
+        _ => {},
+    }
+
+    *nodes += 1;
+
+    if *nodes > options.max_nodes {
+
+        return Err(ParseError::NodeLimitExceeded(options.max_nodes));
+    }
+
+    Ok(())
+}
+
+// Applies the duplicate key policy to the members of one object
+fn resolve_duplicate_keys(members: Vec<Key>, policy: DuplicateKeyPolicy) -> Result<Vec<Key>, ParseError> {
+
+    let mut ret: Vec<Key> = Vec::with_capacity(members.len());
+    let mut seen: HashMap<String, usize> = HashMap::new();
+
+    for member in members {
+
+        let name = unescape_lossy(member.get_name());
+
+        match seen.get(&name) {
+
+            None => {
+
+                seen.insert(name, ret.len());
+                ret.push(member);
+            },
+            Some(&index) => match policy {
+
+                DuplicateKeyPolicy::Error => {
+
+                    let start = |key: &Key| key.name_span().map(|span| span.start).unwrap_or_default();
+
+                    return Err(ParseError::DuplicateKey(name, start(&ret[index]), start(&member)));
+                },
+                DuplicateKeyPolicy::FirstWins => {},
+                DuplicateKeyPolicy::LastWins => ret[index] = member,
+                DuplicateKeyPolicy::KeepAll => ret.push(member),
+            },
+        }
+    }
+
+    Ok(ret)
+}
+
+// Position just past the given character
+fn position_after(position: Position, ch: char) -> Position {
+
+    Position { line: position.line, column: position.column + 1, offset: position.offset + ch.len_utf8() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json_object::NumberKind;
+
+    fn parse(text: &str, options: &ParserOptions) -> Result<Option<Box<JsonObject>>, ParseError> {
+        parse_str(text, options)
+    }
+
+    #[test]
+    fn depth_limit() {
+        let options = ParserOptions { max_depth: 3, ..ParserOptions::default() };
+
+        assert!(parse(r#"{"a": {"b": [1]}}"#, &options).is_ok());
+        assert!(matches!(parse(r#"{"a": {"b": [[1]]}}"#, &options), Err(ParseError::DepthLimitExceeded(3))));
+        assert!(matches!(parse(r#"{"a": [[[{}]]]}"#, &options), Err(ParseError::DepthLimitExceeded(3))));
+    }
+
+    #[test]
+    fn io_error() {
+        let missing = "no such directory/no such file.json";
+
+        assert!(matches!(parser_with_options(missing, &ParserOptions::default()), Err(ParseError::IoError(_))));
+        assert!(parser(missing).is_err());
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let text = "{\n  \"role\": \"user\",\n  \"id\": 1,\n  \"r\\u006fle\": \"admin\"\n}";
+
+        let with = |duplicate_keys| parse(text, &ParserOptions { duplicate_keys, ..ParserOptions::default() });
+
+        match with(DuplicateKeyPolicy::Error) {
+            Err(ParseError::DuplicateKey(name, first, second)) => {
+                assert_eq!(name, "role");
+                assert_eq!((first.line, second.line), (2, 4));
+            },
+            other => panic!("{:?}", other.map(|jobj| jobj.map(|jobj| jobj.to_json_string()))),
+        }
+
+        assert_eq!(with(DuplicateKeyPolicy::FirstWins).unwrap().unwrap().to_json_string(), r#"{"role":"user","id":1}"#);
+        assert_eq!(with(DuplicateKeyPolicy::LastWins).unwrap().unwrap().to_json_string(), r#"{"r\u006fle":"admin","id":1}"#);
+        assert_eq!(with(DuplicateKeyPolicy::KeepAll).unwrap().unwrap().to_json_string(), r#"{"role":"user","id":1,"r\u006fle":"admin"}"#);
+    }
+
+    #[test]
+    fn invalid_numbers() {
+        for literal in ["01", "1.", ".5", "+1", "1e", "0x1F", "Infinity", "tru"] {
+            let text = format!("{{\n  \"a\": 1,\n  \"b\": {}\n}}", literal);
+
+            match parse(&text, &ParserOptions::default()) {
+                Err(ParseError::InvalidNumber(found, at)) => {
+                    assert_eq!(found, literal);
+                    assert_eq!((at.line, at.column), (3, 8), "{}", literal);
+                },
+                other => panic!("{}: {:?}", literal, other.map(|jobj| jobj.map(|jobj| jobj.to_json_string()))),
+            }
+
+            let options = ParserOptions { numbers: NumberMode::Keep, ..ParserOptions::default() };
+            assert!(parse(&text, &options).is_ok(), "{}", literal);
+        }
+    }
 
+    #[test]
+    fn number_kinds() {
+        let jobj = parse(r#"{"a": [-42, 0, 3.14, 1e10, -2.5E-3]}"#, &ParserOptions::default()).unwrap().unwrap();
+        let kinds: Vec<Option<NumberKind>> = jobj.iter().next().unwrap().iter().map(Key::get_number_kind).collect();
+
+        assert_eq!(kinds, [Some(NumberKind::Integer), Some(NumberKind::Integer), Some(NumberKind::Float), Some(NumberKind::Float), Some(NumberKind::Float)]);
+    }
+
+    #[test]
+    fn limits() {
+        let text = "{\n  \"name\": \"abcdef\",\n  \"list\": [1, 2, 3],\n  \"sub\": {\"x\": 1, \"y\": 2}\n}";
+
+        let with = |options: ParserOptions| parse(text, &options);
+        let defaults = ParserOptions::default;
+
+        assert!(with(defaults()).is_ok());
+
+        assert!(matches!(with(ParserOptions { max_input_size: 10, ..defaults() }), Err(ParseError::InputSizeLimitExceeded(10))));
+        assert!(matches!(with(ParserOptions { max_string_length: 5, ..defaults() }), Err(ParseError::StringLengthLimitExceeded(5, at)) if at.line == 2));
+        assert!(matches!(with(ParserOptions { max_key_length: 3, ..defaults() }), Err(ParseError::KeyLengthLimitExceeded(3, at)) if at.line == 2));
+        assert!(matches!(with(ParserOptions { max_members: 2, ..defaults() }), Err(ParseError::MemberLimitExceeded(2, at)) if at.line == 1));
+        assert!(matches!(with(ParserOptions { max_elements: 2, ..defaults() }), Err(ParseError::ElementLimitExceeded(2, at)) if at.line == 3));
+        assert!(matches!(with(ParserOptions { max_nodes: 7, ..defaults() }), Err(ParseError::NodeLimitExceeded(7))));
+
+        // Exactly at the limits
+        let options = ParserOptions { max_string_length: 6, max_key_length: 4, max_members: 3, max_elements: 3, max_nodes: 8, max_input_size: text.len(), ..defaults() };
+        assert!(with(options).is_ok());
+    }
+
+    #[test]
+    fn limits_fail_at_the_first_key_over() {
+        // The parser stops at the third element, it never gets to read the bad literal after it
+        let text = "{\n  \"list\": [1, 2, 3, oops]\n}";
+
+        assert!(matches!(parse(text, &ParserOptions::default()), Err(ParseError::InvalidNumber(..))));
+        assert!(matches!(parse(text, &ParserOptions { max_elements: 2, ..ParserOptions::default() }), Err(ParseError::ElementLimitExceeded(2, at)) if (at.line, at.column) == (2, 11)));
+        assert!(matches!(parse(text, &ParserOptions { max_nodes: 2, ..ParserOptions::default() }), Err(ParseError::NodeLimitExceeded(2))));
+    }
+
+    #[test]
+    fn non_ascii_text_in_containers() {
+        // The last member used to be dropped when a multi-byte character came before it
+        let text = r#"{"b": {"f": 1, "e": "é"}, "c": ["ü", {"d": "ß"}], "g": [1, "ñ"]}"#;
+
+        assert_eq!(parse(text, &ParserOptions::default()).unwrap().unwrap().to_json_string(), r#"{"b":{"f":1,"e":"é"},"c":["ü",{"d":"ß"}],"g":[1,"ñ"]}"#);
+    }
+
+    // The text a span covers, along with the line and column it starts and ends at
+    fn covered(text: &str, span: Option<Span>) -> (&str, (usize, usize), (usize, usize)) {
+        let span = span.unwrap();
+        (&text[span.start.offset..span.end.offset], (span.start.line, span.start.column), (span.end.line, span.end.column))
+    }
+
+    #[test]
+    fn spans_of_multi_line_containers() {
+        let text = "{\n  \"a\": 1,\n  \"b\": {\n    \"c\": [true,\n      \"x\"],\n    \"d\": null\n  }\n}";
+        let jobj = parse(text, &ParserOptions::default()).unwrap().unwrap();
+
+        let a = jobj.iter().next().unwrap();
+        assert_eq!(covered(text, a.name_span()), ("\"a\"", (2, 3), (2, 6)));
+        assert_eq!(covered(text, a.value_span()), ("1", (2, 8), (2, 9)));
+        assert_eq!(covered(text, a.span()), ("\"a\": 1", (2, 3), (2, 9)));
+
+        let b = jobj.iter().nth(1).unwrap();
+        assert_eq!(covered(text, b.value_span()), ("{\n    \"c\": [true,\n      \"x\"],\n    \"d\": null\n  }", (3, 8), (7, 4)));
+
+        let c = b.iter().next().unwrap();
+        assert_eq!(covered(text, c.name_span()), ("\"c\"", (4, 5), (4, 8)));
+        assert_eq!(covered(text, c.value_span()), ("[true,\n      \"x\"]", (4, 10), (5, 11)));
+
+        // Elements have no name, their span is the one of their value
+        let x = c.iter().nth(1).unwrap();
+        assert_eq!(x.name_span(), None);
+        assert_eq!(covered(text, x.span()), ("\"x\"", (5, 7), (5, 10)));
+
+        assert_eq!(covered(text, b.iter().nth(1).unwrap().span()), ("\"d\": null", (6, 5), (6, 14)));
+    }
+
+    #[test]
+    fn spans_with_crlf_line_endings() {
+        let text = "{\r\n  \"a\": 1,\r\n  \"b\": {\r\n    \"c\": \"x\"\r\n  },\r\n  \"e\": [1,\r\n 2]\r\n}\r\n";
+        let jobj = parse(text, &ParserOptions::default()).unwrap().unwrap();
+
+        // \r is part of the line it ends, not a column of the next one
+        let a = jobj.iter().next().unwrap();
+        assert_eq!(covered(text, a.name_span()), ("\"a\"", (2, 3), (2, 6)));
+        assert_eq!(a.name_span().unwrap().start.offset, 5);
+
+        let b = jobj.iter().nth(1).unwrap();
+        assert_eq!(covered(text, b.value_span()), ("{\r\n    \"c\": \"x\"\r\n  }", (3, 8), (5, 4)));
+        assert_eq!(covered(text, b.iter().next().unwrap().span()), ("\"c\": \"x\"", (4, 5), (4, 13)));
+
+        let e = jobj.iter().nth(2).unwrap();
+        assert_eq!(covered(text, e.value_span()), ("[1,\r\n 2]", (6, 8), (7, 4)));
+        assert_eq!(covered(text, e.iter().nth(1).unwrap().value_span()), ("2", (7, 2), (7, 3)));
+    }
+
+    #[test]
+    fn spans_after_non_ascii_text() {
+        // Columns count characters, offsets count bytes
+        let text = "{\n  \"é\": \"日本\", \"k\": 1,\n  \"ü\": {\"ñ\": \"ß\", \"z\": [\"é\", 2]}\n}";
+        let jobj = parse(text, &ParserOptions::default()).unwrap().unwrap();
+
+        let k = jobj.iter().nth(1).unwrap();
+        assert_eq!(covered(text, k.name_span()), ("\"k\"", (2, 14), (2, 17)));
+        assert_eq!(k.name_span().unwrap().start.offset, 20);
+        assert_eq!(covered(text, k.value_span()), ("1", (2, 19), (2, 20)));
+
+        let u = jobj.iter().nth(2).unwrap();
+        let z = u.iter().nth(1).unwrap();
+        assert_eq!(covered(text, z.name_span()), ("\"z\"", (3, 19), (3, 22)));
+        assert_eq!(covered(text, z.value_span()), ("[\"é\", 2]", (3, 24), (3, 32)));
+        assert_eq!(covered(text, z.iter().nth(1).unwrap().value_span()), ("2", (3, 30), (3, 31)));
+
+        // The same on a single line
+        let text = "{\"é\": \"日本\", \"k\": 1, \"ü\": {\"ñ\": \"ß\", \"z\": [\"é\", 2]}}";
+        let jobj = parse(text, &ParserOptions::default()).unwrap().unwrap();
+
+        let z = jobj.iter().nth(2).unwrap().iter().nth(1).unwrap();
+        assert_eq!(covered(text, z.span()), ("\"z\": [\"é\", 2]", (1, 37), (1, 50)));
+        assert_eq!(covered(text, z.iter().next().unwrap().value_span()), ("\"é\"", (1, 43), (1, 46)));
+    }
+
+    #[test]
+    fn quotes_and_brackets_inside_strings() {
+        let options = ParserOptions::default();
+
+        // These used to come back as Ok(None), or with members missing
+        let text = r#"{"a": "x\"y", "b": 1}"#;
+        assert_eq!(parse(text, &options).unwrap().unwrap().to_json_string(), r#"{"a":"x\"y","b":1}"#);
+
+        let text = "{\n  \"a\\\"b\": \"x\\\\\",\n  \"c\": \"{[\",\n  \"d\": [\"]\\\"[\", {\"e\": \"}{,\"}],\n  \"f\": {\"g\": \"x\\\"y\"}\n}";
+        let jobj = parse(text, &options).unwrap().unwrap();
+        assert_eq!(jobj.to_json_string(), r#"{"a\"b":"x\\","c":"{[","d":["]\"[",{"e":"}{,"}],"f":{"g":"x\"y"}}"#);
+        assert_eq!(covered(text, jobj.iter().nth(3).unwrap().value_span()), ("{\"g\": \"x\\\"y\"}", (5, 8), (5, 21)));
+
+        // A string whose closing quote is escaped runs to the end of the input
+        match parse(r#"{"a": ["x\"]}"#, &options) {
+            Err(ParseError::UnterminatedString(at)) => assert_eq!((at.line, at.column), (1, 8)),
+            other => panic!("{:?}", other.map(|jobj| jobj.map(|jobj| jobj.to_json_string()))),
+        }
+
+        assert!(matches!(parse("{\n  \"a\": \"x\\\"\n}", &options), Err(ParseError::UnterminatedString(at)) if (at.line, at.column) == (2, 8)));
+    }
+}
@@ -0,0 +1,465 @@
+/*
+    src/json5.rs
+    This file is part of the JSON-rust crate.
+    Written by, Q@khaa.pk
+ */
+
+/*
+    JSON5 input, https://spec.json5.org, for hand-written files such as configurations.
+    The parser reads JSON only, so with ParserOptions::json5 set the text is first rewritten as JSON here
+    and then parsed the usual way, which gives the Key tree the same document written as JSON gives.
+
+    // and /* */ comments             become spaces
+    unquoted member names             are quoted, key: 1 becomes "key": 1
+    'single quoted' strings           become "double quoted", \' \x41 \v \0 become ' A \u000b \u0000
+    " and \" inside strings           become \u0022
+    a trailing comma                  becomes a space
+    0x1F, +1, .5, 5.                  become 31, 1, 0.5, 5.0
+    Infinity, -Infinity, NaN          stay as they are, Float NumberType keys that f64 reads,
+                                      to_json_string() writes them as null, see NumberKind in json_object.rs
+
+    Every line stays where it is, line numbers in spans and errors are the ones of the JSON5 text.
+    Columns and offsets after a rewritten token, on the same line, can be off by the characters it gained or lost.
+    A string continued on the next line with a backslash becomes one line, followed by an empty one.
+ */
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::json::ParseError;
+use crate::json_object::Position;
+
+/// Rewrites JSON5 text as JSON.
+///
+/// # Arguments
+/// * `text` - The JSON5 text
+///
+/// # Returns
+/// * `Ok(String)` holding the same document as JSON, on the same lines
+/// * `Err(ParseError::InvalidJson5)` for an unterminated string or comment, a member name that is not an identifier,
+///   or braces and brackets that do not match. Bad numbers are left for the parser to report.
+pub fn to_json(text: &str) -> Result<String, ParseError> {
+
+    let mut out = String::with_capacity(text.len());
+
+    write_json(text, &mut out)?;
+
+    Ok(out)
+}
+
+/// Same as `to_json`, writing the JSON at the end of `out` so a buffer can be used again and again.
+pub fn write_json(text: &str, out: &mut String) -> Result<(), ParseError> {
+
+    let mut reader = Reader { chars: text.chars().peekable(), position: Position { line: 1, column: 1, offset: 0 } };
+
+    let mut containers: Vec<char> = Vec::new();
+    let mut expect_name = false;              // After the '{' or a ',' of an object
+    let mut pending_comma: Option<usize> = None; // Where in out the last ',' is, until something else than white space follows it
+    let mut pending_lines: usize = 0;         // Line endings taken out of strings, given back at the next line ending
+
+    while let Some(&ch) = reader.chars.peek() {
+
+        let start = reader.position;
+
+        match ch {
+
+            '\n' => {
+
+                reader.next();
+
+                for _ in 0..pending_lines {
+
+                    out.push('\n');
+                }
+                pending_lines = 0;
+
+                out.push('\n');
+            },
+            '\r' => {
+
+                reader.next();
+                out.push('\r');
+            },
+            '/' => {
+
+                reader.next();
+
+                match reader.next() {
+
+                    Some('/') => {
+
+                        out.push_str("  ");
+
+                        while let Some(&c) = reader.chars.peek() {
+
+                            if c == '\n' || c == '\r' {
+
+                                break;
+                            }
+
+                            reader.next();
+                            out.push(' ');
+                        }
+                    },
+                    Some('*') => {
+
+                        out.push_str("  ");
+
+                        loop {
+
+                            match reader.next() {
+
+                                Some('*') if reader.chars.peek() == Some(&'/') => {
+
+                                    reader.next();
+                                    out.push_str("  ");
+
+                                    break;
+                                },
+                                Some(c) if c == '\n' || c == '\r' => out.push(c),
+                                Some(_) => out.push(' '),
+                                None => return Err(ParseError::InvalidJson5("unterminated comment".to_string(), start)),
+                            }
+                        }
+                    },
+                    _ => return Err(ParseError::InvalidJson5("unexpected '/'".to_string(), start)),
+                }
+            },
+            c if c.is_whitespace() || c == '\u{FEFF}' => {
+
+                reader.next();
+                out.push(' ');
+            },
+            '"' | '\'' => {
+
+                pending_comma = None;
+                expect_name = false;
+
+                pending_lines += read_string(&mut reader, out)?;
+            },
+            '{' | '[' => {
+
+                reader.next();
+
+                pending_comma = None;
+                expect_name = ch == '{';
+
+                containers.push(ch);
+                out.push(ch);
+            },
+            '}' | ']' => {
+
+                reader.next();
+
+                if let Some(comma) = pending_comma.take() {
+
+                    out.replace_range(comma..comma + 1, " ");
+                }
+
+                let opening = if ch == '}' { '{' } else { '[' };
+
+                if containers.pop() != Some(opening) {
+
+                    return Err(ParseError::InvalidJson5(format!("unexpected '{}'", ch), start));
+                }
+
+                expect_name = false;
+                out.push(ch);
+            },
+            ',' => {
+
+                reader.next();
+
+                pending_comma = Some(out.len());
+                expect_name = containers.last() == Some(&'{');
+
+                out.push(ch);
+            },
+            ':' => {
+
+                reader.next();
+
+                pending_comma = None;
+                expect_name = false;
+
+                out.push(ch);
+            },
+            _ => {
+
+                pending_comma = None;
+
+                let word = reader.read_word();
+
+                if expect_name {
+
+                    if !is_identifier(&word) {
+
+                        return Err(ParseError::InvalidJson5(format!("\"{}\" is not a member name", word), start));
+                    }
+
+                    out.push('"');
+                    out.push_str(&word);
+                    out.push('"');
+                } else {
+
+                    out.push_str(&literal(&word));
+                }
+
+                expect_name = false;
+            },
+        }
+    }
+
+    Ok(())
+}
+
+// The characters of the text and where the next one is
+struct Reader<'a> {
+    chars: Peekable<Chars<'a>>,
+    position: Position,
+}
+
+impl Reader<'_> {
+
+    fn next(&mut self) -> Option<char> {
+
+        let ch = self.chars.next()?;
+
+        self.position.offset += ch.len_utf8();
+
+        if ch == '\n' {
+
+            self.position.line += 1;
+            self.position.column = 1;
+        } else {
+
+            self.position.column += 1;
+        }
+
+        Some(ch)
+    }
+
+    // An unquoted name or a literal, up to the next white space, punctuation, quote or comment
+    fn read_word(&mut self) -> String {
+
+        let mut word = String::new();
+
+        while let Some(&ch) = self.chars.peek() {
+
+            if ch.is_whitespace() || ch == '\u{FEFF}' || matches!(ch, ',' | ':' | '[' | ']' | '{' | '}' | '"' | '\'' | '/') {
+
+                break;
+            }
+
+            self.next();
+            word.push(ch);
+        }
+
+        word
+    }
+}
+
+// Reads a string in single or double quotes and writes it in double quotes,
+// returns the number of line endings a backslash took out of it
+fn read_string(reader: &mut Reader, out: &mut String) -> Result<usize, ParseError> {
+
+    let start = reader.position;
+    let quote = reader.next().unwrap_or('"');
+    let mut lines: usize = 0;
+
+    out.push('"');
+
+    loop {
+
+        match reader.next() {
+
+            Some(c) if c == quote => break,
+            Some('\\') => {
+
+                let escape_start = reader.position;
+
+                match reader.next() {
+
+                    Some('\'') => out.push('\''),
+                    Some('v') => out.push_str("\\u000b"),
+                    Some('0') if !reader.chars.peek().is_some_and(char::is_ascii_digit) => out.push_str("\\u0000"),
+                    Some('x') => {
+
+                        let digits: String = (0..2).filter_map(|_| reader.next()).collect();
+
+                        if digits.len() != 2 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+
+                            return Err(ParseError::InvalidJson5("invalid \\x escape".to_string(), escape_start));
+                        }
+
+                        out.push_str("\\u00");
+                        out.push_str(&digits);
+                    },
+                    // The string goes on on the next line
+                    Some('\n') => lines += 1,
+                    Some('\r') => {
+
+                        if reader.chars.peek() == Some(&'\n') {
+
+                            reader.next();
+                        }
+
+                        lines += 1;
+                    },
+                    Some('"') => out.push_str("\\u0022"),
+                    Some(c @ ('\\' | '/' | 'b' | 'f' | 'n' | 'r' | 't' | 'u')) => {
+
+                        out.push('\\');
+                        out.push(c);
+                    },
+                    // Any other character stands for itself
+                    Some(c) => out.push(c),
+                    None => return Err(ParseError::InvalidJson5("unterminated string".to_string(), start)),
+                }
+            },
+            Some('"') => out.push_str("\\u0022"),
+            Some('\n') | Some('\r') | None => return Err(ParseError::InvalidJson5("unterminated string".to_string(), start)),
+            Some(c) => out.push(c),
+        }
+    }
+
+    out.push('"');
+
+    Ok(lines)
+}
+
+// A member name without quotes has to be an ECMAScript identifier, \u escapes are kept as they are
+fn is_identifier(word: &str) -> bool {
+
+    let mut chars = word.chars();
+
+    match chars.next() {
+
+        Some(c) if c.is_alphabetic() || c == '_' || c == '$' || c == '\\' => {},
+        _ => return false,
+    }
+
+    chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$' || c == '\\')
+}
+
+// Writes a JSON5 literal the way JSON does, what is not a JSON5 number is left for the parser to report
+fn literal(word: &str) -> String {
+
+    let (sign, body) = match word.strip_prefix('-') {
+
+        Some(body) => ("-", body),
+        None => ("", word.strip_prefix('+').unwrap_or(word)),
+    };
+
+    if body == "Infinity" {
+
+        return format!("{}Infinity", sign);
+    }
+    if body == "NaN" {
+
+        return "NaN".to_string();
+    }
+
+    if let Some(hex) = body.strip_prefix("0x").or_else(|| body.strip_prefix("0X")) {
+
+        return match u128::from_str_radix(hex, 16) {
+
+            Ok(value) => format!("{}{}", sign, value),
+            Err(_) => word.to_string(),
+        };
+    }
+
+    if !body.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+
+        return word.to_string();
+    }
+
+    let mut number = String::from(sign);
+
+    if body.starts_with('.') {
+
+        number.push('0');
+    }
+
+    let mut chars = body.chars().peekable();
+
+    while let Some(c) = chars.next() {
+
+        number.push(c);
+
+        if c == '.' && !chars.peek().is_some_and(char::is_ascii_digit) {
+
+            number.push('0');
+        }
+    }
+
+    number
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::{parse_str, ParserOptions};
+    use crate::json_object::{JsonObject, NumberKind};
+
+    fn parse(text: &str) -> Result<JsonObject, ParseError> {
+        parse_str(text, &ParserOptions { json5: true, ..ParserOptions::default() }).map(|jobj| *jobj.unwrap())
+    }
+
+    #[test]
+    fn same_tree_as_json() {
+        let json5 = "// settings\n{\n    name: 'app', /* inline */\n    $port: 0x1F90,\n    ratio: .5,\n    half: 5.,\n    big: +1e3,\n    list: [1, 2,],\n}\n";
+        let json = "{\n    \"name\": \"app\",\n    \"$port\": 8080,\n    \"ratio\": 0.5,\n    \"half\": 5.0,\n    \"big\": 1e3,\n    \"list\": [1, 2]\n}";
+
+        assert_eq!(parse(json5).unwrap().to_json_string(), parse_str(json, &ParserOptions::default()).unwrap().unwrap().to_json_string());
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(to_json(r"{'it\'s': 'A\x41\v\0'}").unwrap(), r#"{"it's": "A\u0041\u000b\u0000"}"#);
+        assert_eq!(to_json("{a: 'one \\\ntwo'\n}").unwrap(), "{\"a\": \"one two\"\n\n}");
+    }
+
+    #[test]
+    fn double_quotes_inside_strings() {
+        let jobj = parse("{\n    a: 'say \"hi\"',\n    b: \"x\\\"y\",\n    c: 1\n}").unwrap();
+
+        assert_eq!(jobj.to_json_string(), r#"{"a":"say \u0022hi\u0022","b":"x\u0022y","c":1}"#);
+
+        let values: Vec<String> = jobj.iter().map(|key| crate::escape::unescape_lossy(key.get_value())).collect();
+        assert_eq!(values, ["say \"hi\"", "x\"y", "1"]);
+
+        assert_eq!(parse("{a: 'say \"hi\"'}").unwrap().to_json_string(), r#"{"a":"say \u0022hi\u0022"}"#);
+    }
+
+    #[test]
+    fn lines_are_kept() {
+        let jobj = parse("/*\n header\n */\n{\n    a: 1\n}").unwrap();
+
+        assert_eq!(jobj.iter().next().unwrap().value_span().unwrap().start.line, 5);
+    }
+
+    #[test]
+    fn non_finite_numbers() {
+        let jobj = parse("{\n    i: Infinity,\n    j: -Infinity,\n    k: NaN\n}").unwrap();
+
+        assert!(jobj.iter().all(|key| key.get_number_kind() == Some(NumberKind::Float)));
+        assert_eq!(jobj.to_json_string(), r#"{"i":null,"j":null,"k":null}"#);
+        assert!(crate::canonical::to_canonical_string(&jobj).is_err());
+
+        assert!(matches!(parse_str("{\n    \"k\": NaN\n}", &ParserOptions::default()), Err(ParseError::InvalidNumber(..))));
+    }
+
+    #[test]
+    fn errors() {
+        for (text, message) in [("{\n  a: 'x\n}", "unterminated string"), ("{\n  /* x \n}", "unterminated comment"), ("{\n  1a: 2\n}", "\"1a\" is not a member name"), ("{\n  a: [1}\n}", "unexpected '}'"), ("{\n  a: 1 / 2\n}", "unexpected '/'")] {
+            match parse(text) {
+                Err(ParseError::InvalidJson5(found, at)) => {
+                    assert_eq!(found, message);
+                    assert_eq!(at.line, 2);
+                },
+                other => panic!("{}: {:?}", text, other.map(|jobj| jobj.to_json_string())),
+            }
+        }
+    }
+}
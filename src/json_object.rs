@@ -15,17 +15,46 @@ pub enum ValueType {
     ArrayType,     // A JSON array
 }
 
+/// How a number is written, the parser only accepts numbers of the RFC 8259 grammar.
+///
+/// JSON5 input adds Infinity, -Infinity and NaN, kept as NumberType keys with that text. They are Float,
+/// and since JSON has nothing to write them as, to_json_string() writes them as null, as ToJson does for
+/// non-finite floats. canonical::to_canonical_string() refuses them, RFC 8785 allows finite numbers only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberKind {
+    Integer, // Digits only, with an optional minus sign, e.g. -42
+    Float,   // With a fraction, an exponent or both, e.g. 3.14, 1e10, -2.5E-3, or Infinity, -Infinity and NaN
+}
+
+/// A location in the parsed file. Lines and columns start at 1, columns count characters,
+/// the offset counts bytes from the start of the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+    pub offset: usize,
+}
+
+/// A stretch of the parsed file, from `start` up to, but not including, `end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
 /// Represents a key-value pair in a JSON object with linked list capabilities.
 /// Each Key contains metadata about the value it holds and can link to other keys.
 #[derive(Debug, Clone)]
 pub struct Key {
-    name: String,           // The name (key) of the JSON property
-    value_type: ValueType,  // The type of the associated value
-    value: String,          // The string representation of the value
-    ptr: JsonKeyPtr,        // Optional pointer to another Key (for nested structures)
-    n: usize,               // Possibly a count or size (usage context dependent), number of keys in the object
-    next: Option<Box<Key>>, // Next key in the linked list
-    prev: Option<Box<Key>>, // Previous key in the linked list
+    name: String,             // The name (key) of the JSON property
+    value_type: ValueType,    // The type of the associated value
+    value: String,            // The string representation of the value
+    ptr: JsonKeyPtr,          // Optional pointer to another Key (for nested structures)
+    n: usize,                 // Possibly a count or size (usage context dependent), number of keys in the object
+    next: Option<Box<Key>>,   // Next key in the linked list
+    prev: Option<Box<Key>>,   // Previous key in the linked list
+    name_span: Option<Span>,  // Where the name, quotes included, is in the parsed file
+    value_span: Option<Span>, // Where the value, quotes, braces or brackets included, is in the parsed file
 }
 
 impl Key {
@@ -44,6 +73,8 @@ impl Key {
             n: 0,
             next: None,
             prev: None,
+            name_span: None,
+            value_span: None,
         }
     }
 
@@ -70,6 +101,19 @@ impl Key {
         &self.value
     }
 
+    /// Returns how the number held by this key is written, None for any other type of value.
+    pub fn get_number_kind(&self) -> Option<NumberKind> {
+        if self.value_type != ValueType::NumberType {
+            return None;
+        }
+
+        if self.value.contains(['.', 'e', 'E']) || is_non_finite(&self.value) {
+            Some(NumberKind::Float)
+        } else {
+            Some(NumberKind::Integer)
+        }
+    }
+
     /// Returns a reference to the next key in the linked list, if any.
     pub fn get_next(&self) -> Option<&Box<Key>> {
         self.next.as_ref()
@@ -120,6 +164,37 @@ impl Key {
         self.value = value;
     }
 
+    /// Returns where the key, from its name to the end of its value, is in the parsed file.
+    /// Array elements have no name, their span is the one of their value.
+    /// Keys that were not produced by the parser have no span. Spans are not updated when the tree is edited.
+    pub fn span(&self) -> Option<Span> {
+        match (self.name_span, self.value_span) {
+            (Some(name), Some(value)) => Some(Span { start: name.start, end: value.end }),
+            (None, value) => value,
+            (name, None) => name,
+        }
+    }
+
+    /// Returns where the name of the key is in the parsed file, None for array elements.
+    pub fn name_span(&self) -> Option<Span> {
+        self.name_span
+    }
+
+    /// Returns where the value of the key is in the parsed file.
+    pub fn value_span(&self) -> Option<Span> {
+        self.value_span
+    }
+
+    /// Sets where the name and the value of the key are in the parsed file.
+    ///
+    /// # Arguments
+    /// * `name_span` - Span of the name, None for array elements
+    /// * `value_span` - Span of the value
+    pub fn set_spans(&mut self, name_span: Option<Span>, value_span: Option<Span>) {
+        self.name_span = name_span;
+        self.value_span = value_span;
+    }
+
     /// Adds a key to the nested structure pointed to by `ptr`.
     /// This is used for creating nested objects or arrays within a key.
     ///
@@ -141,6 +216,134 @@ impl Key {
             }
         }
     }
+
+    /// Returns a copy of this key and everything nested under it, without its siblings.
+    /// A derived `clone()` also copies the `next` chain, i.e. every key that follows this one in the list.
+    pub fn clone_detached(&self) -> Key {
+        Key {
+            name: self.name.clone(),
+            value_type: self.value_type.clone(),
+            value: self.value.clone(),
+            ptr: self.ptr.clone(),
+            n: self.n,
+            next: None,
+            prev: None,
+            name_span: self.name_span,
+            value_span: self.value_span,
+        }
+    }
+
+    /// Returns an iterator over the keys nested under this key,
+    /// i.e. the members of an object or the elements of an array.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { current: self.ptr.as_deref() }
+    }
+
+    /// Detaches the nested keys and returns them in order, leaving this key with no children.
+    /// Together with `set_keys` this is the way to insert, remove or reorder nested keys.
+    pub fn take_keys(&mut self) -> Vec<Key> {
+        let ret = unlink(self.ptr.take());
+        self.n = 0;
+        ret
+    }
+
+    /// Replaces the nested keys, linking them in the given order and updating the count.
+    ///
+    /// # Arguments
+    /// * `keys` - The new children of this key
+    pub fn set_keys(&mut self, keys: Vec<Key>) {
+        self.n = keys.len();
+        self.ptr = link(keys);
+    }
+
+    /// Writes the value of this key as compact JSON text, without the name of the key.
+    /// Names and string values are written as they are stored, i.e. with their escape sequences.
+    /// Infinity, -Infinity and NaN are written as null, see `NumberKind`.
+    pub fn to_json_string(&self) -> String {
+        let mut ret = String::new();
+        write_value(&mut ret, self);
+        ret
+    }
+}
+
+// Breaks a linked list of keys into a vector of unlinked keys
+fn unlink(mut ptr: JsonKeyPtr) -> Vec<Key> {
+    let mut ret = Vec::new();
+    while let Some(mut key) = ptr {
+        ptr = key.next.take();
+        key.prev = None;
+        ret.push(*key);
+    }
+    ret
+}
+
+// Links a vector of keys into a linked list, returning its head
+fn link(keys: Vec<Key>) -> JsonKeyPtr {
+    let mut head: JsonKeyPtr = None;
+    for mut key in keys.into_iter().rev() {
+        key.next = head;
+        head = Some(Box::new(key));
+    }
+    head
+}
+
+fn write_members(out: &mut String, members: Iter<'_>) {
+    out.push('{');
+    for (i, member) in members.enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push('"');
+        out.push_str(&member.name);
+        out.push_str("\":");
+        write_value(out, member);
+    }
+    out.push('}');
+}
+
+// The numbers of JSON5 that JSON does not have
+fn is_non_finite(raw: &str) -> bool {
+    matches!(raw.trim(), "Infinity" | "-Infinity" | "NaN")
+}
+
+fn write_value(out: &mut String, key: &Key) {
+    match key.value_type {
+        ValueType::StringType => {
+            out.push('"');
+            out.push_str(&key.value);
+            out.push('"');
+        },
+        ValueType::NumberType if is_non_finite(&key.value) => out.push_str("null"),
+        ValueType::NumberType | ValueType::BooleanType => out.push_str(key.value.trim()),
+        ValueType::NullType => out.push_str("null"),
+        ValueType::ObjectType => write_members(out, key.iter()),
+        ValueType::ArrayType => {
+            out.push('[');
+            for (i, element) in key.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(out, element);
+            }
+            out.push(']');
+        },
+    }
+}
+
+/// Iterator over a linked list of sibling keys, following the `next` pointers.
+#[derive(Clone, Default)]
+pub struct Iter<'a> {
+    current: Option<&'a Key>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a Key;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self.current?;
+        self.current = key.next.as_deref();
+        Some(key)
+    }
 }
 
 /// Type alias for an optional boxed Key, representing a nullable pointer to a Key.
@@ -180,6 +383,34 @@ impl JsonObject {
         self.n
     }
 
+    /// Returns an iterator over the top level keys of the object.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { current: self.ptr.as_deref() }
+    }
+
+    /// Detaches the top level keys and returns them in order, leaving the object empty.
+    pub fn take_keys(&mut self) -> Vec<Key> {
+        let ret = unlink(self.ptr.take());
+        self.n = 0;
+        ret
+    }
+
+    /// Replaces the top level keys, linking them in the given order and updating the count.
+    ///
+    /// # Arguments
+    /// * `keys` - The new members of the object
+    pub fn set_keys(&mut self, keys: Vec<Key>) {
+        self.n = keys.len();
+        self.ptr = link(keys);
+    }
+
+    /// Writes the object as compact JSON text.
+    pub fn to_json_string(&self) -> String {
+        let mut ret = String::new();
+        write_members(&mut ret, self.iter());
+        ret
+    }
+
     /// Sets the count of keys in the object.
     ///
     /// # Arguments
@@ -273,6 +504,7 @@ impl JsonObject {
     ///
     /// # Examples
     /// ```
+    /// # use json_rust::json_object::{JsonObject, Key, ValueType};
     /// let mut obj = JsonObject::new();
     /// obj.add_key(Box::new(Key::new("name".into(), ValueType::StringType, "John".into())));
     /// ```
@@ -9,5 +9,22 @@ pub mod file_content;
 pub mod json;
 pub mod json_object;
 pub mod helper;
+pub mod escape;
+pub mod canonical;
+pub mod pointer;
+pub mod patch;
+pub mod merge_patch;
+pub mod compare;
+pub mod stats;
+pub mod flatten;
+pub mod search;
+pub mod schema;
+pub mod codegen;
+pub mod from_json;
+pub mod to_json;
+pub mod json5;
+pub mod parser;
+#[cfg(feature = "serde")]
+pub mod serde_interop;
 
 
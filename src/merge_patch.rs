@@ -0,0 +1,231 @@
+/*
+    src/merge_patch.rs
+    This file is part of the JSON-rust crate.
+    Written by, Q@khaa.pk
+ */
+
+/*
+    RFC 7396 JSON Merge Patch.
+    A merge patch looks like the document it modifies: objects are merged member by member, a member
+    set to null is deleted, and any other value (arrays included) replaces the value found in the target.
+    Since a null member means "delete", a merge patch can not set a member to null; create_merge_patch()
+    can only express such a change by deleting the member.
+ */
+
+use crate::escape::unescape_lossy;
+use crate::json_object::{ValueType, Key, JsonObject, Iter};
+use crate::patch::values_equal;
+
+impl JsonObject {
+    /// Applies an RFC 7396 merge patch to the object.
+    ///
+    /// # Arguments
+    /// * `patch` - The merge patch, e.g. parsed with `json::parser`
+    pub fn merge_patch(&mut self, patch: &JsonObject) {
+
+        let mut members = self.take_keys();
+
+        merge_members(&mut members, patch.iter());
+
+        self.set_keys(members);
+    }
+}
+
+/// Produces the merge patch that turns `a` into `b`, so that `a.merge_patch(&create_merge_patch(&a, &b))`
+/// gives `b`, provided no object in `b`, at any depth, has a member whose value is null (nulls in arrays are fine).
+/// A null member in a patch means "delete", so RFC 7396 has no way to set one: applying the patch leaves such
+/// members out, whether they are new, changed, or inside an object that replaces a value of another type.
+/// This is a limitation of merge patches, use patch::diff when null members matter.
+///
+/// # Arguments
+/// * `a` - The original document
+/// * `b` - The target document
+pub fn create_merge_patch(a: &JsonObject, b: &JsonObject) -> JsonObject {
+
+    let mut ret = JsonObject::new();
+
+    ret.set_keys(diff_members(a.iter(), b.iter()));
+
+    ret
+}
+
+fn position(members: &[Key], name: &str) -> Option<usize> {
+
+    members.iter().position(|member| unescape_lossy(member.get_name()) == name)
+}
+
+fn merge_members(target: &mut Vec<Key>, patch: Iter<'_>) {
+
+    for member in patch {
+
+        let name = unescape_lossy(member.get_name());
+
+        match member.get_value_type() {
+
+            ValueType::NullType => {
+
+                // Duplicate names included
+                target.retain(|key| unescape_lossy(key.get_name()) != name);
+            },
+            ValueType::ObjectType => match position(target, &name) {
+
+                Some(index) if *target[index].get_value_type() == ValueType::ObjectType => {
+
+                    let key = &mut target[index];
+
+                    let mut merged = key.take_keys();
+                    merge_members(&mut merged, member.iter());
+
+                    key.set_keys(merged);
+                    key.set_value(key.to_json_string());
+                },
+                _ => {
+
+                    // Merging into anything but an object starts from an empty object, which strips the nulls out of the patch
+                    let mut merged = Vec::new();
+                    merge_members(&mut merged, member.iter());
+
+                    let mut key = Key::new(member.get_name().to_string(), ValueType::ObjectType, String::new());
+                    key.set_keys(merged);
+                    key.set_value(key.to_json_string());
+
+                    put(target, &name, key);
+                },
+            },
+            _ => put(target, &name, member.clone_detached()),
+        }
+    }
+}
+
+// Replaces the member in place if it exists (keeping its original spelling of the name), appends it otherwise
+fn put(target: &mut Vec<Key>, name: &str, mut key: Key) {
+
+    match position(target, name) {
+
+        Some(index) => {
+
+            key.set_name(target[index].get_name().to_string());
+            target[index] = key;
+        },
+        None => target.push(key),
+    }
+}
+
+fn diff_members(a: Iter<'_>, b: Iter<'_>) -> Vec<Key> {
+
+    let a: Vec<&Key> = a.collect();
+    let b: Vec<&Key> = b.collect();
+
+    fn find<'a>(members: &[&'a Key], name: &str) -> Option<&'a Key> {
+
+        members.iter().find(|member| unescape_lossy(member.get_name()) == name).copied()
+    }
+
+    let mut ret: Vec<Key> = Vec::new();
+
+    for x in &a {
+
+        let name = unescape_lossy(x.get_name());
+
+        if position(&ret, &name).is_some() {
+
+            continue;
+        }
+
+        match find(&b, &name) {
+
+            None => ret.push(Key::new(x.get_name().to_string(), ValueType::NullType, "null".to_string())),
+            Some(y) if *x.get_value_type() == ValueType::ObjectType && *y.get_value_type() == ValueType::ObjectType => {
+
+                let members = diff_members(x.iter(), y.iter());
+
+                if !members.is_empty() {
+
+                    let mut key = Key::new(x.get_name().to_string(), ValueType::ObjectType, String::new());
+                    key.set_keys(members);
+                    key.set_value(key.to_json_string());
+
+                    ret.push(key);
+                }
+            },
+            Some(y) => {
+
+                if !values_equal(x, y) {
+
+                    ret.push(y.clone_detached());
+                }
+            },
+        }
+    }
+
+    for y in &b {
+
+        let name = unescape_lossy(y.get_name());
+
+        if find(&a, &name).is_none() && position(&ret, &name).is_none() {
+
+            ret.push(y.clone_detached());
+        }
+    }
+
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::{parse_str, ParserOptions};
+
+    fn parse(text: &str) -> JsonObject {
+        parse_str(text, &ParserOptions::default()).unwrap().map(|jobj| *jobj).unwrap_or_else(JsonObject::new)
+    }
+
+    // RFC 7396 Appendix A, the cases whose target, patch and result are objects
+    #[test]
+    fn rfc7396_appendix_a() {
+        let cases = [
+            (r#"{"a":"b"}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"b":"c"}"#, r#"{"a":"b","b":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"a":null}"#, r#"{}"#),
+            (r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#, r#"{"b":"c"}"#),
+            (r#"{"a":["b"]}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"c"}"#, r#"{"a":["b"]}"#, r#"{"a":["b"]}"#),
+            (r#"{"a":{"b":"c"}}"#, r#"{"a":{"b":"d","c":null}}"#, r#"{"a":{"b":"d"}}"#),
+            (r#"{"a":[{"b":"c"}]}"#, r#"{"a":[1]}"#, r#"{"a":[1]}"#),
+            (r#"{"e":null}"#, r#"{"a":1}"#, r#"{"e":null,"a":1}"#),
+            (r#"{}"#, r#"{"a":{"bb":{"ccc":null}}}"#, r#"{"a":{"bb":{}}}"#),
+        ];
+
+        for (target, patch, result) in cases {
+            let mut jobj = parse(target);
+            jobj.merge_patch(&parse(patch));
+
+            assert_eq!(jobj.to_json_string(), result, "{} + {}", target, patch);
+        }
+    }
+
+    #[test]
+    fn create_round_trip() {
+        let a = parse(r#"{"title": "Goodbye!", "author": {"givenName": "John", "familyName": "Doe"}, "tags": ["example", "sample"], "content": "text"}"#);
+        let b = parse(r#"{"title": "Hello!", "author": {"givenName": "John"}, "tags": ["example"], "content": "text", "phoneNumber": "+01-123-456-7890"}"#);
+
+        let patch = create_merge_patch(&a, &b);
+        assert_eq!(patch.to_json_string(), r#"{"title":"Hello!","author":{"familyName":null},"tags":["example"],"phoneNumber":"+01-123-456-7890"}"#);
+
+        let mut a = a;
+        a.merge_patch(&patch);
+        assert_eq!(a.to_json_string(), b.to_json_string());
+    }
+
+    #[test]
+    fn null_members_do_not_round_trip() {
+        let a = parse(r#"{"a": 1}"#);
+        let b = parse(r#"{"a": {"b": null, "c": 2}}"#);
+
+        let patch = create_merge_patch(&a, &b);
+
+        let mut a = a;
+        a.merge_patch(&patch);
+        assert_eq!(a.to_json_string(), r#"{"a":{"c":2}}"#);
+    }
+}
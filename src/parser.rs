@@ -0,0 +1,279 @@
+/*
+    src/parser.rs
+    This file is part of the JSON-rust crate.
+    Written by, Q@khaa.pk
+ */
+
+/*
+    A parser that holds its settings and its buffers, for programs parsing many documents with the same settings.
+    ParserBuilder sets every ParserOptions field, JSON or JSON5, duplicate keys, limits, numbers, escapes and the raw
+    text of containers, and builds a Parser. The buffers it reads the text into are kept from one parse to the next,
+    so a loop parsing thousands of small messages allocates them once, only the tree it returns is new every time.
+
+    let mut parser = Parser::builder().json5(true).duplicate_keys(DuplicateKeyPolicy::Error).max_depth(16).build();
+    for message in messages {
+        let tree = parser.parse_str(message)?;
+    }
+
+    json::parser_with_options() and json::parse_str() are the same parser without the buffers.
+ */
+
+use std::fs::{self, File};
+use std::io::Read;
+
+use crate::file_content::FileContent;
+use crate::json::{parse_file_content, DuplicateKeyPolicy, EscapeMode, NumberMode, ParseError, ParserOptions, Scratch};
+use crate::json_object::JsonObject;
+use crate::json5;
+
+/// A parser with its settings, see `ParserBuilder`.
+pub struct Parser {
+    options: ParserOptions,
+    input: String,             // The file being parsed
+    text: String,              // The input rewritten as JSON, in JSON5 mode
+    file_content: FileContent, // The lines of the text
+    scratch: Scratch,
+}
+
+impl Parser {
+
+    /// A parser with the settings of `options`.
+    pub fn new(options: ParserOptions) -> Self {
+
+        Parser { options, input: String::new(), text: String::new(), file_content: FileContent::from_string(String::new()), scratch: Scratch::default() }
+    }
+
+    /// Returns a builder starting from the default settings.
+    pub fn builder() -> ParserBuilder {
+
+        ParserBuilder::new()
+    }
+
+    /// Returns the settings of the parser.
+    pub fn options(&self) -> &ParserOptions {
+
+        &self.options
+    }
+
+    /// Parses JSON text that is in memory.
+    ///
+    /// # Arguments
+    /// * `text` - The JSON, or JSON5, text
+    ///
+    /// # Returns
+    /// * `Ok(Some(Box<JsonObject>))` if parsing is successful, containing the root `JsonObject`.
+    /// * `Ok(None)` if the text holds no key at all.
+    /// * `Err(ParseError)` if the text cannot be parsed or is over a limit.
+    pub fn parse_str(&mut self, text: &str) -> Result<Option<Box<JsonObject>>, ParseError> {
+
+        if text.len() > self.options.max_input_size {
+
+            return Err(ParseError::InputSizeLimitExceeded(self.options.max_input_size));
+        }
+
+        if self.options.json5 {
+
+            self.text.clear();
+            json5::write_json(text, &mut self.text)?;
+            self.file_content.load(&self.text);
+        } else {
+
+            self.file_content.load(text);
+        }
+
+        parse_file_content(&self.file_content, &self.options, &mut self.scratch)
+    }
+
+    /// Parses a JSON file.
+    ///
+    /// # Arguments
+    /// * `file_name` - The path of the file
+    ///
+    /// # Returns
+    /// * The same as `parse_str`, or `Err(ParseError::IoError)` if the file cannot be read.
+    pub fn parse_file(&mut self, file_name: &str) -> Result<Option<Box<JsonObject>>, ParseError> {
+
+        // Refuse a file over the size limit before reading any of it
+        if fs::metadata(file_name)?.len() > self.options.max_input_size as u64 {
+
+            return Err(ParseError::InputSizeLimitExceeded(self.options.max_input_size));
+        }
+
+        let mut input = std::mem::take(&mut self.input);
+        input.clear();
+
+        let ret = match File::open(file_name).and_then(|mut file| file.read_to_string(&mut input)) {
+
+            Ok(_) => self.parse_str(&input),
+            Err(e) => Err(ParseError::IoError(e)),
+        };
+
+        self.input = input;
+
+        ret
+    }
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Parser::new(ParserOptions::default())
+    }
+}
+
+/// Sets the settings of a `Parser`, each one starts at its `ParserOptions::default()` value.
+#[derive(Debug, Clone, Default)]
+pub struct ParserBuilder {
+    options: ParserOptions,
+}
+
+impl ParserBuilder {
+
+    pub fn new() -> Self {
+
+        ParserBuilder::default()
+    }
+
+    /// JSON5 (lenient) when true, JSON (strict) when false, the default.
+    pub fn json5(mut self, json5: bool) -> Self {
+
+        self.options.json5 = json5;
+        self
+    }
+
+    pub fn duplicate_keys(mut self, policy: DuplicateKeyPolicy) -> Self {
+
+        self.options.duplicate_keys = policy;
+        self
+    }
+
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+
+        self.options.max_depth = max_depth;
+        self
+    }
+
+    pub fn max_input_size(mut self, max_input_size: usize) -> Self {
+
+        self.options.max_input_size = max_input_size;
+        self
+    }
+
+    pub fn max_string_length(mut self, max_string_length: usize) -> Self {
+
+        self.options.max_string_length = max_string_length;
+        self
+    }
+
+    pub fn max_key_length(mut self, max_key_length: usize) -> Self {
+
+        self.options.max_key_length = max_key_length;
+        self
+    }
+
+    pub fn max_members(mut self, max_members: usize) -> Self {
+
+        self.options.max_members = max_members;
+        self
+    }
+
+    pub fn max_elements(mut self, max_elements: usize) -> Self {
+
+        self.options.max_elements = max_elements;
+        self
+    }
+
+    pub fn max_nodes(mut self, max_nodes: usize) -> Self {
+
+        self.options.max_nodes = max_nodes;
+        self
+    }
+
+    pub fn numbers(mut self, numbers: NumberMode) -> Self {
+
+        self.options.numbers = numbers;
+        self
+    }
+
+    pub fn escapes(mut self, escapes: EscapeMode) -> Self {
+
+        self.options.escapes = escapes;
+        self
+    }
+
+    /// Whether objects and arrays keep their text as the value of their key, true by default.
+    pub fn keep_raw(mut self, keep_raw: bool) -> Self {
+
+        self.options.keep_raw = keep_raw;
+        self
+    }
+
+    pub fn build(self) -> Parser {
+
+        Parser::new(self.options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_sets_every_option() {
+        let parser = Parser::builder().json5(true).duplicate_keys(DuplicateKeyPolicy::Error).max_depth(4).max_input_size(1).max_string_length(2)
+            .max_key_length(3).max_members(5).max_elements(6).max_nodes(7).numbers(NumberMode::Keep).escapes(EscapeMode::Validate).keep_raw(false).build();
+
+        let options = parser.options();
+        assert!(options.json5);
+        assert_eq!(options.duplicate_keys, DuplicateKeyPolicy::Error);
+        assert_eq!((options.max_depth, options.max_input_size, options.max_string_length, options.max_key_length), (4, 1, 2, 3));
+        assert_eq!((options.max_members, options.max_elements, options.max_nodes), (5, 6, 7));
+        assert_eq!((options.numbers, options.escapes, options.keep_raw), (NumberMode::Keep, EscapeMode::Validate, false));
+    }
+
+    #[test]
+    fn buffers_are_reused() {
+        let mut parser = Parser::builder().json5(true).build();
+
+        for i in 0..3 {
+            let jobj = parser.parse_str(&format!("{{\n  // message {}\n  id: {},\n}}", i, i)).unwrap().unwrap();
+
+            assert_eq!(jobj.to_json_string(), format!(r#"{{"id":{}}}"#, i));
+        }
+
+        let mut parser = Parser::default();
+        assert!(parser.parse_str("{\n  \"a\": [1]\n}").is_ok());
+        assert!(matches!(parser.parse_str("{\n  \"a\": 01\n}"), Err(ParseError::InvalidNumber(_, _))));
+        assert_eq!(parser.parse_str("{\n  \"b\": 2\n}").unwrap().unwrap().to_json_string(), r#"{"b":2}"#);
+    }
+
+    #[test]
+    fn raw_text_and_escapes() {
+        let text = "{\n  \"a\": {\"b\": [1, 2]},\n  \"c\": \"\\q\"\n}";
+
+        let jobj = Parser::builder().keep_raw(false).build().parse_str(text).unwrap().unwrap();
+        assert_eq!(jobj.iter().next().unwrap().get_value(), "");
+        assert_eq!(jobj.to_json_string(), r#"{"a":{"b":[1,2]},"c":"\q"}"#);
+
+        match Parser::builder().escapes(EscapeMode::Validate).build().parse_str(text) {
+            Err(ParseError::InvalidEscape(_, at)) => assert_eq!(at.line, 3),
+            other => panic!("{:?}", other.map(|jobj| jobj.map(|jobj| jobj.to_json_string()))),
+        }
+    }
+
+    #[test]
+    fn files() {
+        let mut parser = Parser::builder().max_input_size(8).build();
+
+        assert!(matches!(parser.parse_file("no such directory/no such file.json"), Err(ParseError::IoError(_))));
+
+        let path = std::env::temp_dir().join(format!("json-rust-parser-{}.json", std::process::id()));
+        fs::write(&path, "{\n  \"a\": 1\n}").unwrap();
+
+        let too_large = parser.parse_file(path.to_str().unwrap());
+        let parsed = Parser::default().parse_file(path.to_str().unwrap());
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(too_large, Err(ParseError::InputSizeLimitExceeded(8))));
+        assert_eq!(parsed.unwrap().unwrap().to_json_string(), r#"{"a":1}"#);
+    }
+}
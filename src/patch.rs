@@ -0,0 +1,826 @@
+/*
+    src/patch.rs
+    This file is part of the JSON-rust crate.
+    Written by, Q@khaa.pk
+ */
+
+/*
+    RFC 6902 JSON Patch.
+    diff() compares two parsed trees and produces the list of operations that turns the first one into the second one.
+    The operations are meant to be applied in order, every path is valid against the document as it is after the
+    operations that come before it (array indices in particular take earlier insertions and removals into account).
+    apply_patch() does the opposite, it runs a list of operations (e.g. read from a patch document with parse_patch())
+    against a tree. Either every operation succeeds or the tree is left as it was.
+ */
+
+use crate::compare::{self, EqualityOptions};
+use crate::escape::{escape, unescape_lossy};
+use crate::json_object::{ValueType, Key, JsonObject, Iter};
+use crate::pointer::{Path, parse_pointer, parse_index};
+
+/// One operation of an RFC 6902 JSON Patch document.
+/// Paths are JSON Pointer strings, values are detached `Key`s whose names are not significant.
+#[derive(Debug, Clone)]
+pub enum PatchOp {
+    Add { path: String, value: Key },
+    Remove { path: String },
+    Replace { path: String, value: Key },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: Key },
+}
+
+/// Errors that can occur while reading or applying a patch.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchError {
+    MalformedOperation(usize, String), // Element at the given position of the patch document is not a valid operation, and why
+    InvalidPointer(String),            // "path" or "from" is not a valid JSON Pointer
+    PathNotFound(String),              // Nothing exists at the location, or its parent is not an object or an array
+    InvalidIndex(String),              // Array index is not a number or is out of bounds
+    TestFailed(String),                // "test" found a different value at the location
+    MoveIntoDescendant(String),        // "move" of a value into one of its own children
+    InvalidRoot(String),               // The root can not be removed, and can only be replaced by an object
+}
+
+/// How `diff` compares two arrays that are not equal.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArrayDiff {
+    Lcs,     // Element wise, based on the longest common subsequence of the two arrays
+    Replace, // The whole array is replaced by the new one
+}
+
+/// Options controlling the output of `diff_with_options`.
+#[derive(Debug, Clone)]
+pub struct DiffOptions {
+    pub arrays: ArrayDiff,  // Array comparison strategy, element wise by default
+    pub detect_moves: bool, // Turn a removed member and an identical added member into a single "move", on by default
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions { arrays: ArrayDiff::Lcs, detect_moves: true }
+    }
+}
+
+// Operations are collected with typed paths and borrowed values first, they are turned into PatchOps at the very end
+enum PendingOp<'a> {
+    Add(Path, &'a Key),
+    Remove(Path, &'a Key), // The removed value is kept around for move detection
+    Replace(Path, &'a Key),
+    Move(Path, Path),
+}
+
+/// Computes the JSON Patch that turns `a` into `b`, with the default options.
+///
+/// # Arguments
+/// * `a` - The original document
+/// * `b` - The target document
+pub fn diff(a: &JsonObject, b: &JsonObject) -> Vec<PatchOp> {
+
+    diff_with_options(a, b, &DiffOptions::default())
+}
+
+/// Computes the JSON Patch that turns `a` into `b`.
+///
+/// # Arguments
+/// * `a` - The original document
+/// * `b` - The target document
+/// * `options` - Array strategy and move detection
+///
+/// # Returns
+/// The list of operations, empty when both documents are equal.
+pub fn diff_with_options(a: &JsonObject, b: &JsonObject, options: &DiffOptions) -> Vec<PatchOp> {
+
+    let mut pending: Vec<PendingOp> = Vec::new();
+
+    diff_members(a.iter(), b.iter(), &Path::new(), options, &mut pending);
+
+    if options.detect_moves {
+
+        pending = detect_moves(pending);
+    }
+
+    pending.into_iter().map(|op| match op {
+
+        PendingOp::Add(path, value) => PatchOp::Add { path: path.to_pointer(), value: value.clone_detached() },
+        PendingOp::Remove(path, _) => PatchOp::Remove { path: path.to_pointer() },
+        PendingOp::Replace(path, value) => PatchOp::Replace { path: path.to_pointer(), value: value.clone_detached() },
+        PendingOp::Move(from, path) => PatchOp::Move { from: from.to_pointer(), path: path.to_pointer() },
+    }).collect()
+}
+
+/// Writes a list of operations as the text of a JSON Patch document.
+///
+/// # Arguments
+/// * `ops` - The operations, e.g. as returned by `diff`
+pub fn patch_to_string(ops: &[PatchOp]) -> String {
+
+    let mut ret = String::from("[");
+
+    for (i, op) in ops.iter().enumerate() {
+
+        if i > 0 {
+
+            ret.push(',');
+        }
+
+        let (name, from, path, value) = match op {
+
+            PatchOp::Add { path, value } => ("add", None, path, Some(value)),
+            PatchOp::Remove { path } => ("remove", None, path, None),
+            PatchOp::Replace { path, value } => ("replace", None, path, Some(value)),
+            PatchOp::Move { from, path } => ("move", Some(from), path, None),
+            PatchOp::Copy { from, path } => ("copy", Some(from), path, None),
+            PatchOp::Test { path, value } => ("test", None, path, Some(value)),
+        };
+
+        ret.push_str(&format!("{{\"op\":\"{}\"", name));
+
+        if let Some(from) = from {
+
+            ret.push_str(&format!(",\"from\":\"{}\"", escape(from)));
+        }
+
+        ret.push_str(&format!(",\"path\":\"{}\"", escape(path)));
+
+        if let Some(value) = value {
+
+            ret.push_str(",\"value\":");
+            ret.push_str(&value.to_json_string());
+        }
+
+        ret.push('}');
+    }
+
+    ret.push(']');
+
+    ret
+}
+
+/// Reads the operations of a JSON Patch document parsed with `json::parser`.
+/// The document is an array of objects, each with an "op" and a "path" member, plus "from" or "value"
+/// depending on the operation.
+///
+/// # Arguments
+/// * `doc` - The parsed patch document
+///
+/// # Returns
+/// * `Ok(Vec<PatchOp>)` holding the operations in document order
+/// * `Err(PatchError::MalformedOperation)` naming the first element that is not a valid operation
+pub fn parse_patch(doc: &JsonObject) -> Result<Vec<PatchOp>, PatchError> {
+
+    let mut ret = Vec::new();
+
+    for (index, element) in doc.iter().enumerate() {
+
+        if *element.get_value_type() != ValueType::ObjectType || !element.get_name().is_empty() {
+
+            return Err(PatchError::MalformedOperation(index, "operation is not an object".to_string()));
+        }
+
+        let member = |name: &str| element.iter().find(|member| unescape_lossy(member.get_name()) == name);
+
+        let pointer = |name: &str| -> Result<String, PatchError> {
+
+            match member(name) {
+
+                Some(key) if *key.get_value_type() == ValueType::StringType => Ok(unescape_lossy(key.get_value())),
+                Some(_) => Err(PatchError::MalformedOperation(index, format!("\"{}\" is not a string", name))),
+                None => Err(PatchError::MalformedOperation(index, format!("\"{}\" is missing", name))),
+            }
+        };
+
+        let value = || -> Result<Key, PatchError> {
+
+            member("value").map(|key| key.clone_detached()).ok_or_else(|| PatchError::MalformedOperation(index, "\"value\" is missing".to_string()))
+        };
+
+        let op = match member("op") {
+
+            Some(key) if *key.get_value_type() == ValueType::StringType => unescape_lossy(key.get_value()),
+            _ => return Err(PatchError::MalformedOperation(index, "\"op\" is missing or not a string".to_string())),
+        };
+
+        ret.push(match op.as_str() {
+
+            "add" => PatchOp::Add { path: pointer("path")?, value: value()? },
+            "remove" => PatchOp::Remove { path: pointer("path")? },
+            "replace" => PatchOp::Replace { path: pointer("path")?, value: value()? },
+            "move" => PatchOp::Move { from: pointer("from")?, path: pointer("path")? },
+            "copy" => PatchOp::Copy { from: pointer("from")?, path: pointer("path")? },
+            "test" => PatchOp::Test { path: pointer("path")?, value: value()? },
+            other => return Err(PatchError::MalformedOperation(index, format!("unknown operation \"{}\"", other))),
+        });
+    }
+
+    Ok(ret)
+}
+
+/// Applies a list of operations to a tree, in order.
+///
+/// The operations run against a copy of the tree, which replaces the original only once all of them
+/// have succeeded. If any operation fails the tree is left untouched. Counts and sibling links of every
+/// modified object or array are updated, and so is the raw text of the objects and arrays on the way to
+/// each modified location.
+///
+/// # Arguments
+/// * `jobj` - The tree to patch
+/// * `ops` - The operations, e.g. as returned by `parse_patch` or `diff`
+///
+/// # Returns
+/// * `Ok(())` if every operation succeeded
+/// * `Err(PatchError)` describing the first operation that failed
+pub fn apply_patch(jobj: &mut JsonObject, ops: &[PatchOp]) -> Result<(), PatchError> {
+
+    let mut work = jobj.clone();
+
+    for op in ops {
+
+        apply_op(&mut work, op)?;
+    }
+
+    *jobj = work;
+
+    Ok(())
+}
+
+/// Reads a JSON Patch document and applies it to a tree, see `parse_patch` and `apply_patch`.
+///
+/// # Arguments
+/// * `jobj` - The tree to patch
+/// * `patch` - The parsed patch document
+pub fn apply_patch_document(jobj: &mut JsonObject, patch: &JsonObject) -> Result<(), PatchError> {
+
+    let ops = parse_patch(patch)?;
+
+    apply_patch(jobj, &ops)
+}
+
+/// Deep comparison of the values of two keys, the names of the keys themselves are ignored.
+/// Object members are compared regardless of their order, numbers by their exact decimal value and
+/// strings by their decoded characters. When a member name is repeated only its first occurrence counts,
+/// the same way `diff` and the patch operations resolve names.
+///
+/// # Arguments
+/// * `a` - First value
+/// * `b` - Second value
+pub fn values_equal(a: &Key, b: &Key) -> bool {
+
+    compare::values_equal(a, b, &EQUALITY)
+}
+
+const EQUALITY: EqualityOptions = EqualityOptions { ordered_members: false, normalize_numbers: true, count_duplicates: false };
+
+// Decoded names of the members of an object, when a name is repeated only its first occurrence counts
+fn unique_members(members: Iter<'_>) -> Vec<(String, &Key)> {
+
+    let mut ret: Vec<(String, &Key)> = Vec::new();
+
+    for member in members {
+
+        let name = unescape_lossy(member.get_name());
+
+        if !ret.iter().any(|(other, _)| *other == name) {
+
+            ret.push((name, member));
+        }
+    }
+
+    ret
+}
+
+fn diff_members<'a>(a: Iter<'a>, b: Iter<'a>, path: &Path, options: &DiffOptions, pending: &mut Vec<PendingOp<'a>>) {
+
+    let a = unique_members(a);
+    let b = unique_members(b);
+
+    for (name, x) in &a {
+
+        match b.iter().find(|(other, _)| other == name) {
+
+            Some((_, y)) => diff_values(x, y, &path.with_key(name), options, pending),
+            None => pending.push(PendingOp::Remove(path.with_key(name), x)),
+        }
+    }
+
+    for (name, y) in &b {
+
+        if !a.iter().any(|(other, _)| other == name) {
+
+            pending.push(PendingOp::Add(path.with_key(name), y));
+        }
+    }
+}
+
+fn diff_values<'a>(a: &'a Key, b: &'a Key, path: &Path, options: &DiffOptions, pending: &mut Vec<PendingOp<'a>>) {
+
+    if a.get_value_type() != b.get_value_type() {
+
+        pending.push(PendingOp::Replace(path.clone(), b));
+
+        return;
+    }
+
+    match a.get_value_type() {
+
+        ValueType::ObjectType => diff_members(a.iter(), b.iter(), path, options, pending),
+        ValueType::ArrayType => {
+
+            if options.arrays == ArrayDiff::Lcs {
+
+                diff_arrays(a, b, path, options, pending);
+            } else if !values_equal(a, b) {
+
+                pending.push(PendingOp::Replace(path.clone(), b));
+            }
+        },
+        _ => {
+
+            if !values_equal(a, b) {
+
+                pending.push(PendingOp::Replace(path.clone(), b));
+            }
+        },
+    }
+}
+
+/*
+    Element wise array diff.
+    The longest common subsequence tells which elements stay, in between two kept elements there is a run of
+    removed elements and a run of inserted ones. Elements of both runs are paired up and diffed against each other,
+    whatever is left over is removed or added. The index "idx" follows the array as it is being patched.
+ */
+fn diff_arrays<'a>(a: &'a Key, b: &'a Key, path: &Path, options: &DiffOptions, pending: &mut Vec<PendingOp<'a>>) {
+
+    let a: Vec<&Key> = a.iter().collect();
+    let b: Vec<&Key> = b.iter().collect();
+
+    let (n, m) = (a.len(), b.len());
+
+    let mut equal = vec![false; n * m];
+
+    for i in 0..n {
+
+        for j in 0..m {
+
+            equal[i * m + j] = values_equal(a[i], b[j]);
+        }
+    }
+
+    // lcs[i][j] is the length of the longest common subsequence of a[i..] and b[j..]
+    let mut lcs = vec![0usize; (n + 1) * (m + 1)];
+
+    for i in (0..n).rev() {
+
+        for j in (0..m).rev() {
+
+            lcs[i * (m + 1) + j] = if equal[i * m + j] {
+
+                lcs[(i + 1) * (m + 1) + j + 1] + 1
+            } else {
+
+                lcs[(i + 1) * (m + 1) + j].max(lcs[i * (m + 1) + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j, mut idx) = (0, 0, 0);
+    let mut removed: Vec<usize> = Vec::new();
+    let mut inserted: Vec<usize> = Vec::new();
+
+    loop {
+
+        let keep = i < n && j < m && equal[i * m + j];
+        let done = i == n && j == m;
+
+        if keep || done {
+
+            // Flush the runs collected since the last kept element
+            let paired = removed.len().min(inserted.len());
+
+            for k in 0..paired {
+
+                diff_values(a[removed[k]], b[inserted[k]], &path.with_index(idx), options, pending);
+                idx += 1;
+            }
+
+            for &r in &removed[paired..] {
+
+                pending.push(PendingOp::Remove(path.with_index(idx), a[r]));
+            }
+
+            for &s in &inserted[paired..] {
+
+                pending.push(PendingOp::Add(path.with_index(idx), b[s]));
+                idx += 1;
+            }
+
+            removed.clear();
+            inserted.clear();
+
+            if done {
+
+                break;
+            }
+
+            i += 1;
+            j += 1;
+            idx += 1;
+        } else if j == m || (i < n && lcs[(i + 1) * (m + 1) + j] >= lcs[i * (m + 1) + j + 1]) {
+
+            removed.push(i);
+            i += 1;
+        } else {
+
+            inserted.push(j);
+            j += 1;
+        }
+    }
+}
+
+/*
+    A member removed in one place and an identical member added in another place become a single "move".
+    Only paths made of member names are considered, array indices shift as the patch is applied and the
+    position of the move in the list could invalidate them. Member paths stay valid: neither the source nor
+    the parent of the destination is touched by any other operation.
+ */
+fn detect_moves(pending: Vec<PendingOp>) -> Vec<PendingOp> {
+
+    let mut pending: Vec<Option<PendingOp>> = pending.into_iter().map(Some).collect();
+
+    for r in 0..pending.len() {
+
+        let (from, removed) = match &pending[r] {
+
+            Some(PendingOp::Remove(from, removed)) if !from.has_index() => (from.clone(), *removed),
+            _ => continue,
+        };
+
+        let found = pending.iter().position(|op| match op {
+
+            Some(PendingOp::Add(to, added)) => !to.has_index() && !from.is_prefix_of(to) && !to.is_prefix_of(&from) && values_equal(removed, added),
+            _ => false,
+        });
+
+        if let Some(a) = found {
+
+            if let Some(PendingOp::Add(to, _)) = pending[a].take() {
+
+                pending[a] = Some(PendingOp::Move(from, to));
+                pending[r] = None;
+            }
+        }
+    }
+
+    pending.into_iter().flatten().collect()
+}
+
+fn tokens(pointer: &str) -> Result<Vec<String>, PatchError> {
+
+    parse_pointer(pointer).map_err(|_| PatchError::InvalidPointer(pointer.to_string()))
+}
+
+fn apply_op(doc: &mut JsonObject, op: &PatchOp) -> Result<(), PatchError> {
+
+    match op {
+
+        PatchOp::Add { path, value } => add(doc, path, value.clone_detached()),
+        PatchOp::Remove { path } => remove(doc, path).map(|_| ()),
+        PatchOp::Replace { path, value } => replace(doc, path, value.clone_detached()),
+        PatchOp::Move { from, path } => {
+
+            let (from_tokens, path_tokens) = (tokens(from)?, tokens(path)?);
+
+            if from_tokens == path_tokens {
+
+                // Still has to exist
+                return find(doc, &from_tokens, from).map(|_| ());
+            }
+
+            if path_tokens.starts_with(&from_tokens) {
+
+                return Err(PatchError::MoveIntoDescendant(path.clone()));
+            }
+
+            let value = remove(doc, from)?;
+
+            add(doc, path, value)
+        },
+        PatchOp::Copy { from, path } => {
+
+            let value = match find(doc, &tokens(from)?, from)? {
+
+                Some(key) => key.clone_detached(),
+                None => {
+
+                    let mut key = Key::new(String::new(), ValueType::ObjectType, doc.to_json_string());
+                    key.set_keys(doc.iter().map(|member| member.clone_detached()).collect());
+                    key
+                },
+            };
+
+            add(doc, path, value)
+        },
+        PatchOp::Test { path, value } => {
+
+            let equal = match find(doc, &tokens(path)?, path)? {
+
+                Some(key) => values_equal(key, value),
+                None => {
+
+                    let mut expected = JsonObject::new();
+                    expected.set_keys(value.iter().map(|member| member.clone_detached()).collect());
+
+                    *value.get_value_type() == ValueType::ObjectType && compare::equal(doc, &expected, &EQUALITY)
+                },
+            };
+
+            if equal { Ok(()) } else { Err(PatchError::TestFailed(path.clone())) }
+        },
+    }
+}
+
+// Read only lookup, Ok(None) stands for the root object which is not a Key
+fn find<'a>(doc: &'a JsonObject, tokens: &[String], pointer: &str) -> Result<Option<&'a Key>, PatchError> {
+
+    let mut current: Option<&Key> = None;
+
+    for token in tokens {
+
+        let (children, is_array) = match current {
+
+            None => (doc.iter(), false),
+            Some(key) => match key.get_value_type() {
+
+                ValueType::ObjectType => (key.iter(), false),
+                ValueType::ArrayType => (key.iter(), true),
+                _ => return Err(PatchError::PathNotFound(pointer.to_string())),
+            },
+        };
+
+        let children: Vec<&Key> = children.collect();
+
+        current = Some(children[locate(&children, is_array, token, pointer)?]);
+    }
+
+    Ok(current)
+}
+
+fn locate<K: std::borrow::Borrow<Key>>(keys: &[K], is_array: bool, token: &str, pointer: &str) -> Result<usize, PatchError> {
+
+    if is_array {
+
+        match parse_index(token) {
+
+            Some(index) if index < keys.len() => Ok(index),
+            _ => Err(PatchError::InvalidIndex(pointer.to_string())),
+        }
+    } else {
+
+        keys.iter().position(|key| unescape_lossy(key.borrow().get_name()) == token).ok_or_else(|| PatchError::PathNotFound(pointer.to_string()))
+    }
+}
+
+/*
+    Walks down to the object or array holding the last token of the pointer and hands its children to "f".
+    The children of every container on the way are detached with take_keys() and linked back with set_keys(),
+    which keeps the counts and the next pointers right whatever "f" does to them.
+ */
+fn with_parent<R, F>(doc: &mut JsonObject, tokens: &[String], pointer: &str, f: F) -> Result<R, PatchError>
+where
+    F: FnOnce(&mut Vec<Key>, bool, &str) -> Result<R, PatchError>,
+{
+    let mut keys = doc.take_keys();
+
+    let ret = descend(&mut keys, false, tokens, pointer, f);
+
+    doc.set_keys(keys);
+
+    ret
+}
+
+fn descend<R, F>(keys: &mut Vec<Key>, is_array: bool, tokens: &[String], pointer: &str, f: F) -> Result<R, PatchError>
+where
+    F: FnOnce(&mut Vec<Key>, bool, &str) -> Result<R, PatchError>,
+{
+    if tokens.len() == 1 {
+
+        return f(keys, is_array, &tokens[0]);
+    }
+
+    let index = locate(keys, is_array, &tokens[0], pointer)?;
+    let child = &mut keys[index];
+
+    let child_is_array = match child.get_value_type() {
+
+        ValueType::ObjectType => false,
+        ValueType::ArrayType => true,
+        _ => return Err(PatchError::PathNotFound(pointer.to_string())),
+    };
+
+    let mut children = child.take_keys();
+
+    let ret = descend(&mut children, child_is_array, &tokens[1..], pointer, f);
+
+    child.set_keys(children);
+
+    if ret.is_ok() {
+
+        // Keep the raw text of the container in step with its new content
+        child.set_value(child.to_json_string());
+    }
+
+    ret
+}
+
+fn add(doc: &mut JsonObject, pointer: &str, mut value: Key) -> Result<(), PatchError> {
+
+    let tokens = tokens(pointer)?;
+
+    if tokens.is_empty() {
+
+        if *value.get_value_type() != ValueType::ObjectType {
+
+            return Err(PatchError::InvalidRoot(pointer.to_string()));
+        }
+
+        doc.set_keys(value.take_keys());
+
+        return Ok(());
+    }
+
+    with_parent(doc, &tokens, pointer, |keys, is_array, token| {
+
+        if is_array {
+
+            value.set_name(String::new());
+
+            let index = if token == "-" { Some(keys.len()) } else { parse_index(token) };
+
+            match index {
+
+                Some(index) if index <= keys.len() => keys.insert(index, value),
+                _ => return Err(PatchError::InvalidIndex(pointer.to_string())),
+            }
+        } else {
+
+            value.set_name(escape(token));
+
+            // Adding an existing member replaces its value
+            match keys.iter().position(|key| unescape_lossy(key.get_name()) == token) {
+
+                Some(index) => keys[index] = value,
+                None => keys.push(value),
+            }
+        }
+
+        Ok(())
+    })
+}
+
+fn remove(doc: &mut JsonObject, pointer: &str) -> Result<Key, PatchError> {
+
+    let tokens = tokens(pointer)?;
+
+    if tokens.is_empty() {
+
+        return Err(PatchError::InvalidRoot(pointer.to_string()));
+    }
+
+    with_parent(doc, &tokens, pointer, |keys, is_array, token| {
+
+        let index = locate(keys, is_array, token, pointer)?;
+
+        Ok(keys.remove(index))
+    })
+}
+
+fn replace(doc: &mut JsonObject, pointer: &str, mut value: Key) -> Result<(), PatchError> {
+
+    let tokens = tokens(pointer)?;
+
+    if tokens.is_empty() {
+
+        return add(doc, pointer, value);
+    }
+
+    with_parent(doc, &tokens, pointer, |keys, is_array, token| {
+
+        let index = locate(keys, is_array, token, pointer)?;
+
+        value.set_name(keys[index].get_name().to_string());
+        keys[index] = value;
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::{parse_str, ParserOptions};
+
+    fn parse(text: &str) -> JsonObject {
+        *parse_str(text, &ParserOptions::default()).unwrap().unwrap()
+    }
+
+    fn apply(target: &str, patch: &str) -> Result<String, PatchError> {
+        let mut jobj = parse(target);
+
+        apply_patch_document(&mut jobj, &parse(patch)).map(|_| jobj.to_json_string())
+    }
+
+    // RFC 6902 Appendix A, members are added at the end of their object
+    #[test]
+    fn rfc6902_appendix_a() {
+        let cases = [
+            (r#"{"foo": "bar"}"#, r#"[{"op": "add", "path": "/baz", "value": "qux"}]"#, r#"{"foo":"bar","baz":"qux"}"#),
+            (r#"{"foo": ["bar", "baz"]}"#, r#"[{"op": "add", "path": "/foo/1", "value": "qux"}]"#, r#"{"foo":["bar","qux","baz"]}"#),
+            (r#"{"baz": "qux", "foo": "bar"}"#, r#"[{"op": "remove", "path": "/baz"}]"#, r#"{"foo":"bar"}"#),
+            (r#"{"foo": ["bar", "qux", "baz"]}"#, r#"[{"op": "remove", "path": "/foo/1"}]"#, r#"{"foo":["bar","baz"]}"#),
+            (r#"{"baz": "qux", "foo": "bar"}"#, r#"[{"op": "replace", "path": "/baz", "value": "boo"}]"#, r#"{"baz":"boo","foo":"bar"}"#),
+            (
+                r#"{"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}"#,
+                r#"[{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]"#,
+                r#"{"foo":{"bar":"baz"},"qux":{"corge":"grault","thud":"fred"}}"#,
+            ),
+            (r#"{"foo": ["all", "grass", "cows", "eat"]}"#, r#"[{"op": "move", "from": "/foo/1", "path": "/foo/3"}]"#, r#"{"foo":["all","cows","eat","grass"]}"#),
+            (
+                r#"{"baz": "qux", "foo": ["a", 2, "c"]}"#,
+                r#"[{"op": "test", "path": "/baz", "value": "qux"}, {"op": "test", "path": "/foo/1", "value": 2}]"#,
+                r#"{"baz":"qux","foo":["a",2,"c"]}"#,
+            ),
+            (r#"{"foo": "bar"}"#, r#"[{"op": "add", "path": "/child", "value": {"grandchild": {}}}]"#, r#"{"foo":"bar","child":{"grandchild":{}}}"#),
+            (r#"{"foo": "bar"}"#, r#"[{"op": "add", "path": "/baz", "value": "qux", "xyz": 123}]"#, r#"{"foo":"bar","baz":"qux"}"#),
+            (r#"{"/": 9, "~1": 10}"#, r#"[{"op": "test", "path": "/~01", "value": 10}]"#, r#"{"/":9,"~1":10}"#),
+            (r#"{"foo": ["bar"]}"#, r#"[{"op": "add", "path": "/foo/-", "value": ["abc", "def"]}]"#, r#"{"foo":["bar",["abc","def"]]}"#),
+        ];
+
+        for (target, patch, result) in cases {
+            assert_eq!(apply(target, patch).unwrap(), result, "{} with {}", target, patch);
+        }
+    }
+
+    #[test]
+    fn rfc6902_appendix_a_errors() {
+        assert_eq!(apply(r#"{"baz": "qux"}"#, r#"[{"op": "test", "path": "/baz", "value": "bar"}]"#), Err(PatchError::TestFailed("/baz".to_string())));
+        assert_eq!(apply(r#"{"foo": "bar"}"#, r#"[{"op": "add", "path": "/baz/bat", "value": "qux"}]"#), Err(PatchError::PathNotFound("/baz/bat".to_string())));
+        assert_eq!(apply(r#"{"/": 9, "~1": 10}"#, r#"[{"op": "test", "path": "/~01", "value": "10"}]"#), Err(PatchError::TestFailed("/~01".to_string())));
+    }
+
+    #[test]
+    fn failed_patch_leaves_the_tree_alone() {
+        let mut jobj = parse(r#"{"a": 1}"#);
+        let patch = parse(r#"[{"op": "remove", "path": "/a"}, {"op": "remove", "path": "/b"}]"#);
+
+        assert!(apply_patch_document(&mut jobj, &patch).is_err());
+        assert_eq!(jobj.to_json_string(), r#"{"a":1}"#);
+    }
+
+    #[test]
+    fn diff_round_trip() {
+        let a = parse(r#"{"name": "png", "tags": ["a", "b", "c"], "size": {"w": 1, "h": 2}, "old": true}"#);
+        let b = parse(r#"{"name": "png", "tags": ["a", "c", "d"], "size": {"w": 1.0, "h": 3}, "new": null}"#);
+
+        for arrays in [ArrayDiff::Lcs, ArrayDiff::Replace] {
+            for detect_moves in [true, false] {
+                let ops = diff_with_options(&a, &b, &DiffOptions { arrays: arrays.clone(), detect_moves });
+
+                let mut patched = a.clone();
+                apply_patch(&mut patched, &ops).unwrap();
+
+                assert_eq!(patched.to_json_string(), r#"{"name":"png","tags":["a","c","d"],"size":{"w":1,"h":3},"new":null}"#);
+            }
+        }
+
+        assert!(diff(&a, &a).is_empty());
+    }
+
+    #[test]
+    fn diff_operations() {
+        let a = parse(r#"{"a": {"x": [1, 2, 3]}, "b": 1}"#);
+        let b = parse(r#"{"a": {"x": [1, 3]}, "c": 1}"#);
+
+        assert_eq!(patch_to_string(&diff(&a, &b)), r#"[{"op":"remove","path":"/a/x/1"},{"op":"move","from":"/b","path":"/c"}]"#);
+
+        let ops = diff_with_options(&a, &b, &DiffOptions { arrays: ArrayDiff::Replace, detect_moves: false });
+        assert_eq!(patch_to_string(&ops), r#"[{"op":"replace","path":"/a/x","value":[1,3]},{"op":"remove","path":"/b"},{"op":"add","path":"/c","value":1}]"#);
+    }
+
+    #[test]
+    fn large_integers_are_not_rounded() {
+        let a = parse(r#"{"id": 12345678901234567890}"#);
+        let b = parse(r#"{"id": 12345678901234567891}"#);
+
+        assert_eq!(patch_to_string(&diff(&a, &b)), r#"[{"op":"replace","path":"/id","value":12345678901234567891}]"#);
+        assert_eq!(apply(r#"{"id": 12345678901234567890}"#, r#"[{"op": "test", "path": "/id", "value": 12345678901234567891}]"#), Err(PatchError::TestFailed("/id".to_string())));
+        assert!(apply(r#"{"id": 1.0}"#, r#"[{"op": "test", "path": "/id", "value": 1}]"#).is_ok());
+    }
+
+    #[test]
+    fn malformed_operations() {
+        let doc = parse(r#"[{"op": "add", "path": "/a", "value": 1}, {"op": "jump", "path": "/a"}]"#);
+
+        assert_eq!(parse_patch(&doc).unwrap_err(), PatchError::MalformedOperation(1, "unknown operation \"jump\"".to_string()));
+    }
+}
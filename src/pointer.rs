@@ -0,0 +1,215 @@
+/*
+    src/pointer.rs
+    This file is part of the JSON-rust crate.
+    Written by, Q@khaa.pk
+ */
+
+/*
+    Locations inside a parsed JSON tree.
+    A location is kept as a typed Path (a list of member names and array indices) and can be written out as,
+    or read back from, an RFC 6901 JSON Pointer string such as "/chunks/0/data/0/width".
+ */
+
+use std::fmt;
+
+/// One step of a path, either the name of an object member or the index of an array element.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    Key(String),  // Member name, decoded (escape sequences of the JSON string resolved)
+    Index(usize), // Zero based position of an array element
+}
+
+/// Location of a value inside a JSON tree, from the root object down to the value.
+/// The empty path designates the root object itself.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Path {
+    segments: Vec<PathSegment>,
+}
+
+/// Errors that can occur while reading a JSON Pointer string.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PointerError {
+    MissingLeadingSlash(String), // A non empty pointer has to start with '/'
+    InvalidEscape(String),       // '~' not followed by '0' or '1'
+}
+
+impl Path {
+    /// Creates the empty path, the one designating the root object.
+    pub fn new() -> Self {
+        Path { segments: Vec::new() }
+    }
+
+    /// Returns the segments of the path, from the root down.
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    /// Returns the number of segments.
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Returns true for the path of the root object.
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Appends a segment at the end of the path.
+    ///
+    /// # Arguments
+    /// * `segment` - The segment to append
+    pub fn push(&mut self, segment: PathSegment) {
+        self.segments.push(segment);
+    }
+
+    /// Removes and returns the last segment of the path.
+    pub fn pop(&mut self) -> Option<PathSegment> {
+        self.segments.pop()
+    }
+
+    /// Returns the last segment of the path, if any.
+    pub fn last(&self) -> Option<&PathSegment> {
+        self.segments.last()
+    }
+
+    /// Returns a new path extended with an object member name.
+    ///
+    /// # Arguments
+    /// * `name` - The decoded member name
+    pub fn with_key(&self, name: &str) -> Path {
+        let mut ret = self.clone();
+        ret.push(PathSegment::Key(name.to_string()));
+        ret
+    }
+
+    /// Returns a new path extended with an array index.
+    ///
+    /// # Arguments
+    /// * `index` - The position of the element
+    pub fn with_index(&self, index: usize) -> Path {
+        let mut ret = self.clone();
+        ret.push(PathSegment::Index(index));
+        ret
+    }
+
+    /// Returns the path of the parent, or None for the root.
+    pub fn parent(&self) -> Option<Path> {
+        if self.segments.is_empty() {
+            return None;
+        }
+
+        Some(Path { segments: self.segments[..self.segments.len() - 1].to_vec() })
+    }
+
+    /// Returns true if `self` is `other` or one of its ancestors.
+    ///
+    /// # Arguments
+    /// * `other` - The path to test against
+    pub fn is_prefix_of(&self, other: &Path) -> bool {
+        other.segments.starts_with(&self.segments)
+    }
+
+    /// Returns true if any segment of the path is an array index.
+    pub fn has_index(&self) -> bool {
+        self.segments.iter().any(|segment| matches!(segment, PathSegment::Index(_)))
+    }
+
+    /// Writes the path as an RFC 6901 JSON Pointer, e.g. "/chunks/0/type".
+    pub fn to_pointer(&self) -> String {
+
+        let mut ret = String::new();
+
+        for segment in &self.segments {
+
+            ret.push('/');
+
+            match segment {
+
+                PathSegment::Key(name) => ret.push_str(&escape_token(name)),
+                PathSegment::Index(index) => ret.push_str(&index.to_string()),
+            }
+        }
+
+        ret
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_pointer())
+    }
+}
+
+/// Escapes a member name for use as a JSON Pointer reference token ('~' becomes "~0", '/' becomes "~1").
+///
+/// # Arguments
+/// * `name` - The decoded member name
+pub fn escape_token(name: &str) -> String {
+    name.replace('~', "~0").replace('/', "~1")
+}
+
+/// Splits a JSON Pointer into its decoded reference tokens.
+/// Whether a token names a member or indexes an array depends on the document, which is why the
+/// tokens are returned as strings and not as `PathSegment`s.
+///
+/// # Arguments
+/// * `pointer` - The JSON Pointer, "" for the whole document
+///
+/// # Returns
+/// * `Ok(Vec<String>)` holding the tokens, empty for the whole document
+/// * `Err(PointerError)` if the pointer is malformed
+pub fn parse_pointer(pointer: &str) -> Result<Vec<String>, PointerError> {
+
+    if pointer.is_empty() {
+
+        return Ok(Vec::new());
+    }
+
+    if !pointer.starts_with('/') {
+
+        return Err(PointerError::MissingLeadingSlash(pointer.to_string()));
+    }
+
+    let mut ret = Vec::new();
+
+    for token in pointer[1..].split('/') {
+
+        let mut decoded = String::with_capacity(token.len());
+        let mut chars = token.chars();
+
+        while let Some(ch) = chars.next() {
+
+            if ch != '~' {
+
+                decoded.push(ch);
+
+                continue;
+            }
+
+            match chars.next() {
+
+                Some('0') => decoded.push('~'),
+                Some('1') => decoded.push('/'),
+                _ => return Err(PointerError::InvalidEscape(pointer.to_string())),
+            }
+        }
+
+        ret.push(decoded);
+    }
+
+    Ok(ret)
+}
+
+/// Reads an array index token. RFC 6901 does not allow leading zeros, signs or anything but digits.
+///
+/// # Arguments
+/// * `token` - A decoded reference token
+pub fn parse_index(token: &str) -> Option<usize> {
+
+    if token.is_empty() || !token.bytes().all(|b| b.is_ascii_digit()) || (token.len() > 1 && token.starts_with('0')) {
+
+        return None;
+    }
+
+    token.parse().ok()
+}
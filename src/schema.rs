@@ -0,0 +1,895 @@
+/*
+    src/schema.rs
+    This file is part of the JSON-rust crate.
+    Written by, Q@khaa.pk
+ */
+
+/*
+    JSON Schema (draft 2020-12) support.
+    infer() bootstraps a schema from sample documents: every value met at the same location in any of the
+    samples adds to what is known about that location, i.e. its types, the members of its objects and how
+    often they show up, the elements of its arrays and the strings it holds.
+    Validator checks documents against a schema, both being trees as returned by json::parser.
+ */
+
+use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::Entry;
+use std::fmt;
+use regex::Regex;
+use crate::compare::{self, EqualityOptions};
+use crate::escape::{escape, unescape_lossy};
+use crate::json_object::{ValueType, Key, JsonObject, Iter};
+use crate::pointer::{Path, PathSegment, parse_pointer, parse_index};
+
+/// The dialect the inferred schemas declare in "$schema".
+pub const SCHEMA_DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Strings at a location become an "enum" when they take at most this many distinct values...
+pub const ENUM_MAX_VALUES: usize = 8;
+
+/// ...and each distinct value was seen, on average, at least this many times.
+pub const ENUM_MIN_OCCURRENCES: usize = 2;
+
+// What is known about the values met at one location
+#[derive(Default)]
+struct Shape {
+    nulls: usize,
+    booleans: usize,
+    integers: usize,
+    numbers: usize,                        // Numbers with a fractional part
+    strings: usize,
+    distinct_strings: Option<Vec<String>>, // Distinct values, in the order they were met, None once there are too many
+    objects: usize,
+    members: Vec<(String, usize, Shape)>,  // Decoded name, in how many of the objects it is present, and its values
+    arrays: usize,
+    items: Option<Box<Shape>>,             // The elements of every array, None until an element is met
+}
+
+impl Shape {
+    fn add(&mut self, key: &Key) {
+
+        match key.get_value_type() {
+
+            ValueType::NullType => self.nulls += 1,
+            ValueType::BooleanType => self.booleans += 1,
+            ValueType::NumberType => {
+
+                if is_integer(key.get_value()) { self.integers += 1 } else { self.numbers += 1 }
+            },
+            ValueType::StringType => {
+
+                if self.strings == 0 {
+
+                    self.distinct_strings = Some(Vec::new());
+                }
+
+                self.strings += 1;
+
+                let value = unescape_lossy(key.get_value());
+
+                if let Some(distinct) = &mut self.distinct_strings {
+
+                    if !distinct.contains(&value) {
+
+                        distinct.push(value);
+                    }
+
+                    if distinct.len() > ENUM_MAX_VALUES {
+
+                        self.distinct_strings = None;
+                    }
+                }
+            },
+            ValueType::ObjectType => self.add_object(key.iter()),
+            ValueType::ArrayType => {
+
+                self.arrays += 1;
+
+                for element in key.iter() {
+
+                    self.items.get_or_insert_with(Box::default).add(element);
+                }
+            },
+        }
+    }
+
+    fn add_object(&mut self, members: Iter<'_>) {
+
+        self.objects += 1;
+
+        let mut seen: Vec<String> = Vec::new();
+
+        for member in members {
+
+            let name = unescape_lossy(member.get_name());
+
+            let index = match self.members.iter().position(|(other, _, _)| *other == name) {
+
+                Some(index) => index,
+                None => {
+
+                    self.members.push((name.clone(), 0, Shape::default()));
+                    self.members.len() - 1
+                },
+            };
+
+            // A repeated name counts once towards presence, all of its values count
+            if !seen.contains(&name) {
+
+                self.members[index].1 += 1;
+                seen.push(name);
+            }
+
+            self.members[index].2.add(member);
+        }
+    }
+
+    // The schema of the values, as the members of a schema object
+    fn to_schema(&self) -> Vec<Key> {
+
+        let mut ret = Vec::new();
+        let mut types = Vec::new();
+
+        if self.objects > 0 { types.push("object") }
+        if self.arrays > 0 { types.push("array") }
+        if self.strings > 0 { types.push("string") }
+        if self.numbers > 0 { types.push("number") } else if self.integers > 0 { types.push("integer") }
+        if self.booleans > 0 { types.push("boolean") }
+        if self.nulls > 0 { types.push("null") }
+
+        match types.as_slice() {
+
+            // Never seen a value, e.g. the items of arrays that were all empty, anything goes
+            [] => return ret,
+            [single] => ret.push(string_key("type", single)),
+            _ => ret.push(array_key("type", types.iter().map(|t| string_key("", t)).collect())),
+        }
+
+        // Strings that look like a closed set of values, the only non null type
+        let only_strings = self.strings > 0 && self.strings + self.nulls == self.total();
+
+        if let (true, Some(distinct)) = (only_strings, &self.distinct_strings) {
+
+            if self.strings >= distinct.len() * ENUM_MIN_OCCURRENCES {
+
+                let mut values: Vec<Key> = distinct.iter().map(|value| string_key("", value)).collect();
+
+                if self.nulls > 0 {
+
+                    values.push(Key::new(String::new(), ValueType::NullType, "null".to_string()));
+                }
+
+                ret.push(array_key("enum", values));
+            }
+        }
+
+        if self.objects > 0 {
+
+            let properties = self.members.iter().map(|(name, _, shape)| object_key(name, shape.to_schema())).collect();
+            let required: Vec<Key> = self.members.iter().filter(|(_, present, _)| *present == self.objects).map(|(name, _, _)| string_key("", name)).collect();
+
+            ret.push(object_key("properties", properties));
+
+            if !required.is_empty() {
+
+                ret.push(array_key("required", required));
+            }
+        }
+
+        if let Some(items) = &self.items {
+
+            ret.push(object_key("items", items.to_schema()));
+        }
+
+        ret
+    }
+
+    fn total(&self) -> usize {
+
+        self.nulls + self.booleans + self.integers + self.numbers + self.strings + self.objects + self.arrays
+    }
+}
+
+/// Infers a JSON Schema (draft 2020-12) that every one of the samples is valid against.
+///
+/// Each location gets the types met there ("integer" when every number is whole), with "null" among
+/// them for nullable fields. Objects list their "properties", and as "required" the members present in
+/// every object met at that location. Arrays get one "items" schema for all of their elements. Strings
+/// taking a few distinct, repeated values become an "enum" (see `ENUM_MAX_VALUES`).
+///
+/// # Arguments
+/// * `samples` - Parsed documents, the more the better
+///
+/// # Returns
+/// The schema, as a tree with "$schema", "type": "object", "properties" and "required" at the top
+pub fn infer(samples: &[&JsonObject]) -> JsonObject {
+
+    let mut shape = Shape::default();
+
+    for sample in samples {
+
+        shape.add_object(sample.iter());
+    }
+
+    let mut keys = vec![string_key("$schema", SCHEMA_DRAFT)];
+    keys.extend(shape.to_schema());
+
+    let mut ret = JsonObject::new();
+    ret.set_keys(keys);
+
+    ret
+}
+
+fn string_key(name: &str, value: &str) -> Key {
+
+    Key::new(escape(name), ValueType::StringType, escape(value))
+}
+
+fn object_key(name: &str, members: Vec<Key>) -> Key {
+
+    container_key(name, ValueType::ObjectType, members)
+}
+
+fn array_key(name: &str, elements: Vec<Key>) -> Key {
+
+    container_key(name, ValueType::ArrayType, elements)
+}
+
+fn container_key(name: &str, value_type: ValueType, children: Vec<Key>) -> Key {
+
+    let mut key = Key::new(escape(name), value_type, String::new());
+    key.set_keys(children);
+    key.set_value(key.to_json_string());
+
+    key
+}
+
+/*
+    Validation.
+    Supported keywords: type, enum, const, properties, required, minProperties, maxProperties, items,
+    prefixItems, minItems, maxItems, minLength, maxLength, pattern, minimum, maximum, exclusiveMinimum,
+    exclusiveMaximum, allOf, anyOf, oneOf, and $ref to a JSON Pointer into the same schema ("#/$defs/name").
+    Other keywords are ignored. Boolean schemas (true, false) are supported.
+ */
+
+// How many $ref can be followed in a row without moving to a child of the instance, more is taken as a cycle
+const MAX_REF_CHAIN: usize = 32;
+
+/// Reasons a schema can not be used for validation, the String is the JSON Pointer of the offending keyword in the schema.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaError {
+    InvalidSchema(String),          // A subschema is neither an object nor a boolean
+    InvalidKeyword(String),         // The value of a supported keyword has the wrong type, e.g. "required": "name"
+    InvalidPattern(String, String), // The pattern is not a valid regular expression, the message of the regex crate
+    UnresolvedRef(String, String),  // The $ref, given, is not a local reference or points nowhere
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::InvalidSchema(at) => write!(f, "{}: a schema must be an object or a boolean", at),
+            SchemaError::InvalidKeyword(at) => write!(f, "{}: the keyword has a value of the wrong type", at),
+            SchemaError::InvalidPattern(at, message) => write!(f, "{}: invalid pattern, {}", at, message),
+            SchemaError::UnresolvedRef(at, reference) => write!(f, "{}: can not resolve \"{}\"", at, reference),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// What an instance failed to satisfy, limits are given as they are written in the schema.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationErrorKind {
+    FalseSchema,                // The schema is false, nothing is valid
+    Type(Vec<String>),          // Not one of the allowed types, which are given
+    Enum,                       // Not one of the values of "enum"
+    Const,                      // Not the value of "const"
+    Required(String),           // The member, given, is missing
+    MinProperties(usize),       // Fewer members than the limit
+    MaxProperties(usize),       // More members than the limit
+    MinItems(usize),            // Fewer elements than the limit
+    MaxItems(usize),            // More elements than the limit
+    MinLength(usize),           // Fewer characters than the limit
+    MaxLength(usize),           // More characters than the limit
+    Pattern(String),            // The string does not match the pattern, which is given
+    Minimum(String),            // Below the limit
+    Maximum(String),            // Above the limit
+    ExclusiveMinimum(String),   // Not above the limit
+    ExclusiveMaximum(String),   // Not below the limit
+    AnyOf,                      // Valid against none of the subschemas
+    OneOf(usize),               // Valid against none, or more than one (how many are given), of the subschemas
+    RefCycle(String),           // The $ref, given, leads back to itself without getting any further in the instance
+    UncompiledPattern(String),  // The pattern, given, is in a place Validator::new() did not look, it cannot be checked
+}
+
+/// A failed validation, with where it happened in the instance and in the schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    pub instance_path: Path, // The value that is not valid
+    pub schema_path: Path,   // The keyword it does not satisfy, after resolving any $ref
+    pub kind: ValidationErrorKind,
+}
+
+impl ValidationError {
+    /// Returns the location of the invalid value as a JSON Pointer.
+    pub fn instance_pointer(&self) -> String {
+        self.instance_path.to_pointer()
+    }
+
+    /// Returns the location of the failed keyword in the schema as a JSON Pointer.
+    pub fn schema_pointer(&self) -> String {
+        self.schema_path.to_pointer()
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.instance_pointer())?;
+
+        match &self.kind {
+            ValidationErrorKind::FalseSchema => write!(f, "no value is allowed here"),
+            ValidationErrorKind::Type(types) => write!(f, "expected {}", types.join(" or ")),
+            ValidationErrorKind::Enum => write!(f, "not one of the allowed values"),
+            ValidationErrorKind::Const => write!(f, "not the expected value"),
+            ValidationErrorKind::Required(name) => write!(f, "missing required member \"{}\"", name),
+            ValidationErrorKind::MinProperties(limit) => write!(f, "fewer than {} members", limit),
+            ValidationErrorKind::MaxProperties(limit) => write!(f, "more than {} members", limit),
+            ValidationErrorKind::MinItems(limit) => write!(f, "fewer than {} elements", limit),
+            ValidationErrorKind::MaxItems(limit) => write!(f, "more than {} elements", limit),
+            ValidationErrorKind::MinLength(limit) => write!(f, "shorter than {} characters", limit),
+            ValidationErrorKind::MaxLength(limit) => write!(f, "longer than {} characters", limit),
+            ValidationErrorKind::Pattern(pattern) => write!(f, "does not match \"{}\"", pattern),
+            ValidationErrorKind::Minimum(limit) => write!(f, "less than {}", limit),
+            ValidationErrorKind::Maximum(limit) => write!(f, "greater than {}", limit),
+            ValidationErrorKind::ExclusiveMinimum(limit) => write!(f, "not greater than {}", limit),
+            ValidationErrorKind::ExclusiveMaximum(limit) => write!(f, "not less than {}", limit),
+            ValidationErrorKind::AnyOf => write!(f, "not valid against any of the \"anyOf\" schemas"),
+            ValidationErrorKind::OneOf(matched) => write!(f, "valid against {} of the \"oneOf\" schemas instead of exactly one", matched),
+            ValidationErrorKind::RefCycle(reference) => write!(f, "\"{}\" refers back to itself", reference),
+            ValidationErrorKind::UncompiledPattern(pattern) => write!(f, "\"{}\" was not compiled", pattern),
+        }?;
+
+        write!(f, " (schema {})", self.schema_pointer())
+    }
+}
+
+// The root of a tree is a JsonObject, everything under it is a Key, either can be a schema or an instance
+#[derive(Clone, Copy)]
+enum Node<'a> {
+    Root(&'a JsonObject),
+    Key(&'a Key),
+}
+
+impl<'a> Node<'a> {
+    fn value_type(&self) -> &'a ValueType {
+        match self {
+            Node::Root(_) => &ValueType::ObjectType,
+            Node::Key(key) => key.get_value_type(),
+        }
+    }
+
+    fn value(&self) -> &'a str {
+        match self {
+            Node::Root(_) => "",
+            Node::Key(key) => key.get_value(),
+        }
+    }
+
+    fn iter(&self) -> Iter<'a> {
+        match self {
+            Node::Root(jobj) => jobj.iter(),
+            Node::Key(key) => key.iter(),
+        }
+    }
+
+    fn get_n(&self) -> usize {
+        match self {
+            Node::Root(jobj) => jobj.get_n(),
+            Node::Key(key) => key.get_n(),
+        }
+    }
+
+    // The first member with the given (decoded) name
+    fn member(&self, name: &str) -> Option<&'a Key> {
+        self.iter().find(|key| unescape_lossy(key.get_name()) == name)
+    }
+
+    fn equals(&self, other: &Key) -> bool {
+        match self {
+            Node::Key(key) => compare::values_equal(key, other, &EqualityOptions::default()),
+            Node::Root(jobj) => {
+                if *other.get_value_type() != ValueType::ObjectType {
+                    return false;
+                }
+
+                let mut members = JsonObject::new();
+                members.set_keys(other.iter().map(Key::clone_detached).collect());
+
+                compare::equal(jobj, &members, &EqualityOptions::default())
+            },
+        }
+    }
+}
+
+/// A schema ready to validate any number of instances, its patterns compiled and its references checked once.
+pub struct Validator<'a> {
+    schema: &'a JsonObject,
+    patterns: HashMap<String, Regex>,
+}
+
+impl<'a> Validator<'a> {
+    /// Creates a new Validator.
+    ///
+    /// # Arguments
+    /// * `schema` - The schema, as parsed by `json::parser`
+    ///
+    /// # Returns
+    /// * `Ok(Validator)`
+    /// * `Err(SchemaError)` if the schema can not be used
+    pub fn new(schema: &'a JsonObject) -> Result<Self, SchemaError> {
+
+        let mut ret = Validator { schema, patterns: HashMap::new() };
+
+        ret.compile(Node::Root(schema), &Path::new(), &mut HashSet::new())?;
+
+        Ok(ret)
+    }
+
+    /// Validates an instance.
+    ///
+    /// # Arguments
+    /// * `instance` - The document to validate, as parsed by `json::parser`
+    ///
+    /// # Returns
+    /// Every error found, in document order of the schema keywords, empty when the instance is valid
+    pub fn validate(&self, instance: &JsonObject) -> Vec<ValidationError> {
+
+        let mut ret = Vec::new();
+
+        self.validate_node(Node::Root(self.schema), &Path::new(), Node::Root(instance), &Path::new(), 0, &mut ret);
+
+        ret
+    }
+
+    /// Tells whether an instance is valid.
+    ///
+    /// # Arguments
+    /// * `instance` - The document to validate
+    pub fn is_valid(&self, instance: &JsonObject) -> bool {
+
+        self.validate(instance).is_empty()
+    }
+
+    // Checks a schema, the schemas it holds and the ones its references lead to, compiling their patterns on the way.
+    // refs holds the references already followed, a schema can refer to itself
+    fn compile(&mut self, schema: Node<'a>, path: &Path, refs: &mut HashSet<String>) -> Result<(), SchemaError> {
+
+        let invalid_keyword = |name: &str| SchemaError::InvalidKeyword(path.with_key(name).to_pointer());
+
+        match schema.value_type() {
+
+            ValueType::BooleanType => return Ok(()),
+            ValueType::ObjectType => {},
+            _ => return Err(SchemaError::InvalidSchema(path.to_pointer())),
+        }
+
+        for keyword in schema.iter() {
+
+            let name = unescape_lossy(keyword.get_name());
+            let value_type = keyword.get_value_type();
+            let at = path.with_key(&name);
+
+            match name.as_str() {
+
+                "properties" | "$defs" | "definitions" | "patternProperties" | "dependentSchemas" => {
+
+                    if *value_type != ValueType::ObjectType {
+
+                        return Err(invalid_keyword(&name));
+                    }
+
+                    for member in keyword.iter() {
+
+                        self.compile(Node::Key(member), &at.with_key(&unescape_lossy(member.get_name())), refs)?;
+                    }
+                },
+                "items" | "additionalItems" | "additionalProperties" | "contains" | "propertyNames" | "not" | "if" | "then" | "else" |
+                "unevaluatedItems" | "unevaluatedProperties" => self.compile(Node::Key(keyword), &at, refs)?,
+                "prefixItems" | "allOf" | "anyOf" | "oneOf" => {
+
+                    if *value_type != ValueType::ArrayType {
+
+                        return Err(invalid_keyword(&name));
+                    }
+
+                    for (index, element) in keyword.iter().enumerate() {
+
+                        self.compile(Node::Key(element), &at.with_index(index), refs)?;
+                    }
+                },
+                "type" => {
+
+                    let types: Vec<&Key> = if *value_type == ValueType::ArrayType { keyword.iter().collect() } else { vec![keyword] };
+
+                    if !types.iter().all(|t| *t.get_value_type() == ValueType::StringType && TYPES.contains(&t.get_value())) {
+
+                        return Err(invalid_keyword(&name));
+                    }
+                },
+                "enum" | "required" if *value_type != ValueType::ArrayType => return Err(invalid_keyword(&name)),
+                "required" if keyword.iter().any(|k| *k.get_value_type() != ValueType::StringType) => return Err(invalid_keyword(&name)),
+                "minProperties" | "maxProperties" | "minItems" | "maxItems" | "minLength" | "maxLength" if count(keyword).is_none() => return Err(invalid_keyword(&name)),
+                "minimum" | "maximum" | "exclusiveMinimum" | "exclusiveMaximum" if number(keyword).is_none() => return Err(invalid_keyword(&name)),
+                "pattern" => {
+
+                    if *value_type != ValueType::StringType {
+
+                        return Err(invalid_keyword(&name));
+                    }
+
+                    let pattern = unescape_lossy(keyword.get_value());
+
+                    if let Entry::Vacant(entry) = self.patterns.entry(pattern) {
+
+                        let regex = Regex::new(entry.key()).map_err(|e| SchemaError::InvalidPattern(at.to_pointer(), e.to_string()))?;
+
+                        entry.insert(regex);
+                    }
+                },
+                "$ref" => {
+
+                    let reference = unescape_lossy(keyword.get_value());
+
+                    let target = if *value_type == ValueType::StringType { self.resolve(&reference) } else { None };
+
+                    let Some((target, target_path)) = target else {
+
+                        return Err(SchemaError::UnresolvedRef(at.to_pointer(), reference));
+                    };
+
+                    // The target can be anywhere, "#/definitions/x" or "#/properties/a/items" alike
+                    if refs.insert(target_path.to_pointer()) {
+
+                        self.compile(target, &target_path, refs)?;
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        Ok(())
+    }
+
+    // Finds the schema a local reference ("#", "#/$defs/name") points to, along with its path
+    fn resolve(&self, reference: &str) -> Option<(Node<'a>, Path)> {
+
+        let fragment = percent_decode(reference.strip_prefix('#')?)?;
+        let mut node = Node::Root(self.schema);
+        let mut path = Path::new();
+
+        for token in parse_pointer(&fragment).ok()? {
+
+            node = match node.value_type() {
+
+                ValueType::ObjectType => {
+
+                    path.push(PathSegment::Key(token.clone()));
+                    Node::Key(node.member(&token)?)
+                },
+                ValueType::ArrayType => {
+
+                    let index = parse_index(&token)?;
+
+                    path.push(PathSegment::Index(index));
+                    Node::Key(node.iter().nth(index)?)
+                },
+                _ => return None,
+            };
+        }
+
+        match node.value_type() {
+
+            ValueType::ObjectType | ValueType::BooleanType => Some((node, path)),
+            _ => None,
+        }
+    }
+
+    fn validate_node(&self, schema: Node<'a>, schema_path: &Path, instance: Node, instance_path: &Path, refs: usize, errors: &mut Vec<ValidationError>) {
+
+        let error = |keyword: &str, kind| ValidationError { instance_path: instance_path.clone(), schema_path: schema_path.with_key(keyword), kind };
+
+        if *schema.value_type() == ValueType::BooleanType {
+
+            if schema.value().trim() == "false" {
+
+                errors.push(ValidationError { instance_path: instance_path.clone(), schema_path: schema_path.clone(), kind: ValidationErrorKind::FalseSchema });
+            }
+
+            return;
+        }
+
+        let instance_type = instance.value_type();
+
+        for keyword in schema.iter() {
+
+            let name = unescape_lossy(keyword.get_name());
+
+            match name.as_str() {
+
+                "type" => {
+
+                    let types: Vec<String> = if *keyword.get_value_type() == ValueType::ArrayType { keyword.iter().map(|t| t.get_value().to_string()).collect() } else { vec![keyword.get_value().to_string()] };
+
+                    if !types.iter().any(|t| has_type(instance, t)) {
+
+                        errors.push(error(&name, ValidationErrorKind::Type(types)));
+                    }
+                },
+                "enum" if !keyword.iter().any(|value| instance.equals(value)) => errors.push(error(&name, ValidationErrorKind::Enum)),
+                "const" if !instance.equals(keyword) => errors.push(error(&name, ValidationErrorKind::Const)),
+                "required" if *instance_type == ValueType::ObjectType => {
+
+                    for member in keyword.iter() {
+
+                        let member = unescape_lossy(member.get_value());
+
+                        if instance.member(&member).is_none() {
+
+                            errors.push(error(&name, ValidationErrorKind::Required(member)));
+                        }
+                    }
+                },
+                "minProperties" | "maxProperties" | "minItems" | "maxItems" | "minLength" | "maxLength" => {
+
+                    let limit = count(keyword).unwrap_or(0);
+
+                    let size = match (name.as_str(), instance_type) {
+
+                        ("minProperties" | "maxProperties", ValueType::ObjectType) | ("minItems" | "maxItems", ValueType::ArrayType) => instance.get_n(),
+                        ("minLength" | "maxLength", ValueType::StringType) => unescape_lossy(instance.value()).chars().count(),
+                        _ => continue,
+                    };
+
+                    if name.starts_with("min") && size < limit {
+
+                        errors.push(error(&name, match name.as_str() { "minProperties" => ValidationErrorKind::MinProperties(limit), "minItems" => ValidationErrorKind::MinItems(limit), _ => ValidationErrorKind::MinLength(limit) }));
+                    } else if name.starts_with("max") && size > limit {
+
+                        errors.push(error(&name, match name.as_str() { "maxProperties" => ValidationErrorKind::MaxProperties(limit), "maxItems" => ValidationErrorKind::MaxItems(limit), _ => ValidationErrorKind::MaxLength(limit) }));
+                    }
+                },
+                "minimum" | "maximum" | "exclusiveMinimum" | "exclusiveMaximum" if *instance_type == ValueType::NumberType => {
+
+                    let (Some(limit), Some(value)) = (number(keyword), instance.value().trim().parse::<f64>().ok()) else { continue };
+                    let text = keyword.get_value().trim().to_string();
+
+                    match name.as_str() {
+
+                        "minimum" if value < limit => errors.push(error(&name, ValidationErrorKind::Minimum(text))),
+                        "maximum" if value > limit => errors.push(error(&name, ValidationErrorKind::Maximum(text))),
+                        "exclusiveMinimum" if value <= limit => errors.push(error(&name, ValidationErrorKind::ExclusiveMinimum(text))),
+                        "exclusiveMaximum" if value >= limit => errors.push(error(&name, ValidationErrorKind::ExclusiveMaximum(text))),
+                        _ => {},
+                    }
+                },
+                "pattern" if *instance_type == ValueType::StringType => {
+
+                    let pattern = unescape_lossy(keyword.get_value());
+
+                    match self.patterns.get(&pattern) {
+
+                        Some(regex) if !regex.is_match(&unescape_lossy(instance.value())) => errors.push(error(&name, ValidationErrorKind::Pattern(pattern))),
+                        Some(_) => {},
+                        None => errors.push(error(&name, ValidationErrorKind::UncompiledPattern(pattern))),
+                    }
+                },
+                "properties" if *instance_type == ValueType::ObjectType => {
+
+                    for member in instance.iter() {
+
+                        let member_name = unescape_lossy(member.get_name());
+
+                        if let Some(subschema) = Node::Key(keyword).member(&member_name) {
+
+                            self.validate_node(Node::Key(subschema), &schema_path.with_key(&name).with_key(&member_name), Node::Key(member), &instance_path.with_key(&member_name), 0, errors);
+                        }
+                    }
+                },
+                "items" if *instance_type == ValueType::ArrayType => {
+
+                    // Elements covered by prefixItems are not for items
+                    let skip = schema.member("prefixItems").map_or(0, |prefix| prefix.get_n());
+
+                    for (index, element) in instance.iter().enumerate().skip(skip) {
+
+                        self.validate_node(Node::Key(keyword), &schema_path.with_key(&name), Node::Key(element), &instance_path.with_index(index), 0, errors);
+                    }
+                },
+                "prefixItems" if *instance_type == ValueType::ArrayType => {
+
+                    for (index, (subschema, element)) in keyword.iter().zip(instance.iter()).enumerate() {
+
+                        self.validate_node(Node::Key(subschema), &schema_path.with_key(&name).with_index(index), Node::Key(element), &instance_path.with_index(index), 0, errors);
+                    }
+                },
+                "allOf" => {
+
+                    for (index, subschema) in keyword.iter().enumerate() {
+
+                        self.validate_node(Node::Key(subschema), &schema_path.with_key(&name).with_index(index), instance, instance_path, refs, errors);
+                    }
+                },
+                "anyOf" | "oneOf" => {
+
+                    let matched = keyword.iter().enumerate().filter(|(index, subschema)| {
+
+                        let mut scratch = Vec::new();
+
+                        self.validate_node(Node::Key(subschema), &schema_path.with_key(&name).with_index(*index), instance, instance_path, refs, &mut scratch);
+
+                        scratch.is_empty()
+                    }).count();
+
+                    if name == "anyOf" && matched == 0 {
+
+                        errors.push(error(&name, ValidationErrorKind::AnyOf));
+                    } else if name == "oneOf" && matched != 1 {
+
+                        errors.push(error(&name, ValidationErrorKind::OneOf(matched)));
+                    }
+                },
+                "$ref" => {
+
+                    let reference = unescape_lossy(keyword.get_value());
+
+                    if refs >= MAX_REF_CHAIN {
+
+                        errors.push(error(&name, ValidationErrorKind::RefCycle(reference)));
+                    } else if let Some((target, target_path)) = self.resolve(&reference) {
+
+                        self.validate_node(target, &target_path, instance, instance_path, refs + 1, errors);
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+}
+
+/// Validates an instance against a schema, see `Validator` to validate many instances against the same schema.
+///
+/// # Arguments
+/// * `schema` - The schema
+/// * `instance` - The document to validate
+///
+/// # Returns
+/// * `Ok(Vec<ValidationError>)` with every error found, empty when the instance is valid
+/// * `Err(SchemaError)` if the schema can not be used
+pub fn validate(schema: &JsonObject, instance: &JsonObject) -> Result<Vec<ValidationError>, SchemaError> {
+
+    Ok(Validator::new(schema)?.validate(instance))
+}
+
+// The type names of JSON Schema
+const TYPES: [&str; 7] = ["null", "boolean", "object", "array", "number", "string", "integer"];
+
+fn has_type(instance: Node, name: &str) -> bool {
+
+    match (name, instance.value_type()) {
+
+        ("null", ValueType::NullType) | ("boolean", ValueType::BooleanType) | ("object", ValueType::ObjectType) |
+        ("array", ValueType::ArrayType) | ("number", ValueType::NumberType) | ("string", ValueType::StringType) => true,
+        ("integer", ValueType::NumberType) => is_integer(instance.value()),
+        _ => false,
+    }
+}
+
+// A number without a fractional part, 3.0 and 1e21 included
+fn is_integer(raw: &str) -> bool {
+
+    raw.trim().parse::<f64>().is_ok_and(|value| value.is_finite() && value.fract() == 0.0)
+}
+
+// The value of a keyword that takes a non negative integer, 3.0 included
+fn count(keyword: &Key) -> Option<usize> {
+
+    let value = number(keyword)?;
+
+    if value >= 0.0 && value.fract() == 0.0 { Some(value as usize) } else { None }
+}
+
+fn number(keyword: &Key) -> Option<f64> {
+
+    if *keyword.get_value_type() != ValueType::NumberType {
+
+        return None;
+    }
+
+    keyword.get_value().trim().parse().ok()
+}
+
+// A URI fragment can hold %XX escapes, e.g. "#/$defs/a%20b"
+fn percent_decode(fragment: &str) -> Option<String> {
+
+    let bytes = fragment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+
+        if bytes[i] == b'%' {
+
+            decoded.push(u8::from_str_radix(fragment.get(i + 1..i + 3)?, 16).ok()?);
+            i += 3;
+        } else {
+
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8(decoded).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::{parse_str, ParserOptions};
+
+    fn parse(text: &str) -> JsonObject {
+        *parse_str(text, &ParserOptions::default()).unwrap().unwrap()
+    }
+
+    #[test]
+    fn pattern_behind_a_ref_is_enforced() {
+        let schema = parse(r##"{"properties": {"code": {"$ref": "#/definitions/code"}}, "definitions": {"code": {"type": "string", "pattern": "^A+$"}}}"##);
+        let validator = Validator::new(&schema).unwrap();
+
+        assert!(validator.is_valid(&parse(r#"{"code": "AAA"}"#)));
+
+        let errors = validator.validate(&parse(r#"{"code": "abc"}"#));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ValidationErrorKind::Pattern("^A+$".to_string()));
+        assert_eq!(errors[0].instance_pointer(), "/code");
+        assert_eq!(errors[0].schema_pointer(), "/definitions/code/pattern");
+    }
+
+    #[test]
+    fn invalid_pattern_behind_a_ref_is_reported() {
+        let schema = parse(r##"{"properties": {"a": {"$ref": "#/definitions/x"}}, "definitions": {"x": {"pattern": "("}}}"##);
+
+        assert!(matches!(Validator::new(&schema), Err(SchemaError::InvalidPattern(pointer, _)) if pointer == "/definitions/x/pattern"));
+    }
+
+    #[test]
+    fn recursive_ref_compiles() {
+        let schema = parse(r##"{"properties": {"name": {"pattern": "^n"}, "child": {"$ref": "#"}}}"##);
+        let validator = Validator::new(&schema).unwrap();
+
+        let errors = validator.validate(&parse(r#"{"child": {"child": {"name": "x"}}}"#));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].instance_pointer(), "/child/child/name");
+    }
+
+    #[test]
+    fn integer_is_a_number_without_fraction() {
+        let schema = parse(r#"{"properties": {"n": {"type": "integer"}}}"#);
+        let validator = Validator::new(&schema).unwrap();
+
+        for valid in ["1", "-7", "3.0", "1e21", "1E+2"] {
+            assert!(validator.is_valid(&parse(&format!(r#"{{"n": {}}}"#, valid))), "{}", valid);
+        }
+        for invalid in ["1.5", "1e-1", "\"1\""] {
+            assert!(!validator.is_valid(&parse(&format!(r#"{{"n": {}}}"#, invalid))), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn inferred_integer() {
+        let schema = infer(&[&parse(r#"{"a": 1e21, "b": 1.5}"#)]);
+        let validator = Validator::new(&schema).unwrap();
+
+        assert!(validator.is_valid(&parse(r#"{"a": 2, "b": 2}"#)));
+        assert!(!validator.is_valid(&parse(r#"{"a": 2.5, "b": 2}"#)));
+    }
+}
@@ -0,0 +1,263 @@
+/*
+    src/search.rs
+    This file is part of the JSON-rust crate.
+    Written by, Q@khaa.pk
+ */
+
+/*
+    Searching a parsed tree for keys by name, by string value, or by any predicate.
+    Names and string values are matched once decoded, so "été" is found by "été".
+    Every match comes with its path, from which the JSON Pointer of the key is one to_pointer() away.
+ */
+
+use regex::{Regex, RegexBuilder};
+use crate::escape::unescape_lossy;
+use crate::helper::{walk_with_paths_and_max_depth, Location, TraverseError, VisitAction};
+use crate::json_object::{ValueType, Key, JsonObject};
+use crate::pointer::Path;
+
+/// How a `Matcher` compares its pattern with a name or a value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MatchMode {
+    Exact,           // The whole text equals the pattern
+    CaseInsensitive, // The whole text equals the pattern, ignoring case
+    Regex,           // The pattern is a regular expression found anywhere in the text, use ^ and $ to match the whole text
+}
+
+/// A pattern along with the way to match it, built once and used for every key of the tree.
+#[derive(Debug, Clone)]
+pub struct Matcher {
+    pattern: String,
+    mode: MatchMode,
+    regex: Option<Regex>, // Compiled pattern, for Regex and CaseInsensitive modes
+}
+
+impl Matcher {
+    /// Creates a new Matcher.
+    ///
+    /// # Arguments
+    /// * `pattern` - The text, or regular expression, to look for
+    /// * `mode` - How to match it
+    ///
+    /// # Returns
+    /// * `Ok(Matcher)`
+    /// * `Err(regex::Error)` if the mode is Regex and the pattern is not a valid regular expression
+    pub fn new(pattern: &str, mode: MatchMode) -> Result<Self, regex::Error> {
+
+        let regex = match mode {
+
+            MatchMode::Exact => None,
+            // Unicode aware case folding, which comparing lowercased strings does not get right for every script
+            MatchMode::CaseInsensitive => Some(RegexBuilder::new(&format!("^(?:{})$", regex::escape(pattern))).case_insensitive(true).build()?),
+            MatchMode::Regex => Some(Regex::new(pattern)?),
+        };
+
+        Ok(Matcher { pattern: pattern.to_string(), mode, regex })
+    }
+
+    /// Creates a Matcher for the exact text.
+    ///
+    /// # Arguments
+    /// * `pattern` - The text to look for
+    pub fn exact(pattern: &str) -> Self {
+
+        Matcher { pattern: pattern.to_string(), mode: MatchMode::Exact, regex: None }
+    }
+
+    /// Returns the pattern the matcher was created with.
+    pub fn get_pattern(&self) -> &str {
+
+        &self.pattern
+    }
+
+    /// Returns the mode the matcher was created with.
+    pub fn get_mode(&self) -> &MatchMode {
+
+        &self.mode
+    }
+
+    /// Tells whether the given (decoded) text matches.
+    ///
+    /// # Arguments
+    /// * `text` - The text to test
+    pub fn is_match(&self, text: &str) -> bool {
+
+        match &self.regex {
+
+            Some(regex) => regex.is_match(text),
+            None => text == self.pattern,
+        }
+    }
+}
+
+/// Finds every key whose name matches. Array elements have no name and are never found.
+///
+/// # Arguments
+/// * `jobj` - The tree to search
+/// * `matcher` - What the name has to match
+///
+/// # Returns
+/// * `Ok(Vec<(&Key, Path)>)` with the matching keys and their paths, in document order
+/// * `Err(TraverseError)` if the tree is malformed, see `helper::walk_with_paths`
+pub fn find_keys<'a>(jobj: &'a JsonObject, matcher: &Matcher) -> Result<Vec<(&'a Key, Path)>, TraverseError> {
+
+    find_all(jobj, |key, location| {
+
+        !is_element(location) && matcher.is_match(&unescape_lossy(key.get_name()))
+    })
+}
+
+/// Finds every key holding a string value that matches. Numbers, booleans and nulls are not looked at.
+///
+/// # Arguments
+/// * `jobj` - The tree to search
+/// * `matcher` - What the value has to match
+///
+/// # Returns
+/// * `Ok(Vec<(&Key, Path)>)` with the matching keys and their paths, in document order
+/// * `Err(TraverseError)` if the tree is malformed, see `helper::walk_with_paths`
+pub fn find_values<'a>(jobj: &'a JsonObject, matcher: &Matcher) -> Result<Vec<(&'a Key, Path)>, TraverseError> {
+
+    find_all(jobj, |key, _| {
+
+        *key.get_value_type() == ValueType::StringType && matcher.is_match(&unescape_lossy(key.get_value()))
+    })
+}
+
+/// Finds every key for which the predicate holds. The predicate also gets the location of the key,
+/// so it can look at its path or at the keys holding it.
+///
+/// # Arguments
+/// * `jobj` - The tree to search
+/// * `predicate` - Called for every key, in document order
+///
+/// # Returns
+/// * `Ok(Vec<(&Key, Path)>)` with the matching keys and their paths, in document order
+/// * `Err(TraverseError)` if the tree is malformed, see `helper::walk_with_paths`
+pub fn find_all<'a, F>(jobj: &'a JsonObject, mut predicate: F) -> Result<Vec<(&'a Key, Path)>, TraverseError>
+where
+    F: FnMut(&'a Key, &Location<'a, '_>) -> bool,
+{
+    let mut ret = Vec::new();
+
+    // The tree is already built and the walk does not recurse, whatever depth the parser allowed is searched
+    walk_with_paths_and_max_depth(jobj, |key, location| {
+
+        if predicate(key, location) {
+
+            ret.push((key, location.path().clone()));
+        }
+
+        VisitAction::Continue
+    }, usize::MAX)?;
+
+    Ok(ret)
+}
+
+// Array elements are keys with an empty name, which is not a name
+fn is_element(location: &Location) -> bool {
+
+    location.parent().is_some_and(|parent| *parent.get_value_type() == ValueType::ArrayType)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::{parse_str, ParserOptions};
+
+    fn parse(text: &str) -> JsonObject {
+        *parse_str(text, &ParserOptions::default()).unwrap().unwrap()
+    }
+
+    fn pointers(found: &[(&Key, Path)]) -> Vec<String> {
+        found.iter().map(|(_, path)| path.to_pointer()).collect()
+    }
+
+    const DOCUMENT: &str = r#"{
+        "name": "Straße",
+        "Name": "STRASSE",
+        "tags": ["name", "NAME", {"name": "inner"}],
+        "count": 1,
+        "flag": true,
+        "empty": null,
+        "ÉTÉ": "été",
+        "nested": {"name": "x", "list": [{"title": "name"}], "number": "1"}
+    }"#;
+
+    #[test]
+    fn exact() {
+        let jobj = parse(DOCUMENT);
+
+        assert_eq!(pointers(&find_keys(&jobj, &Matcher::exact("name")).unwrap()), vec!["/name", "/tags/2/name", "/nested/name"]);
+        assert_eq!(pointers(&find_values(&jobj, &Matcher::exact("name")).unwrap()), vec!["/tags/0", "/nested/list/0/title"]);
+        assert!(find_keys(&jobj, &Matcher::exact("nam")).unwrap().is_empty());
+
+        let matcher = Matcher::new("été", MatchMode::Exact).unwrap();
+        assert_eq!((matcher.get_pattern(), matcher.get_mode()), ("été", &MatchMode::Exact));
+        assert_eq!(pointers(&find_values(&jobj, &matcher).unwrap()), vec!["/ÉTÉ"]);
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let jobj = parse(DOCUMENT);
+
+        let matcher = Matcher::new("NAME", MatchMode::CaseInsensitive).unwrap();
+        assert_eq!(pointers(&find_keys(&jobj, &matcher).unwrap()), vec!["/name", "/Name", "/tags/2/name", "/nested/name"]);
+        assert_eq!(pointers(&find_values(&jobj, &matcher).unwrap()), vec!["/tags/0", "/tags/1", "/nested/list/0/title"]);
+
+        // Letters outside ASCII fold too, the whole text has to match
+        assert_eq!(pointers(&find_keys(&jobj, &Matcher::new("été", MatchMode::CaseInsensitive).unwrap()).unwrap()), vec!["/ÉTÉ"]);
+        assert_eq!(pointers(&find_values(&jobj, &Matcher::new("STRAẞE", MatchMode::CaseInsensitive).unwrap()).unwrap()), vec!["/name"]);
+        assert!(find_values(&jobj, &Matcher::new("strass", MatchMode::CaseInsensitive).unwrap()).unwrap().is_empty());
+
+        // Folding is one character for one, ß is not SS
+        assert_eq!(pointers(&find_values(&jobj, &Matcher::new("strasse", MatchMode::CaseInsensitive).unwrap()).unwrap()), vec!["/Name"]);
+
+        // The pattern is text, not a regular expression
+        assert!(find_keys(&jobj, &Matcher::new("n.me", MatchMode::CaseInsensitive).unwrap()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn regex() {
+        let jobj = parse(DOCUMENT);
+
+        let matcher = Matcher::new("^[Nn]", MatchMode::Regex).unwrap();
+        assert_eq!(pointers(&find_keys(&jobj, &matcher).unwrap()), vec!["/name", "/Name", "/tags/2/name", "/nested", "/nested/name", "/nested/number"]);
+
+        // Found anywhere in the text unless anchored
+        assert_eq!(pointers(&find_values(&jobj, &Matcher::new("ß", MatchMode::Regex).unwrap()).unwrap()), vec!["/name"]);
+        assert_eq!(pointers(&find_values(&jobj, &Matcher::new("(?i)^na", MatchMode::Regex).unwrap()).unwrap()), vec!["/tags/0", "/tags/1", "/nested/list/0/title"]);
+
+        assert!(Matcher::new("(", MatchMode::Regex).is_err());
+    }
+
+    #[test]
+    fn keys_skip_array_elements() {
+        let jobj = parse(r#"{"list": ["list", ["list"], {"list": 1}]}"#);
+
+        // The elements are named "" and their values are never names
+        assert_eq!(pointers(&find_keys(&jobj, &Matcher::exact("list")).unwrap()), vec!["/list", "/list/2/list"]);
+        assert!(find_keys(&jobj, &Matcher::exact("")).unwrap().is_empty());
+        assert_eq!(find_keys(&jobj, &Matcher::new("", MatchMode::Regex).unwrap()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn values_are_strings_only() {
+        let jobj = parse(DOCUMENT);
+
+        // 1, true and null are not the strings "1", "true" or "null", containers are not looked into as text
+        assert_eq!(pointers(&find_values(&jobj, &Matcher::exact("1")).unwrap()), vec!["/nested/number"]);
+        assert!(find_values(&jobj, &Matcher::exact("true")).unwrap().is_empty());
+        assert!(find_values(&jobj, &Matcher::exact("null")).unwrap().is_empty());
+        assert_eq!(pointers(&find_values(&jobj, &Matcher::new("inner", MatchMode::Regex).unwrap()).unwrap()), vec!["/tags/2/name"]);
+    }
+
+    #[test]
+    fn predicate() {
+        let jobj = parse(DOCUMENT);
+
+        let found = find_all(&jobj, |key, location| location.path().len() == 3 && *key.get_value_type() == ValueType::StringType).unwrap();
+        assert_eq!(pointers(&found), vec!["/tags/2/name"]);
+        assert_eq!(found[0].0.get_value(), "inner");
+    }
+}
@@ -0,0 +1,632 @@
+/*
+    src/serde_interop.rs
+    This file is part of the JSON-rust crate.
+    Written by, Q@khaa.pk
+ */
+
+/*
+    serde support, compiled in with the "serde" cargo feature.
+    JsonObject and Key implement Serialize, so a parsed tree can be written by any serde data format,
+    and Deserialize, so anything a serde Deserializer reads (serde_json text, a serde_json::Value, ...) becomes a tree.
+    from_json_object() goes the other way and fills any #[derive(Deserialize)] type straight from a parsed tree,
+    errors carry the Path of the value that could not be read.
+ */
+
+use std::fmt;
+
+use serde::de::{self, DeserializeSeed, Deserializer, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess, Visitor};
+use serde::ser::{SerializeMap, SerializeSeq, Serializer};
+use serde::{Deserialize, Serialize};
+
+use crate::escape::unescape;
+use crate::from_json::Value;
+use crate::json_object::{Iter, JsonObject, Key, ValueType};
+use crate::pointer::Path;
+use crate::to_json::{array, named, object, with_raw_text, ToJson};
+
+impl Serialize for Key {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.get_value_type() {
+            ValueType::StringType => serializer.serialize_str(&decode::<S::Error>(self.get_value())?),
+            ValueType::NumberType => {
+                let raw = self.get_value().trim();
+                if let Ok(n) = raw.parse::<i64>() {
+                    serializer.serialize_i64(n)
+                } else if let Ok(n) = raw.parse::<u64>() {
+                    serializer.serialize_u64(n)
+                } else if let Ok(n) = raw.parse::<f64>() {
+                    serializer.serialize_f64(n)
+                } else {
+                    Err(serde::ser::Error::custom(format!("invalid number {}", raw)))
+                }
+            },
+            ValueType::BooleanType => serializer.serialize_bool(self.get_value().trim() == "true"),
+            ValueType::NullType => serializer.serialize_unit(),
+            ValueType::ObjectType => serialize_members(self.iter(), serializer),
+            ValueType::ArrayType => {
+                let mut seq = serializer.serialize_seq(Some(self.iter().count()))?;
+                for element in self.iter() {
+                    seq.serialize_element(element)?;
+                }
+                seq.end()
+            },
+        }
+    }
+}
+
+impl Serialize for JsonObject {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_members(self.iter(), serializer)
+    }
+}
+
+fn serialize_members<S: Serializer>(members: Iter<'_>, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut map = serializer.serialize_map(Some(members.clone().count()))?;
+    for member in members {
+        map.serialize_entry(&decode::<S::Error>(member.get_name())?, member)?;
+    }
+    map.end()
+}
+
+fn decode<E: serde::ser::Error>(raw: &str) -> Result<String, E> {
+    unescape(raw).map_err(|e| E::custom(format!("invalid string, {:?}", e)))
+}
+
+// Builds a Key, without a name, out of whatever a Deserializer reads
+struct KeyVisitor;
+
+impl<'de> Visitor<'de> for KeyVisitor {
+    type Value = Key;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a JSON value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Key, E> {
+        Ok(v.to_json())
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Key, E> {
+        Ok(v.to_json())
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Key, E> {
+        Ok(v.to_json())
+    }
+
+    fn visit_i128<E: de::Error>(self, v: i128) -> Result<Key, E> {
+        Ok(v.to_json())
+    }
+
+    fn visit_u128<E: de::Error>(self, v: u128) -> Result<Key, E> {
+        Ok(v.to_json())
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Key, E> {
+        Ok(v.to_json())
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Key, E> {
+        Ok(v.to_json())
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Key, E> {
+        Ok(().to_json())
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Key, E> {
+        Ok(().to_json())
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Key, D::Error> {
+        deserializer.deserialize_any(KeyVisitor)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Key, A::Error> {
+        let mut elements = Vec::new();
+        while let Some(element) = seq.next_element_seed(KeySeed)? {
+            elements.push(element);
+        }
+        Ok(array(elements))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Key, A::Error> {
+        let mut members = Vec::new();
+        while let Some(name) = map.next_key::<String>()? {
+            members.push(named(&name, map.next_value_seed(KeySeed)?));
+        }
+        Ok(object(members))
+    }
+}
+
+// Reads a value below the one being deserialized, objects and arrays there are left without their text
+struct KeySeed;
+
+impl<'de> DeserializeSeed<'de> for KeySeed {
+    type Value = Key;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Key, D::Error> {
+        deserializer.deserialize_any(KeyVisitor)
+    }
+}
+
+/// Objects and arrays get their text the way `to_json::with_raw_text` gives it, the ones below them do not.
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(KeyVisitor).map(with_raw_text)
+    }
+}
+
+/// Only maps can be read, the root of a document is always an object.
+impl<'de> Deserialize<'de> for JsonObject {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut key = deserializer.deserialize_map(KeyVisitor)?;
+
+        let mut ret = JsonObject::new();
+        ret.set_keys(key.take_keys().into_iter().map(with_raw_text).collect());
+
+        Ok(ret)
+    }
+}
+
+/// A value that could not be deserialized, with where it is in the document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    pub path: Option<Path>, // None until the error has made its way back to the value it is about
+    pub message: String,
+}
+
+impl Error {
+    // Records the location of the value the error is about, the deepest location is kept
+    fn at(mut self, path: &Path) -> Self {
+        if self.path.is_none() {
+            self.path = Some(path.clone());
+        }
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "{}: {}", path.to_pointer(), self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        Error { path: None, message: message.to_string() }
+    }
+}
+
+/// Deserializes a whole document into any type implementing Deserialize.
+///
+/// # Arguments
+/// * `jobj` - The root object of the document
+///
+/// # Returns
+/// * `Ok(T)` holding the deserialized document
+/// * `Err(Error)` for the first value that could not be deserialized
+pub fn from_json_object<'de, T: Deserialize<'de>>(jobj: &'de JsonObject) -> Result<T, Error> {
+    T::deserialize(ValueDeserializer::new(Value::Root(jobj), Path::new()))
+}
+
+/// Deserializes one value of a tree.
+///
+/// # Arguments
+/// * `key` - The value
+/// * `path` - Where the value is in the document, used to report errors
+pub fn from_key<'de, T: Deserialize<'de>>(key: &'de Key, path: &Path) -> Result<T, Error> {
+    T::deserialize(ValueDeserializer::new(Value::Key(key), path.clone()))
+}
+
+/// A serde Deserializer reading a value of a parsed tree.
+pub struct ValueDeserializer<'de> {
+    value: Value<'de>,
+    path: Path,
+}
+
+impl<'de> ValueDeserializer<'de> {
+    /// Creates a deserializer for the value at the given path.
+    pub fn new(value: Value<'de>, path: Path) -> Self {
+        ValueDeserializer { value, path }
+    }
+
+    fn error(&self, message: &str) -> Error {
+        Error { path: Some(self.path.clone()), message: message.to_string() }
+    }
+
+    fn key(&self) -> Option<&'de Key> {
+        match self.value {
+            Value::Root(_) => None,
+            Value::Key(key) => Some(key),
+        }
+    }
+
+    fn deserialize_any_inner<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value.value_type() {
+            ValueType::StringType => {
+                let raw = self.key().unwrap().get_value();
+                if raw.contains('\\') {
+                    let decoded = unescape(raw).map_err(|e| self.error(&format!("invalid string, {:?}", e)))?;
+                    visitor.visit_string(decoded)
+                } else {
+                    visitor.visit_borrowed_str(raw)
+                }
+            },
+            ValueType::NumberType => {
+                let raw = self.key().unwrap().get_value().trim();
+                if let Ok(n) = raw.parse::<i64>() {
+                    visitor.visit_i64(n)
+                } else if let Ok(n) = raw.parse::<u64>() {
+                    visitor.visit_u64(n)
+                } else if let Ok(n) = raw.parse::<f64>() {
+                    visitor.visit_f64(n)
+                } else {
+                    Err(self.error(&format!("invalid number {}", raw)))
+                }
+            },
+            ValueType::BooleanType => visitor.visit_bool(self.key().unwrap().get_value().trim() == "true"),
+            ValueType::NullType => visitor.visit_unit(),
+            ValueType::ObjectType => visitor.visit_map(Members { members: self.value.iter(), path: &self.path, value: None }),
+            ValueType::ArrayType => visitor.visit_seq(Elements { elements: self.value.iter(), path: &self.path, index: 0 }),
+        }
+    }
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let path = self.path.clone();
+        self.deserialize_any_inner(visitor).map_err(|e| e.at(&path))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.value.value_type() == ValueType::NullType {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    // A unit variant is a string, any other variant an object with a single member named after it
+    fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        let path = self.path.clone();
+
+        let ret = match self.value.value_type() {
+            ValueType::StringType => {
+                let variant = decode_at(self.key().unwrap().get_value(), &self.path)?;
+                visitor.visit_enum(Variant { name: variant, content: None, path: &self.path })
+            },
+            ValueType::ObjectType if self.value.iter().count() == 1 => {
+                let member = self.value.iter().next().unwrap();
+                let variant = decode_at(member.get_name(), &self.path)?;
+                visitor.visit_enum(Variant { name: variant, content: Some(member), path: &self.path })
+            },
+            _ => Err(self.error("expected a string or an object with a single member")),
+        };
+
+        ret.map_err(|e| e.at(&path))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+fn decode_at(raw: &str, path: &Path) -> Result<String, Error> {
+    unescape(raw).map_err(|e| Error { path: Some(path.clone()), message: format!("invalid string, {:?}", e) })
+}
+
+// The members of an object, handed out name then value
+struct Members<'de, 'p> {
+    members: Iter<'de>,
+    path: &'p Path,
+    value: Option<&'de Key>,
+}
+
+impl<'de> MapAccess<'de> for Members<'de, '_> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+        match self.members.next() {
+            Some(member) => {
+                self.value = Some(member);
+                let name = decode_at(member.get_name(), self.path)?;
+                seed.deserialize(name.into_deserializer()).map(Some)
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let member = self.value.take().ok_or_else(|| Error { path: Some(self.path.clone()), message: "value requested before its name".to_string() })?;
+        let path = self.path.with_key(&decode_at(member.get_name(), self.path)?);
+
+        seed.deserialize(ValueDeserializer::new(Value::Key(member), path.clone())).map_err(|e| e.at(&path))
+    }
+}
+
+// The elements of an array
+struct Elements<'de, 'p> {
+    elements: Iter<'de>,
+    path: &'p Path,
+    index: usize,
+}
+
+impl<'de> SeqAccess<'de> for Elements<'de, '_> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+        match self.elements.next() {
+            Some(element) => {
+                let path = self.path.with_index(self.index);
+                self.index += 1;
+                seed.deserialize(ValueDeserializer::new(Value::Key(element), path.clone())).map(Some).map_err(|e| e.at(&path))
+            },
+            None => Ok(None),
+        }
+    }
+}
+
+// The variant of an enum, with its content unless it is a unit variant written as a string
+struct Variant<'de, 'p> {
+    name: String,
+    content: Option<&'de Key>,
+    path: &'p Path,
+}
+
+impl<'de, 'p> EnumAccess<'de> for Variant<'de, 'p> {
+    type Error = Error;
+    type Variant = Content<'de>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Content<'de>), Error> {
+        let content = Content { key: self.content, path: self.path.with_key(&self.name) };
+        let variant = seed.deserialize(self.name.into_deserializer())?;
+        Ok((variant, content))
+    }
+}
+
+struct Content<'de> {
+    key: Option<&'de Key>,
+    path: Path,
+}
+
+impl<'de> Content<'de> {
+    fn deserializer(self, expected: &str) -> Result<ValueDeserializer<'de>, Error> {
+        match self.key {
+            Some(key) => Ok(ValueDeserializer::new(Value::Key(key), self.path)),
+            None => Err(Error { path: self.path.parent(), message: format!("expected {}", expected) }),
+        }
+    }
+}
+
+impl<'de> VariantAccess<'de> for Content<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self.key {
+            None => Ok(()),
+            Some(_) => Deserialize::deserialize(self.deserializer("null")?),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self.deserializer("an object with a single member")?)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserializer("an object with a single member")?.deserialize_seq(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        self.deserializer("an object with a single member")?.deserialize_map(visitor)
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::json::{parse_str, ParserOptions};
+
+    fn parse(text: &str) -> JsonObject {
+        *parse_str(text, &ParserOptions::default()).unwrap().unwrap()
+    }
+
+    // Reads the text into T, writes T with serde_json, then reads that text back into T
+    fn round_trip<T: for<'de> Deserialize<'de> + Serialize + fmt::Debug + PartialEq>(text: &str) -> T {
+        let jobj = parse(text);
+        let value: T = from_json_object(&jobj).unwrap();
+
+        let written = serde_json::to_string(&value).unwrap();
+        let again: T = from_json_object(&parse(&written)).unwrap();
+        assert_eq!(again, value, "{}", written);
+
+        value
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Order {
+        id: u64,
+        customer: Customer,
+        lines: Vec<Line>,
+        note: Option<String>,
+        coupon: Option<String>,
+        #[serde(default)]
+        gift: Option<bool>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Customer {
+        name: String,
+        #[serde(rename = "e-mail")]
+        email: String,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Line {
+        sku: String,
+        quantity: u32,
+        price: f64,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Shape {
+        Empty,
+        Circle(f64),
+        Rectangle(f64, f64),
+        Polygon { sides: u8, length: f64 },
+    }
+
+    #[test]
+    fn structs() {
+        let order: Order = round_trip(r#"{
+            "id": 7,
+            "customer": {"name": "Zoë é", "e-mail": "z@example.com"},
+            "lines": [{"sku": "A-1", "quantity": 2, "price": 9.5}, {"sku": "B-2", "quantity": 1, "price": 0.1}],
+            "note": "leave at the door",
+            "coupon": null
+        }"#);
+
+        assert_eq!(order.customer, Customer { name: String::from("Zoë é"), email: String::from("z@example.com") });
+        assert_eq!(order.lines[1], Line { sku: String::from("B-2"), quantity: 1, price: 0.1 });
+        assert_eq!((order.note.as_deref(), order.coupon, order.gift), (Some("leave at the door"), None, None));
+
+        let jobj = parse(r#"{"id": "7", "customer": {"name": "Z", "e-mail": "z"}, "lines": [], "note": null, "coupon": null}"#);
+        let e = from_json_object::<Order>(&jobj).unwrap_err();
+        assert_eq!(e.path.unwrap().to_pointer(), "/id");
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Shapes {
+        shapes: Vec<Shape>,
+    }
+
+    #[test]
+    fn enums() {
+        let shapes: Vec<Shape> = round_trip::<Shapes>(r#"{"shapes": [
+            "Empty",
+            {"Circle": 1.5},
+            {"Rectangle": [2, 3]},
+            {"Polygon": {"sides": 6, "length": 0.25}}
+        ]}"#).shapes;
+
+        assert_eq!(shapes, vec![Shape::Empty, Shape::Circle(1.5), Shape::Rectangle(2.0, 3.0), Shape::Polygon { sides: 6, length: 0.25 }]);
+
+        let jobj = parse(r#"{"shapes": [{"Circle": 1, "Empty": null}]}"#);
+        assert_eq!(from_json_object::<Shapes>(&jobj).unwrap_err().path.unwrap().to_pointer(), "/shapes/0");
+
+        let jobj = parse(r#"{"shapes": ["Hexagon"]}"#);
+        assert!(from_json_object::<Shapes>(&jobj).is_err());
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Options {
+        a: Option<i32>,
+        b: Option<i32>,
+        c: Option<Option<i32>>,
+        #[serde(default)]
+        d: Option<Vec<i32>>,
+    }
+
+    #[test]
+    fn options() {
+        let options: Options = round_trip(r#"{"a": 1, "b": null, "c": 2}"#);
+        assert_eq!(options, Options { a: Some(1), b: None, c: Some(Some(2)), d: None });
+
+        let options: Options = round_trip(r#"{"a": null, "b": -3, "c": null, "d": [4]}"#);
+        assert_eq!(options, Options { a: None, b: Some(-3), c: None, d: Some(vec![4]) });
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Sequences {
+        empty: Vec<String>,
+        nested: Vec<Vec<i64>>,
+        pair: (String, bool),
+        mixed: Vec<Option<u8>>,
+    }
+
+    #[test]
+    fn sequences() {
+        let sequences: Sequences = round_trip(r#"{"empty": [], "nested": [[1, 2], [], [3]], "pair": ["x", true], "mixed": [1, null, 255]}"#);
+
+        assert_eq!(sequences, Sequences {
+            empty: Vec::new(),
+            nested: vec![vec![1, 2], Vec::new(), vec![3]],
+            pair: (String::from("x"), true),
+            mixed: vec![Some(1), None, Some(255)],
+        });
+
+        let jobj = parse(r#"{"empty": [], "nested": [[1, 2], [3, "4"]], "pair": ["x", true], "mixed": []}"#);
+        assert_eq!(from_json_object::<Sequences>(&jobj).unwrap_err().path.unwrap().to_pointer(), "/nested/1/1");
+
+        let jobj = parse(r#"{"empty": [], "nested": [], "pair": ["x", true], "mixed": [256]}"#);
+        assert_eq!(from_json_object::<Sequences>(&jobj).unwrap_err().path.unwrap().to_pointer(), "/mixed/0");
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Numbers {
+        largest: u64,
+        smallest: i64,
+        tenth: f64,
+        huge: f64,
+        tiny: f64,
+        negative_zero: f64,
+        third: f32,
+    }
+
+    #[test]
+    fn number_precision() {
+        let numbers: Numbers = round_trip(r#"{
+            "largest": 18446744073709551615,
+            "smallest": -9223372036854775808,
+            "tenth": 0.1,
+            "huge": 1.7976931348623157e308,
+            "tiny": 5e-324,
+            "negative_zero": -0.0,
+            "third": 0.33333334
+        }"#);
+
+        assert_eq!(numbers.largest, u64::MAX);
+        assert_eq!(numbers.smallest, i64::MIN);
+        assert_eq!(numbers.tenth, 0.1);
+        assert_eq!(numbers.huge, f64::MAX);
+        assert_eq!(numbers.tiny, 5e-324);
+        assert!(numbers.negative_zero == 0.0 && numbers.negative_zero.is_sign_negative());
+        assert_eq!(numbers.third, 1.0f32 / 3.0);
+
+        // Integers too large for u64 are read as f64
+        let jobj = parse(r#"{"n": 18446744073709551616}"#);
+        assert!(from_json_object::<std::collections::BTreeMap<String, u64>>(&jobj).is_err());
+        assert_eq!(from_json_object::<std::collections::BTreeMap<String, f64>>(&jobj).unwrap()["n"], 18446744073709551616.0);
+    }
+
+    #[test]
+    fn trees() {
+        let text = r#"{"a":[1,-2.5,18446744073709551615,-9223372036854775808,0.1],"b":{"c":null,"d":true,"e":"x\nyé"},"f":[]}"#;
+        let jobj = parse(text);
+
+        // Through serde_json text and back, the tree is unchanged
+        let written = serde_json::to_string(&jobj).unwrap();
+        assert_eq!(written, "{\"a\":[1,-2.5,18446744073709551615,-9223372036854775808,0.1],\"b\":{\"c\":null,\"d\":true,\"e\":\"x\\nyé\"},\"f\":[]}");
+
+        let again: JsonObject = serde_json::from_str(&written).unwrap();
+        assert_eq!(again.to_json_string(), parse(&written).to_json_string());
+
+        // Straight from one tree to another
+        let copy: JsonObject = from_json_object(&jobj).unwrap();
+        assert_eq!(copy.to_json_string(), parse(&written).to_json_string());
+
+        assert!(serde_json::from_str::<JsonObject>("[1, 2]").is_err());
+    }
+}
@@ -0,0 +1,246 @@
+/*
+    src/stats.rs
+    This file is part of the JSON-rust crate.
+    Written by, Q@khaa.pk
+ */
+
+/*
+    Statistics about the shape of a parsed document: how many values of each type it holds, how deep
+    and how wide it gets, how much string data it carries and where its bulk is. Handy to profile
+    incoming payloads and to pick sensible parser limits.
+ */
+
+use crate::escape::unescape_lossy;
+use crate::json_object::{ValueType, Key, JsonObject, Iter};
+use crate::pointer::Path;
+
+/// How many of the largest subtrees `stats` reports.
+pub const LARGEST_SUBTREES: usize = 5;
+
+/// Figures collected by `stats`. Depths count the top level keys as depth 1.
+#[derive(Debug, Clone, Default)]
+pub struct DocumentStats {
+    pub strings: usize,                       // Number of string values
+    pub numbers: usize,                       // Number of number values
+    pub booleans: usize,                      // Number of boolean values
+    pub nulls: usize,                         // Number of null values
+    pub objects: usize,                       // Number of objects, the root object not included
+    pub arrays: usize,                        // Number of arrays
+    pub total_nodes: usize,                   // Number of keys in the tree, i.e. the sum of the counts above
+    pub max_depth: usize,                     // Depth of the deepest key
+    pub average_depth: f64,                   // Average depth over every key
+    pub widest_object: Option<(Path, usize)>, // Object with the most members, and how many (the root, with an empty path, included)
+    pub longest_array: Option<(Path, usize)>, // Array with the most elements, and how many
+    pub total_string_bytes: usize,            // UTF-8 length of every string value once decoded, member names not included
+    pub largest_subtrees: Vec<(Path, usize)>, // Objects and arrays holding the most keys (themselves included), largest first
+}
+
+impl DocumentStats {
+    /// Returns the number of values of the given type.
+    ///
+    /// # Arguments
+    /// * `value_type` - The type to count
+    pub fn count(&self, value_type: &ValueType) -> usize {
+        match value_type {
+            ValueType::StringType => self.strings,
+            ValueType::NumberType => self.numbers,
+            ValueType::BooleanType => self.booleans,
+            ValueType::NullType => self.nulls,
+            ValueType::ObjectType => self.objects,
+            ValueType::ArrayType => self.arrays,
+        }
+    }
+}
+
+// An object or array whose children are being counted
+struct Frame<'a> {
+    path: Path,
+    children: Iter<'a>,
+    is_array: bool,
+    index: usize,
+    size: usize, // Keys counted so far in this subtree, the container itself included
+}
+
+/// Collects statistics about a parsed document, in a single pass that keeps its own stack.
+///
+/// # Arguments
+/// * `jobj` - The root object
+pub fn stats(jobj: &JsonObject) -> DocumentStats {
+
+    let mut ret = DocumentStats { widest_object: Some((Path::new(), jobj.get_n())), ..Default::default() };
+    let mut depth_sum = 0;
+
+    let mut stack: Vec<Frame> = vec![Frame { path: Path::new(), children: jobj.iter(), is_array: false, index: 0, size: 0 }];
+
+    loop {
+
+        // Depth of the keys found in the frame on top of the stack
+        let depth = stack.len();
+
+        let frame = match stack.last_mut() {
+
+            Some(frame) => frame,
+            None => break,
+        };
+
+        let key: &Key = match frame.children.next() {
+
+            Some(key) => key,
+            None => {
+
+                // Subtree done, its size adds up to the size of its parent
+                let frame = stack.pop().unwrap();
+
+                if let Some(parent) = stack.last_mut() {
+
+                    parent.size += frame.size;
+
+                    record_subtree(&mut ret.largest_subtrees, frame.path, frame.size);
+                }
+
+                continue;
+            },
+        };
+
+        let path = if frame.is_array { frame.path.with_index(frame.index) } else { frame.path.with_key(&unescape_lossy(key.get_name())) };
+        frame.index += 1;
+
+        ret.total_nodes += 1;
+        depth_sum += depth;
+        ret.max_depth = ret.max_depth.max(depth);
+
+        match key.get_value_type() {
+
+            ValueType::StringType => {
+
+                ret.strings += 1;
+                ret.total_string_bytes += unescape_lossy(key.get_value()).len();
+            },
+            ValueType::NumberType => ret.numbers += 1,
+            ValueType::BooleanType => ret.booleans += 1,
+            ValueType::NullType => ret.nulls += 1,
+            ValueType::ObjectType | ValueType::ArrayType => {
+
+                let is_array = *key.get_value_type() == ValueType::ArrayType;
+                let widest = if is_array { &mut ret.longest_array } else { &mut ret.widest_object };
+
+                if widest.as_ref().map_or(true, |(_, n)| key.get_n() > *n) {
+
+                    *widest = Some((path.clone(), key.get_n()));
+                }
+
+                if is_array { ret.arrays += 1 } else { ret.objects += 1 }
+
+                stack.push(Frame { path, children: key.iter(), is_array, index: 0, size: 1 });
+
+                continue;
+            },
+        }
+
+        frame.size += 1;
+    }
+
+    if ret.total_nodes > 0 {
+
+        ret.average_depth = depth_sum as f64 / ret.total_nodes as f64;
+    }
+
+    ret
+}
+
+// Keeps the LARGEST_SUBTREES biggest subtrees, largest first, the one recorded first comes first among equals
+fn record_subtree(largest: &mut Vec<(Path, usize)>, path: Path, size: usize) {
+
+    let position = largest.iter().position(|(_, other)| size > *other).unwrap_or(largest.len());
+
+    if position < LARGEST_SUBTREES {
+
+        largest.insert(position, (path, size));
+        largest.truncate(LARGEST_SUBTREES);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::{parse_str, ParserOptions};
+
+    fn parse(text: &str) -> JsonObject {
+        *parse_str(text, &ParserOptions::default()).unwrap().unwrap()
+    }
+
+    fn pointers(subtrees: &[(Path, usize)]) -> Vec<(String, usize)> {
+        subtrees.iter().map(|(path, size)| (path.to_pointer(), *size)).collect()
+    }
+
+    const DOCUMENT: &str = r#"{
+        "name": "café",
+        "n": 1,
+        "ok": true,
+        "none": null,
+        "list": [1, 2, [3, "xé"], {"a": 1}],
+        "obj": {"b": {"c": [], "d": "\n"}, "e": 2, "f": 3}
+    }"#;
+
+    #[test]
+    fn counts() {
+        let figures = stats(&parse(DOCUMENT));
+
+        assert_eq!((figures.strings, figures.numbers, figures.booleans, figures.nulls, figures.objects, figures.arrays), (3, 7, 1, 1, 3, 3));
+        assert_eq!(figures.total_nodes, 18);
+        assert_eq!(figures.count(&ValueType::NumberType), 7);
+        assert_eq!(figures.count(&ValueType::ArrayType), 3);
+
+        // "café" is 5 bytes, "xé" 3 once decoded, "\n" 1
+        assert_eq!(figures.total_string_bytes, 9);
+    }
+
+    #[test]
+    fn depths() {
+        let figures = stats(&parse(DOCUMENT));
+
+        // 6 keys at depth 1, 7 at depth 2, 5 at depth 3
+        assert_eq!(figures.max_depth, 3);
+        assert_eq!(figures.average_depth, 35.0 / 18.0);
+
+        let figures = stats(&parse(r#"{"a": [[[[1]]]], "b": 2}"#));
+        assert_eq!((figures.max_depth, figures.average_depth), (5, 16.0 / 6.0));
+    }
+
+    #[test]
+    fn widest_and_longest() {
+        let figures = stats(&parse(DOCUMENT));
+
+        assert_eq!(figures.widest_object.map(|(path, n)| (path.to_pointer(), n)), Some((String::new(), 6)));
+        assert_eq!(figures.longest_array.map(|(path, n)| (path.to_pointer(), n)), Some((String::from("/list"), 4)));
+
+        // Wider than the root, the first one found wins a tie
+        let figures = stats(&parse(r#"{"a": {"x": 1, "y": 2, "v": 3}, "b": [[1, 2], [3, 4], {"z": 1, "w": 2, "u": 3}]}"#));
+
+        assert_eq!(figures.widest_object.map(|(path, n)| (path.to_pointer(), n)), Some((String::from("/a"), 3)));
+        assert_eq!(figures.longest_array.map(|(path, n)| (path.to_pointer(), n)), Some((String::from("/b"), 3)));
+    }
+
+    #[test]
+    fn largest_subtrees() {
+        let figures = stats(&parse(DOCUMENT));
+
+        // Largest first, among equals the one finished first, /obj/b/c is the sixth and is left out
+        assert_eq!(pointers(&figures.largest_subtrees), vec![
+            (String::from("/list"), 8),
+            (String::from("/obj"), 6),
+            (String::from("/list/2"), 3),
+            (String::from("/obj/b"), 3),
+            (String::from("/list/3"), 2),
+        ]);
+    }
+
+    #[test]
+    fn empty_document() {
+        let figures = stats(&JsonObject::new());
+
+        assert_eq!((figures.total_nodes, figures.max_depth, figures.average_depth), (0, 0, 0.0));
+        assert_eq!(figures.widest_object.map(|(path, n)| (path.to_pointer(), n)), Some((String::new(), 0)));
+        assert!(figures.longest_array.is_none() && figures.largest_subtrees.is_empty());
+    }
+}
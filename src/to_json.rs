@@ -0,0 +1,342 @@
+/*
+    src/to_json.rs
+    This file is part of the JSON-rust crate.
+    Written by, Q@khaa.pk
+ */
+
+/*
+    Building trees from Rust values, the reverse of from_json.rs.
+    ToJson turns a value into an unnamed Key, it is implemented here for strings, numbers, booleans,
+    Option, Vec, slices, maps and tuples, and can be derived for structs and enums with #[derive(ToJson)].
+    A value written by a derived ToJson is read back by the FromJson derived for the same type.
+
+    Structs take the field attributes of FromJson, rename, skip and flatten change what is written, default does not.
+    Enums are tagged the way the attribute on the enum says,
+    (none)                                  external, {"Variant": content}, a unit variant is the string "Variant"
+    #[json(tag = "type")]                   internal, {"type": "Variant", ...fields}, unit and struct variants only
+    #[json(tag = "type", content = "c")]    adjacent, {"type": "Variant", "c": content}
+    #[json(untagged)]                       the content alone, FromJson takes the first variant that reads it
+    Content is null for a unit variant, the field for a newtype variant, an array for a tuple variant
+    and an object for a struct variant. #[json(rename = "name")] on a variant changes its tag.
+ */
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::BuildHasher;
+
+use crate::escape::escape;
+use crate::json_object::{JsonObject, Key, ValueType};
+
+pub use json_rust_derive::ToJson;
+
+/// Conversion of a Rust value into a tree.
+pub trait ToJson {
+    /// Builds the value as a key without a name, ready to become an array element
+    /// or, once named, an object member. Objects and arrays are built without their text,
+    /// get_value() is empty as with ParserOptions::keep_raw off, see `with_raw_text`.
+    fn to_json(&self) -> Key;
+}
+
+/// Builds a whole document.
+///
+/// # Arguments
+/// * `value` - The value to write, it has to be written as an object
+///
+/// # Returns
+/// * `Some(JsonObject)` holding the members the value is written as
+/// * `None` if the value is not written as an object, the root of a document can only be an object
+pub fn to_json_object<T: ToJson + ?Sized>(value: &T) -> Option<JsonObject> {
+
+    let mut key = value.to_json();
+
+    if *key.get_value_type() != ValueType::ObjectType {
+
+        return None;
+    }
+
+    let mut ret = JsonObject::new();
+    ret.set_keys(key.take_keys().into_iter().map(with_raw_text).collect());
+
+    Some(ret)
+}
+
+/// Gives an object or array its text as its value, the way the parser does, other values are returned as they are.
+/// Only the key itself gets it, writing the text at every level would take time in the size of the value times its depth,
+/// so the objects and arrays below it are left without theirs. `to_json_object` does this for each member of the document.
+///
+/// # Arguments
+/// * `key` - A value built by `ToJson::to_json`
+pub fn with_raw_text(mut key: Key) -> Key {
+
+    if matches!(key.get_value_type(), ValueType::ObjectType | ValueType::ArrayType) {
+
+        key.set_value(key.to_json_string());
+    }
+
+    key
+}
+
+/// Builds a value and names it, used by derived implementations.
+///
+/// # Arguments
+/// * `name` - The decoded member name, it is escaped here
+/// * `value` - The value of the member
+pub fn member<T: ToJson + ?Sized>(name: &str, value: &T) -> Key {
+
+    named(name, value.to_json())
+}
+
+/// Names a key that is already built.
+///
+/// # Arguments
+/// * `name` - The decoded member name, it is escaped here
+/// * `key` - The value of the member
+pub fn named(name: &str, mut key: Key) -> Key {
+
+    key.set_name(escape(name));
+
+    key
+}
+
+/// Puts the tag of an internally tagged enum in front of the members of its variant.
+///
+/// # Arguments
+/// * `tag` - The decoded name of the tag member
+/// * `variant` - The tag, i.e. the name of the variant
+/// * `content` - The variant written as an object
+pub fn tagged(tag: &str, variant: &str, mut content: Key) -> Key {
+
+    let mut members = vec![member(tag, variant)];
+    members.extend(content.take_keys());
+
+    object(members)
+}
+
+/// Builds an object from its members.
+pub fn object(members: Vec<Key>) -> Key {
+
+    container(ValueType::ObjectType, members)
+}
+
+/// Builds an array from its elements, their names are cleared.
+pub fn array(mut elements: Vec<Key>) -> Key {
+
+    for element in elements.iter_mut() {
+
+        element.set_name(String::new());
+    }
+
+    container(ValueType::ArrayType, elements)
+}
+
+/// Returns the members a value is written as, for a field flattened into the object of its struct.
+/// A value that is not written as an object has no members and contributes nothing.
+pub fn flattened<T: ToJson + ?Sized>(value: &T) -> Vec<Key> {
+
+    let mut key = value.to_json();
+
+    match key.get_value_type() {
+
+        ValueType::ObjectType => key.take_keys(),
+        _ => Vec::new(),
+    }
+}
+
+// Without its text, each level writing the whole subtree again would make building a value quadratic in its depth
+fn container(value_type: ValueType, children: Vec<Key>) -> Key {
+
+    let mut key = Key::new(String::new(), value_type, String::new());
+    key.set_keys(children);
+
+    key
+}
+
+fn scalar(value_type: ValueType, value: String) -> Key {
+
+    Key::new(String::new(), value_type, value)
+}
+
+fn null() -> Key {
+
+    scalar(ValueType::NullType, "null".to_string())
+}
+
+macro_rules! to_json_integer {
+    ($($t:ty),*) => {
+        $(
+            impl ToJson for $t {
+                fn to_json(&self) -> Key {
+                    scalar(ValueType::NumberType, self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+to_json_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+// JSON has no NaN or infinities, they are written as null
+macro_rules! to_json_float {
+    ($($t:ty),*) => {
+        $(
+            impl ToJson for $t {
+                fn to_json(&self) -> Key {
+                    if self.is_finite() {
+                        scalar(ValueType::NumberType, self.to_string())
+                    } else {
+                        null()
+                    }
+                }
+            }
+        )*
+    };
+}
+
+to_json_float!(f32, f64);
+
+impl ToJson for bool {
+    fn to_json(&self) -> Key {
+        scalar(ValueType::BooleanType, self.to_string())
+    }
+}
+
+impl ToJson for str {
+    fn to_json(&self) -> Key {
+        scalar(ValueType::StringType, escape(self))
+    }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> Key {
+        self.as_str().to_json()
+    }
+}
+
+impl ToJson for char {
+    fn to_json(&self) -> Key {
+        self.to_string().to_json()
+    }
+}
+
+impl ToJson for () {
+    fn to_json(&self) -> Key {
+        null()
+    }
+}
+
+/// None is written as null.
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> Key {
+        match self {
+            Some(value) => value.to_json(),
+            None => null(),
+        }
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> Key {
+        (**self).to_json()
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for Box<T> {
+    fn to_json(&self) -> Key {
+        (**self).to_json()
+    }
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> Key {
+        array(self.iter().map(ToJson::to_json).collect())
+    }
+}
+
+impl<T: ToJson, const N: usize> ToJson for [T; N] {
+    fn to_json(&self) -> Key {
+        self.as_slice().to_json()
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> Key {
+        self.as_slice().to_json()
+    }
+}
+
+/// Members are written in the order the map iterates them, which for a HashMap is unspecified.
+impl<T: ToJson, S: BuildHasher> ToJson for HashMap<String, T, S> {
+    fn to_json(&self) -> Key {
+        object(self.iter().map(|(name, value)| member(name, value)).collect())
+    }
+}
+
+impl<T: ToJson> ToJson for BTreeMap<String, T> {
+    fn to_json(&self) -> Key {
+        object(self.iter().map(|(name, value)| member(name, value)).collect())
+    }
+}
+
+/// A copy of the key and everything nested under it, without its name.
+impl ToJson for Key {
+    fn to_json(&self) -> Key {
+        let mut key = self.clone_detached();
+        key.set_name(String::new());
+        key
+    }
+}
+
+impl ToJson for JsonObject {
+    fn to_json(&self) -> Key {
+        object(self.iter().map(Key::clone_detached).collect())
+    }
+}
+
+// Tuples are written as arrays
+macro_rules! to_json_tuple {
+    ($($t:ident $i:tt),+) => {
+        impl<$($t: ToJson),+> ToJson for ($($t,)+) {
+            fn to_json(&self) -> Key {
+                array(vec![$(self.$i.to_json()),+])
+            }
+        }
+    };
+}
+
+to_json_tuple!(A 0);
+to_json_tuple!(A 0, B 1);
+to_json_tuple!(A 0, B 1, C 2);
+to_json_tuple!(A 0, B 1, C 2, D 3);
+to_json_tuple!(A 0, B 1, C 2, D 3, E 4);
+to_json_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+to_json_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+to_json_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_outermost_container_has_its_text() {
+        let value = vec![vec![vec![1, 2]], vec![]];
+
+        let key = value.to_json();
+        assert_eq!(key.get_value(), "");
+
+        let key = with_raw_text(key);
+        assert_eq!(key.get_value(), "[[[1,2]],[]]");
+        assert!(key.iter().all(|element| element.get_value().is_empty()));
+        assert_eq!(key.to_json_string(), "[[[1,2]],[]]");
+    }
+
+    #[test]
+    fn document_members_have_their_text() {
+        let mut value = BTreeMap::new();
+        value.insert("a".to_string(), vec![1, 2]);
+
+        let jobj = to_json_object(&value).unwrap();
+        assert_eq!(jobj.iter().next().unwrap().get_value(), "[1,2]");
+        assert_eq!(jobj.to_json_string(), r#"{"a":[1,2]}"#);
+
+        assert!(to_json_object(&1).is_none());
+    }
+}
@@ -0,0 +1,70 @@
+/*
+    tests/codegen.rs
+    This file is part of the JSON-rust crate.
+    Written by, Q@khaa.pk
+ */
+
+/*
+    Golden test for codegen: tests/codegen/generated.rs is what generate() gives for the two samples next to it,
+    it is also compiled here and used to load them. After a change to the generated code, regenerate it with
+        cargo run --bin json2rust -- tests/codegen/first.json tests/codegen/second.json > tests/codegen/generated.rs
+ */
+
+// A struct with a single member gets a match with a single arm
+#[allow(clippy::single_match)]
+#[path = "codegen/generated.rs"]
+mod generated;
+
+use json_rust::codegen::generate;
+use json_rust::json::{parse_str, ParserOptions};
+use json_rust::json_object::JsonObject;
+
+use generated::{Item, Meta, Root};
+
+fn sample(name: &str) -> Box<JsonObject> {
+
+    let text = std::fs::read_to_string(format!("{}/tests/codegen/{}", env!("CARGO_MANIFEST_DIR"), name)).unwrap();
+
+    parse_str(&text, &ParserOptions::default()).unwrap().unwrap()
+}
+
+#[test]
+fn generated_code_is_unchanged() {
+
+    let (first, second) = (sample("first.json"), sample("second.json"));
+
+    assert_eq!(generate(&[&first, &second], "Root"), include_str!("codegen/generated.rs"));
+}
+
+#[test]
+fn generated_code_loads_the_samples() {
+
+    assert_eq!(Root::from_json_object(&sample("first.json")), Ok(Root {
+        user_name: String::from("x"),
+        r#type: 1,
+        items: vec![
+            Item { id: 1, tags: Some(vec![String::from("a")]), note: None },
+            Item { id: 2, tags: None, note: Some(String::from("n")) },
+        ],
+        meta: Meta { created_at: true },
+        extra: None,
+    }));
+
+    assert_eq!(Root::from_json_object(&sample("second.json")), Ok(Root {
+        user_name: String::from("y"),
+        r#type: 2,
+        items: Vec::new(),
+        meta: Meta { created_at: false },
+        extra: Some(1.5),
+    }));
+}
+
+#[test]
+fn generated_code_reports_where_a_document_differs() {
+
+    let wrong = parse_str(r#"{"user-name": "z", "type": 3, "items": [{"id": "4"}], "meta": {"createdAt": true}}"#, &ParserOptions::default()).unwrap().unwrap();
+    assert_eq!(Root::from_json_object(&wrong), Err(String::from("/items/0/id: expected an integer")));
+
+    let missing = parse_str(r#"{"user-name": "z", "type": 3, "items": []}"#, &ParserOptions::default()).unwrap().unwrap();
+    assert_eq!(Root::from_json_object(&missing), Err(String::from(": missing member \"meta\"")));
+}
@@ -0,0 +1,163 @@
+// Generated by json_rust::codegen, edit as needed.
+
+#![allow(dead_code)]
+
+use json_rust::escape::unescape_lossy;
+use json_rust::json_object::{JsonObject, Key, ValueType, Iter};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Root {
+    pub user_name: String, // "user-name"
+    pub r#type: i64,
+    pub items: Vec<Item>,
+    pub meta: Meta,
+    pub extra: Option<f64>,
+}
+
+impl Root {
+    /// Loads from a key holding an object, `path` is the JSON Pointer of the key, for error messages.
+    pub fn from_key(key: &Key, path: &str) -> Result<Self, String> {
+        Self::from_members(json_members(key, path)?, path)
+    }
+
+    /// Loads from the members of an object.
+    pub fn from_members(members: Iter<'_>, path: &str) -> Result<Self, String> {
+        let mut member_user_name = None;
+        let mut member_type = None;
+        let mut member_items = None;
+        let mut member_meta = None;
+        let mut member_extra = None;
+
+        for member in members {
+            match unescape_lossy(member.get_name()).as_str() {
+                "user-name" => member_user_name = Some(member),
+                "type" => member_type = Some(member),
+                "items" => member_items = Some(member),
+                "meta" => member_meta = Some(member),
+                "extra" => member_extra = Some(member),
+                _ => {},
+            }
+        }
+
+        Ok(Root {
+            user_name: match member_user_name { Some(key) => json_string(key, &format!("{}/{}", path, "user-name"))?, None => return Err(format!("{}: missing member {:?}", path, "user-name")) },
+            r#type: match member_type { Some(key) => json_i64(key, &format!("{}/{}", path, "type"))?, None => return Err(format!("{}: missing member {:?}", path, "type")) },
+            items: match member_items { Some(key) => json_vec(key, &format!("{}/{}", path, "items"), Item::from_key)?, None => return Err(format!("{}: missing member {:?}", path, "items")) },
+            meta: match member_meta { Some(key) => Meta::from_key(key, &format!("{}/{}", path, "meta"))?, None => return Err(format!("{}: missing member {:?}", path, "meta")) },
+            extra: match member_extra { Some(key) => Some(json_f64(key, &format!("{}/{}", path, "extra"))?), None => None },
+        })
+    }
+}
+
+impl Root {
+    /// Loads from a parsed document.
+    pub fn from_json_object(jobj: &JsonObject) -> Result<Self, String> {
+        Self::from_members(jobj.iter(), "")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item {
+    pub id: i64,
+    pub tags: Option<Vec<String>>,
+    pub note: Option<String>,
+}
+
+impl Item {
+    /// Loads from a key holding an object, `path` is the JSON Pointer of the key, for error messages.
+    pub fn from_key(key: &Key, path: &str) -> Result<Self, String> {
+        Self::from_members(json_members(key, path)?, path)
+    }
+
+    /// Loads from the members of an object.
+    pub fn from_members(members: Iter<'_>, path: &str) -> Result<Self, String> {
+        let mut member_id = None;
+        let mut member_tags = None;
+        let mut member_note = None;
+
+        for member in members {
+            match unescape_lossy(member.get_name()).as_str() {
+                "id" => member_id = Some(member),
+                "tags" => member_tags = Some(member),
+                "note" => member_note = Some(member),
+                _ => {},
+            }
+        }
+
+        Ok(Item {
+            id: match member_id { Some(key) => json_i64(key, &format!("{}/{}", path, "id"))?, None => return Err(format!("{}: missing member {:?}", path, "id")) },
+            tags: match member_tags { Some(key) => Some(json_vec(key, &format!("{}/{}", path, "tags"), json_string)?), None => None },
+            note: match member_note { Some(key) => Some(json_string(key, &format!("{}/{}", path, "note"))?), None => None },
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Meta {
+    pub created_at: bool, // "createdAt"
+}
+
+impl Meta {
+    /// Loads from a key holding an object, `path` is the JSON Pointer of the key, for error messages.
+    pub fn from_key(key: &Key, path: &str) -> Result<Self, String> {
+        Self::from_members(json_members(key, path)?, path)
+    }
+
+    /// Loads from the members of an object.
+    pub fn from_members(members: Iter<'_>, path: &str) -> Result<Self, String> {
+        let mut member_created_at = None;
+
+        for member in members {
+            match unescape_lossy(member.get_name()).as_str() {
+                "createdAt" => member_created_at = Some(member),
+                _ => {},
+            }
+        }
+
+        Ok(Meta {
+            created_at: match member_created_at { Some(key) => json_bool(key, &format!("{}/{}", path, "createdAt"))?, None => return Err(format!("{}: missing member {:?}", path, "createdAt")) },
+        })
+    }
+}
+
+fn json_string(key: &Key, path: &str) -> Result<String, String> {
+    match key.get_value_type() {
+        ValueType::StringType => Ok(unescape_lossy(key.get_value())),
+        _ => Err(format!("{}: expected a string", path)),
+    }
+}
+
+fn json_i64(key: &Key, path: &str) -> Result<i64, String> {
+    match key.get_value_type() {
+        ValueType::NumberType => key.get_value().trim().parse().map_err(|_| format!("{}: expected an integer", path)),
+        _ => Err(format!("{}: expected an integer", path)),
+    }
+}
+
+fn json_f64(key: &Key, path: &str) -> Result<f64, String> {
+    match key.get_value_type() {
+        ValueType::NumberType => key.get_value().trim().parse().map_err(|_| format!("{}: expected a number", path)),
+        _ => Err(format!("{}: expected a number", path)),
+    }
+}
+
+fn json_bool(key: &Key, path: &str) -> Result<bool, String> {
+    match key.get_value_type() {
+        ValueType::BooleanType => Ok(key.get_value().trim() == "true"),
+        _ => Err(format!("{}: expected a boolean", path)),
+    }
+}
+
+fn json_members<'a>(key: &'a Key, path: &str) -> Result<Iter<'a>, String> {
+    match key.get_value_type() {
+        ValueType::ObjectType => Ok(key.iter()),
+        _ => Err(format!("{}: expected an object", path)),
+    }
+}
+
+fn json_vec<T>(key: &Key, path: &str, load: impl Fn(&Key, &str) -> Result<T, String>) -> Result<Vec<T>, String> {
+    match key.get_value_type() {
+        ValueType::ArrayType => key.iter().enumerate().map(|(index, element)| load(element, &format!("{}/{}", path, index))).collect(),
+        _ => Err(format!("{}: expected an array", path)),
+    }
+}